@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+/// Number of times a conditional branch has gone each way
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct BranchCoverage {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+impl BranchCoverage {
+    /// Check if the branch has gone both ways
+    #[must_use]
+    pub const fn is_covered(&self) -> bool {
+        self.taken > 0 && self.not_taken > 0
+    }
+}
+
+/// Addresses executed and branches taken while collecting the coverage
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Coverage {
+    /// Number of times the instruction at each address has been executed
+    pub hits: BTreeMap<u16, u64>,
+    /// Directions of the conditional branches, by their address. The BR
+    /// instructions that are always or never taken are not included
+    pub branches: BTreeMap<u16, BranchCoverage>,
+}
+
+impl Coverage {
+    /// Count an execution of the instruction at `address`
+    pub fn record_instruction(&mut self, address: u16) {
+        *self.hits.entry(address).or_default() += 1;
+    }
+
+    /// Count the direction of the conditional branch at `address`
+    pub fn record_branch(&mut self, address: u16, taken: bool) {
+        let branch = self.branches.entry(address).or_default();
+        if taken {
+            branch.taken += 1;
+        } else {
+            branch.not_taken += 1;
+        }
+    }
+}
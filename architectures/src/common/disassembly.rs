@@ -0,0 +1,54 @@
+use super::Operand;
+
+use std::collections::BTreeMap;
+
+/// Labels of the addresses, used to replace the addresses in the disassembly
+pub type SymbolTable = BTreeMap<u16, String>;
+
+/// Instruction decoded by a disassembler
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Disassembly<I> {
+    pub instruction: I,
+    /// Absolute address referenced by the instruction, if any
+    pub target: Option<u16>,
+    pub text: String,
+}
+
+/// Get the label of `address`, or the address itself if it has no label
+pub fn label(address: u16, symbols: Option<&SymbolTable>) -> String {
+    symbols
+        .and_then(|symbols| symbols.get(&address))
+        .map_or_else(|| format!("x{address:04X}"), Clone::clone)
+}
+
+/// Get the name of a General Purpose Register
+pub fn register_name<G: Clone>(gpr: &G) -> String
+where
+    u8: From<G>,
+{
+    format!("R{}", u8::from(gpr.clone()))
+}
+
+/// Get the text of the second source operand of ADD and AND
+pub fn operand_text<G: Clone>(operand: &Operand<G>) -> String
+where
+    u8: From<G>,
+{
+    match operand {
+        Operand::Register(gpr) => register_name(gpr),
+        Operand::Immediate(value) => format!("#{value}"),
+    }
+}
+
+/// Get the name of the trap routines of the standard operating system
+pub const fn trap_alias(vector: u8) -> Option<&'static str> {
+    match vector {
+        0x20 => Some("GETC"),
+        0x21 => Some("OUT"),
+        0x22 => Some("PUTS"),
+        0x23 => Some("IN"),
+        0x24 => Some("PUTSP"),
+        0x25 => Some("HALT"),
+        _ => None,
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+
+/// Second source operand of ADD and AND, with the General Purpose Registers
+/// `G` of the architecture
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Operand<G> {
+    Register(G),
+    /// Sign-extended 5-bit immediate value
+    Immediate(i16),
+}
+
+/// Get the General Purpose Register encoded in `word` starting from `offset`
+pub fn gpr<G>(word: u16, offset: u8) -> G
+where
+    G: TryFrom<usize>,
+    G::Error: Debug,
+{
+    G::try_from(usize::from((word >> offset) & 0b111))
+        .expect("The register should be between 0 and 7")
+}
+
+/// Sign-extend the lowest `bits` bits of `word`
+pub const fn sign_extend(word: u16, bits: u32) -> i16 {
+    let shift = 16 - bits;
+    i16::from_ne_bytes((word << shift).to_ne_bytes()) >> shift
+}
+
+/// Encode the General Purpose Register `gpr` starting from `offset`
+pub fn register<G: Clone>(gpr: &G, offset: u8) -> u16
+where
+    u8: From<G>,
+{
+    u16::from(u8::from(gpr.clone())) << offset
+}
+
+/// Encode the second source operand of ADD and AND
+pub fn operand<G: Clone>(operand: &Operand<G>) -> u16
+where
+    u8: From<G>,
+{
+    match operand {
+        Operand::Register(gpr) => register(gpr, 0),
+        Operand::Immediate(value) => 1 << 5 | field(*value, 5),
+    }
+}
+
+/// Truncate `value` to its lowest `bits` bits
+pub const fn field(value: i16, bits: u32) -> u16 {
+    u16::from_ne_bytes(value.to_ne_bytes()) & ((1 << bits) - 1)
+}
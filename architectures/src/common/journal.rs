@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+/// Changes made by an instruction: the registers `R` before it, and the
+/// cells of the memory it has written with their previous values
+pub struct JournalEntry<R> {
+    pub registers: R,
+    pub memory: Vec<(u16, u16)>,
+}
+
+/// Journal of the last instructions executed, used to undo them. It keeps
+/// at most `depth` entries, dropping the oldest ones
+pub struct Journal<R> {
+    depth: usize,
+    entries: VecDeque<JournalEntry<R>>,
+    recording: Option<JournalEntry<R>>,
+}
+
+impl<R> Default for Journal<R> {
+    fn default() -> Self {
+        Self {
+            depth: 0,
+            entries: VecDeque::new(),
+            recording: None,
+        }
+    }
+}
+
+impl<R> Journal<R> {
+    /// Change the maximum number of entries, dropping the oldest ones if
+    /// there are too many. A depth of 0 disables the journal
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.entries.len() > depth {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Check if the journal is enabled
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// Start recording an instruction, executed with `registers`
+    pub fn begin(&mut self, registers: R) {
+        if self.is_enabled() {
+            self.recording = Some(JournalEntry {
+                registers,
+                memory: Vec::new(),
+            });
+        }
+    }
+
+    /// Record the previous value of a memory cell written by the instruction
+    /// being recorded
+    pub fn record_memory(&mut self, address: u16, data: u16) {
+        if let Some(entry) = &mut self.recording {
+            entry.memory.push((address, data));
+        }
+    }
+
+    /// Stop recording the instruction and add it to the journal
+    pub fn end(&mut self) {
+        if let Some(entry) = self.recording.take() {
+            if self.entries.len() == self.depth {
+                self.entries.pop_front();
+            }
+            self.entries.push_back(entry);
+        }
+    }
+
+    /// Stop recording the instruction, without adding it to the journal
+    pub fn discard(&mut self) {
+        self.recording = None;
+    }
+
+    /// Remove the last entry from the journal
+    pub fn pop(&mut self) -> Option<JournalEntry<R>> {
+        self.entries.pop_back()
+    }
+
+    /// Get the number of entries in the journal
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the journal is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -1,9 +1,74 @@
+use crate::devices::{Bus, Device, DeviceRequest};
+
+use std::ops::RangeInclusive;
+
+/// Memory of 2^16 words of 16 bits, with a bus of memory-mapped devices. The
+/// addresses mapped to a device are read and written through it, while
+/// indexing the memory always accesses the cells
 #[derive(Debug)]
-pub struct Memory16x16(Box<[u16; 2_usize.pow(16)]>);
+pub struct Memory16x16 {
+    cells: Box<[u16; 2_usize.pow(16)]>,
+    bus: Bus,
+}
 
 impl Default for Memory16x16 {
     fn default() -> Self {
-        Self(Box::new([0u16; 2_usize.pow(16)]))
+        Self {
+            cells: vec![0u16; 2_usize.pow(16)]
+                .into_boxed_slice()
+                .try_into()
+                .expect("The memory should be 2^16 words long"),
+            bus: Bus::default(),
+        }
+    }
+}
+
+impl Memory16x16 {
+    /// Read the cell at `address`, or the device mapped to it
+    pub fn read(&mut self, address: u16) -> u16 {
+        match self.bus.device(address) {
+            Some(device) => device.read(address),
+            None => self.cells[address as usize],
+        }
+    }
+
+    /// Read the cell at `address`, or the device mapped to it without its side
+    /// effects
+    #[must_use]
+    pub fn peek(&self, address: u16) -> u16 {
+        self.bus
+            .peek(address)
+            .unwrap_or(self.cells[address as usize])
+    }
+
+    /// Write the cell at `address`, or the device mapped to it
+    pub fn write(&mut self, address: u16, data: u16) {
+        match self.bus.device(address) {
+            Some(device) => device.write(address, data),
+            None => self.cells[address as usize] = data,
+        }
+    }
+
+    /// Map `device` to `addresses`, taking precedence over the devices
+    /// already mapped to the same addresses
+    pub fn map(&mut self, addresses: RangeInclusive<u16>, device: Box<dyn Device>) {
+        self.bus.map(addresses, device);
+    }
+
+    /// Get all the cells, ignoring the devices
+    #[must_use]
+    pub const fn cells(&self) -> &[u16; 2_usize.pow(16)] {
+        &self.cells
+    }
+
+    /// Replace all the cells, ignoring the devices
+    pub fn set_cells(&mut self, cells: &[u16; 2_usize.pow(16)]) {
+        self.cells.copy_from_slice(cells);
+    }
+
+    /// Update the devices, returning their requests
+    pub fn tick(&mut self) -> Vec<DeviceRequest> {
+        self.bus.tick()
     }
 }
 
@@ -11,12 +76,12 @@ impl std::ops::Index<u16> for Memory16x16 {
     type Output = u16;
 
     fn index(&self, idx: u16) -> &Self::Output {
-        &self.0[idx as usize]
+        &self.cells[idx as usize]
     }
 }
 
 impl std::ops::IndexMut<u16> for Memory16x16 {
     fn index_mut(&mut self, idx: u16) -> &mut Self::Output {
-        &mut self.0[idx as usize]
+        &mut self.cells[idx as usize]
     }
 }
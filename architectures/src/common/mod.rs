@@ -1,9 +1,29 @@
 mod condition_code;
+mod coverage;
+mod disassembly;
+mod instruction;
+mod journal;
 mod memory_16x16;
+mod profile;
+mod snapshot;
+mod statistics;
+mod trace;
 mod watcher_storage;
 
 pub use condition_code::ConditionCode;
+pub use coverage::{BranchCoverage, Coverage};
+pub(crate) use disassembly::{label, operand_text, register_name, trap_alias};
+pub use disassembly::{Disassembly, SymbolTable};
+pub use instruction::Operand;
+pub(crate) use instruction::{field, gpr, operand, register, sign_extend};
+pub use journal::{Journal, JournalEntry};
 pub use memory_16x16::Memory16x16;
+pub use profile::{Profile, StackProfile};
+pub use snapshot::SnapshotMemory;
+pub(crate) use snapshot::{read_snapshot, write_snapshot};
+pub use statistics::{Counter, Statistics, INSTRUCTION_CYCLES, MEMORY_ACCESS_CYCLES};
+pub use trace::{MemoryAccess, TraceRecord, Tracer};
 pub use watcher_storage::{
-    ConditionCodeWatchersStorage, MemoryWatchersStorage, RegisterWatchersStorage,
+    ConditionCodeWatchersStorage, MemoryRangeWatchersStorage, MemoryWatchersStorage,
+    RangeWatchersStorage, RegisterWatchersStorage, WatchersStorage,
 };
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+/// Instructions executed with a call stack, and calls that have created it
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct StackProfile {
+    /// Instructions executed by the innermost routine of the stack
+    pub instructions: u64,
+    /// Times the innermost routine has been called by the rest of the stack
+    pub calls: u64,
+}
+
+/// Node of the calling context tree
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct Node {
+    /// Address of the routine, or `None` for the root
+    routine: Option<u16>,
+    parent: usize,
+    children: BTreeMap<u16, usize>,
+    profile: StackProfile,
+}
+
+/// Instructions executed by each call stack.
+///
+/// It's a shadow call stack of the subroutines, trap routines and service
+/// routines entered by the processor, with the tree of the call stacks seen.
+/// The instructions executed outside any routine belong to the root
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Profile {
+    nodes: Vec<Node>,
+    /// Node of each routine being executed, with the address it returns to
+    frames: Vec<(usize, u16)>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node {
+                routine: None,
+                parent: 0,
+                children: BTreeMap::new(),
+                profile: StackProfile::default(),
+            }],
+            frames: Vec::new(),
+        }
+    }
+}
+
+impl Profile {
+    /// Count an instruction executed by the current routine
+    pub fn record_instruction(&mut self) {
+        let node = self.current();
+        self.nodes[node].profile.instructions += 1;
+    }
+
+    /// Enter the routine at `routine`, that returns to `return_address`
+    pub fn record_call(&mut self, routine: u16, return_address: u16) {
+        let parent = self.current();
+        let node = if let Some(node) = self.nodes[parent].children.get(&routine) {
+            *node
+        } else {
+            self.nodes.push(Node {
+                routine: Some(routine),
+                parent,
+                children: BTreeMap::new(),
+                profile: StackProfile::default(),
+            });
+            let node = self.nodes.len() - 1;
+            self.nodes[parent].children.insert(routine, node);
+            node
+        };
+
+        self.nodes[node].profile.calls += 1;
+        self.frames.push((node, return_address));
+    }
+
+    /// Return to `address`, leaving the innermost routine that returns there
+    /// and the ones it has called. Nothing is left if no routine returns
+    /// there, like when the return is used as a jump
+    pub fn record_return(&mut self, address: u16) {
+        if let Some(frame) = self
+            .frames
+            .iter()
+            .rposition(|(_, return_address)| *return_address == address)
+        {
+            self.frames.truncate(frame);
+        }
+    }
+
+    /// Get the call stacks, from the outermost routine to the innermost one,
+    /// that have executed an instruction or made a call. The empty stack is
+    /// the one of the instructions executed outside any routine
+    #[must_use]
+    pub fn stacks(&self) -> BTreeMap<Vec<u16>, StackProfile> {
+        let mut stacks = BTreeMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.profile == StackProfile::default() {
+                continue;
+            }
+
+            // Walk the tree up to the root
+            let mut stack = Vec::new();
+            let mut current = index;
+            while let Some(routine) = self.nodes[current].routine {
+                stack.push(routine);
+                current = self.nodes[current].parent;
+            }
+            stack.reverse();
+            stacks.insert(stack, node.profile);
+        }
+
+        stacks
+    }
+
+    /// Get the node of the routine being executed
+    fn current(&self) -> usize {
+        self.frames.last().map_or(0, |(node, _)| *node)
+    }
+}
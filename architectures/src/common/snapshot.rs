@@ -0,0 +1,90 @@
+//! Snapshot file format, made of big-endian 16-bit words:
+//!
+//! | Words | Content                                             |
+//! |-------|-----------------------------------------------------|
+//! | 4     | The magic bytes `LEMUSNAP`                          |
+//! | 1     | The version of the format, currently 1              |
+//! | 1     | The architecture, 2 for the LC-2 and 3 for the LC-3 |
+//! | 1     | The number of registers `N`                         |
+//! | `N`   | The registers, in the order of the architecture     |
+//! | 65536 | The memory                                          |
+
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"LEMUSNAP";
+const VERSION: u16 = 1;
+
+/// Memory of a snapshot
+pub type SnapshotMemory = Box<[u16; 2_usize.pow(16)]>;
+
+/// Write a snapshot of `architecture` in the snapshot file format
+pub fn write_snapshot<W: Write>(
+    mut writer: W,
+    architecture: u16,
+    registers: &[u16],
+    memory: &[u16; 2_usize.pow(16)],
+) -> io::Result<()> {
+    let register_count = u16::try_from(registers.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many registers"))?;
+
+    // Write the header, the registers and the memory
+    let mut bytes = Vec::with_capacity(MAGIC.len() + (registers.len() + memory.len() + 3) * 2);
+    bytes.extend_from_slice(MAGIC);
+    for word in [VERSION, architecture, register_count]
+        .iter()
+        .chain(registers)
+        .chain(memory.iter())
+    {
+        bytes.extend_from_slice(&word.to_be_bytes());
+    }
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+/// Read a snapshot of `architecture` with `register_count` registers from
+/// the snapshot file format, returning the registers and the memory
+pub fn read_snapshot<R: Read>(
+    mut reader: R,
+    architecture: u16,
+    register_count: usize,
+) -> io::Result<(Vec<u16>, SnapshotMemory)> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    // Check the header
+    let mut header = [0u8; MAGIC.len() + 6];
+    reader.read_exact(&mut header)?;
+    let (magic, header) = header.split_at(MAGIC.len());
+    if magic != MAGIC {
+        return Err(invalid("The file is not a snapshot".to_owned()));
+    }
+    let [version, snapshot_architecture, snapshot_register_count] =
+        [0, 2, 4].map(|i| u16::from_be_bytes([header[i], header[i + 1]]));
+    if version != VERSION {
+        return Err(invalid(format!("Unsupported snapshot version {version}")));
+    }
+    if snapshot_architecture != architecture {
+        return Err(invalid(format!(
+            "The snapshot is of an LC-{snapshot_architecture}, not of an LC-{architecture}"
+        )));
+    }
+    if usize::from(snapshot_register_count) != register_count {
+        return Err(invalid(format!(
+            "The snapshot has {snapshot_register_count} registers instead of {register_count}"
+        )));
+    }
+
+    // Read the registers and the memory
+    let mut bytes = vec![0u8; (register_count + 2_usize.pow(16)) * 2];
+    reader.read_exact(&mut bytes)?;
+    let mut words = bytes
+        .chunks_exact(2)
+        .map(|word| u16::from_be_bytes([word[0], word[1]]));
+    let registers = words.by_ref().take(register_count).collect();
+    let memory = words
+        .collect::<Vec<u16>>()
+        .into_boxed_slice()
+        .try_into()
+        .expect("The memory should be 2^16 words long");
+
+    Ok((registers, memory))
+}
@@ -0,0 +1,129 @@
+use crate::WatcherType;
+
+use std::collections::BTreeMap;
+
+/// Cycles taken by an instruction besides its memory accesses.
+///
+/// They are the states of the textbook state machine that don't access the
+/// memory: two to fetch the instruction, one to decode it and one to execute it
+pub const INSTRUCTION_CYCLES: u64 = 4;
+/// Cycles taken by each memory access, including the fetch of the instruction
+pub const MEMORY_ACCESS_CYCLES: u64 = 5;
+
+/// Counters of the execution, that are updated by `step_instruction` and
+/// by the interrupts
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Statistics {
+    /// Instructions executed, including the ones that have raised an
+    /// exception
+    pub instructions: u64,
+    /// Instructions executed for each opcode, indexed by the 4 most
+    /// significant bits of the instruction
+    pub opcodes: [u64; 16],
+    /// Memory cells read by the instructions, including their fetch
+    pub memory_reads: u64,
+    /// Memory cells written by the instructions
+    pub memory_writes: u64,
+    pub branches_taken: u64,
+    pub branches_not_taken: u64,
+    /// TRAP instructions executed for each trap vector
+    pub traps: BTreeMap<u8, u64>,
+    /// Interrupts accepted by the processor
+    pub interrupts: u64,
+    /// Cycles modelled for the instructions, counting `INSTRUCTION_CYCLES`
+    /// for each of them and `MEMORY_ACCESS_CYCLES` for each of their memory
+    /// accesses
+    pub cycles: u64,
+}
+
+/// Instruction being counted, with the memory accesses it has made
+#[derive(Default)]
+struct Accesses {
+    instruction: Option<u16>,
+    reads: u64,
+    writes: u64,
+}
+
+/// Recorder of the statistics of the instructions executed
+#[derive(Default)]
+pub struct Counter {
+    statistics: Statistics,
+    counting: Option<Accesses>,
+}
+
+// Mutable references in constant functions need Rust 1.83
+#[allow(clippy::missing_const_for_fn)]
+impl Counter {
+    /// Start counting the memory accesses of an instruction
+    pub fn begin(&mut self) {
+        self.counting = Some(Accesses::default());
+    }
+
+    /// Remember the instruction fetched, which is counted by `end`
+    pub fn fetched(&mut self, instruction: u16) {
+        if let Some(accesses) = &mut self.counting {
+            accesses.instruction = Some(instruction);
+        }
+    }
+
+    /// Count an access to the memory, if an instruction is being executed
+    pub fn record_memory(&mut self, access_type: &WatcherType) {
+        if let Some(accesses) = &mut self.counting {
+            match access_type {
+                WatcherType::OnRead => accesses.reads += 1,
+                WatcherType::OnWrite => accesses.writes += 1,
+            }
+        }
+    }
+
+    /// Count a conditional branch
+    pub fn record_branch(&mut self, taken: bool) {
+        if taken {
+            self.statistics.branches_taken += 1;
+        } else {
+            self.statistics.branches_not_taken += 1;
+        }
+    }
+
+    /// Count a TRAP instruction
+    pub fn record_trap(&mut self, vector: u8) {
+        *self.statistics.traps.entry(vector).or_default() += 1;
+    }
+
+    /// Count an accepted interrupt
+    pub fn record_interrupt(&mut self) {
+        self.statistics.interrupts += 1;
+    }
+
+    /// Stop counting the instruction, adding it and its memory accesses to
+    /// the statistics. If the instruction couldn't be fetched only the memory
+    /// accesses are added
+    pub fn end(&mut self) {
+        if let Some(accesses) = self.counting.take() {
+            let statistics = &mut self.statistics;
+            if let Some(instruction) = accesses.instruction {
+                statistics.instructions += 1;
+                statistics.opcodes[usize::from(instruction >> 12)] += 1;
+            }
+            statistics.memory_reads += accesses.reads;
+            statistics.memory_writes += accesses.writes;
+            statistics.cycles +=
+                INSTRUCTION_CYCLES + (accesses.reads + accesses.writes) * MEMORY_ACCESS_CYCLES;
+        }
+    }
+
+    /// Stop counting the instruction, that couldn't be executed
+    pub fn discard(&mut self) {
+        self.counting = None;
+    }
+
+    #[must_use]
+    pub const fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Set all the counters to zero
+    pub fn reset(&mut self) {
+        self.statistics = Statistics::default();
+    }
+}
@@ -0,0 +1,120 @@
+use crate::WatcherType;
+
+/// Access to a memory cell made by an instruction
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct MemoryAccess {
+    pub address: u16,
+    /// Value read from the cell, or written into it
+    pub data: u16,
+    /// Either `OnRead` or `OnWrite`
+    pub access_type: WatcherType,
+}
+
+/// Record of an executed instruction, with the registers `R` it has written
+/// and the Condition Code `C` after it. The fetch of the instruction and the
+/// increment of the Program Counter are not recorded
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TraceRecord<R, C> {
+    /// Address of the instruction
+    pub address: u16,
+    /// Value of the Instruction Register
+    pub instruction: u16,
+    /// Registers written by the instruction with their new values, in the
+    /// order in which they have been written
+    pub registers: Vec<(R, u16)>,
+    /// Memory accesses made by the instruction, in the order in which they
+    /// have been made
+    pub memory: Vec<MemoryAccess>,
+    pub condition_code: C,
+}
+
+/// Recorder of the last instruction executed, used for tracing
+pub struct Tracer<R, C> {
+    enabled: bool,
+    recording: Option<TraceRecord<R, C>>,
+    last: Option<TraceRecord<R, C>>,
+}
+
+impl<R, C> Default for Tracer<R, C> {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            recording: None,
+            last: None,
+        }
+    }
+}
+
+impl<R, C> Tracer<R, C> {
+    /// Enable or disable the tracer, forgetting the last record when disabled
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.recording = None;
+            self.last = None;
+        }
+    }
+
+    /// Start recording the instruction at `address`
+    pub fn begin(&mut self, address: u16)
+    where
+        C: Default,
+    {
+        if self.enabled {
+            self.recording = Some(TraceRecord {
+                address,
+                instruction: 0,
+                registers: Vec::new(),
+                memory: Vec::new(),
+                condition_code: C::default(),
+            });
+        }
+    }
+
+    /// Forget the accesses recorded while fetching `instruction`
+    pub fn fetched(&mut self, instruction: u16) {
+        if let Some(record) = &mut self.recording {
+            record.instruction = instruction;
+            record.registers.clear();
+            record.memory.clear();
+        }
+    }
+
+    /// Record the write of a register
+    pub fn record_register(&mut self, register: R, data: u16) {
+        if let Some(record) = &mut self.recording {
+            record.registers.push((register, data));
+        }
+    }
+
+    /// Record an access to the memory
+    pub fn record_memory(&mut self, address: u16, data: u16, access_type: WatcherType) {
+        if let Some(record) = &mut self.recording {
+            record.memory.push(MemoryAccess {
+                address,
+                data,
+                access_type,
+            });
+        }
+    }
+
+    /// Stop recording the instruction, with the Condition Code after it
+    pub fn end(&mut self, condition_code: C) {
+        if let Some(mut record) = self.recording.take() {
+            record.condition_code = condition_code;
+            self.last = Some(record);
+        }
+    }
+
+    /// Stop recording the instruction, that couldn't be executed
+    pub fn discard(&mut self) {
+        self.recording = None;
+        self.last = None;
+    }
+
+    /// Get the record of the last instruction executed
+    #[must_use]
+    pub const fn last(&self) -> Option<&TraceRecord<R, C>> {
+        self.last.as_ref()
+    }
+}
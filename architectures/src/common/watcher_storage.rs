@@ -1,9 +1,118 @@
-use crate::{common::ConditionCode, WatcherType};
-use std::collections::BTreeMap;
+use crate::{common::ConditionCode, WatcherId, WatcherType};
+use std::collections::{btree_map, BTreeMap};
+use std::ops::RangeInclusive;
 
-pub type RegisterWatchersStorage<T> = BTreeMap<(T, WatcherType), Box<dyn Fn(u16)>>;
-pub type MemoryWatchersStorage<T> = BTreeMap<(T, WatcherType), Box<dyn Fn(u16)>>;
+pub type RegisterWatchersStorage<T> = WatchersStorage<(T, WatcherType), u16>;
+pub type MemoryWatchersStorage<T> = WatchersStorage<(T, WatcherType), u16>;
+pub type MemoryRangeWatchersStorage<T> = RangeWatchersStorage<T, u16>;
+pub type ConditionCodeWatchersStorage = WatchersStorage<WatcherType, ConditionCode>;
 
-// 0 => WatcherType::OnWrite,
-// 1 => WatcherType::OnRead,
-pub type ConditionCodeWatchersStorage = [Option<Box<dyn Fn(ConditionCode)>>; 2];
+type Watchers<D> = Vec<(WatcherId, Box<dyn Fn(D)>)>;
+
+/// Watchers grouped by what they watch. The watchers of the same key are
+/// called in the order in which they have been added
+pub struct WatchersStorage<K, D> {
+    watchers: BTreeMap<K, Watchers<D>>,
+}
+
+impl<K, D> Default for WatchersStorage<K, D> {
+    fn default() -> Self {
+        Self {
+            watchers: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, D: Clone> WatchersStorage<K, D> {
+    /// Add a watcher for `key` after the ones already present, returning its
+    /// id
+    pub fn insert(&mut self, key: K, function: Box<dyn Fn(D)>) -> WatcherId {
+        let id = WatcherId::unique();
+        self.watchers.entry(key).or_default().push((id, function));
+        id
+    }
+
+    /// Remove the watcher with the given id, returning `false` if it doesn't
+    /// exist
+    pub fn remove(&mut self, id: WatcherId) -> bool {
+        let mut removed = false;
+        self.watchers.retain(|_, watchers| {
+            let length = watchers.len();
+            watchers.retain(|(watcher_id, _)| *watcher_id != id);
+            removed |= watchers.len() != length;
+            !watchers.is_empty()
+        });
+        removed
+    }
+
+    /// Call all the watchers of `key`
+    pub fn call(&self, key: &K, data: &D) {
+        for (_, function) in self.watchers.get(key).into_iter().flatten() {
+            function(data.clone());
+        }
+    }
+
+    /// Get the keys that have at least a watcher
+    pub fn keys(&self) -> btree_map::Keys<'_, K, Watchers<D>> {
+        self.watchers.keys()
+    }
+}
+
+type RangeWatcher<A, D> = (WatcherId, RangeInclusive<A>, WatcherType, Box<dyn Fn(A, D)>);
+
+/// Watchers of ranges of addresses, that receive the accessed address along
+/// with the data. The watchers are called in the order in which they have
+/// been added
+pub struct RangeWatchersStorage<A, D> {
+    watchers: Vec<RangeWatcher<A, D>>,
+}
+
+impl<A, D> Default for RangeWatchersStorage<A, D> {
+    fn default() -> Self {
+        Self {
+            watchers: Vec::new(),
+        }
+    }
+}
+
+impl<A: PartialOrd + Clone, D: Clone> RangeWatchersStorage<A, D> {
+    /// Add a watcher for `addresses` after the ones already present,
+    /// returning its id
+    pub fn insert(
+        &mut self,
+        addresses: RangeInclusive<A>,
+        watcher_type: WatcherType,
+        function: Box<dyn Fn(A, D)>,
+    ) -> WatcherId {
+        let id = WatcherId::unique();
+        self.watchers.push((id, addresses, watcher_type, function));
+        id
+    }
+
+    /// Remove the watcher with the given id, returning `false` if it doesn't
+    /// exist
+    pub fn remove(&mut self, id: WatcherId) -> bool {
+        let length = self.watchers.len();
+        self.watchers.retain(|(watcher_id, ..)| *watcher_id != id);
+        self.watchers.len() != length
+    }
+
+    /// Call all the watchers of type `watcher_type` whose range contains
+    /// `address`
+    pub fn call(&self, address: &A, watcher_type: &WatcherType, data: &D) {
+        for (_, addresses, kind, function) in &self.watchers {
+            if kind == watcher_type && addresses.contains(address) {
+                function(address.clone(), data.clone());
+            }
+        }
+    }
+
+    /// Get the ranges that have a watcher, with the type of the watcher
+    #[must_use]
+    pub fn ranges(&self) -> Vec<(&RangeInclusive<A>, &WatcherType)> {
+        self.watchers
+            .iter()
+            .map(|(_, addresses, watcher_type, _)| (addresses, watcher_type))
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+use super::{Device, DeviceRequest};
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Devices with the range of addresses they're mapped to. When two ranges
+/// overlap the last device mapped handles the addresses in common
+#[derive(Default)]
+pub struct Bus {
+    devices: Vec<(RangeInclusive<u16>, Box<dyn Device>)>,
+}
+
+impl Bus {
+    /// Map `device` to `addresses`
+    pub fn map(&mut self, addresses: RangeInclusive<u16>, device: Box<dyn Device>) {
+        self.devices.push((addresses, device));
+    }
+
+    /// Get the device mapped to `address`, if any
+    pub fn device(&mut self, address: u16) -> Option<&mut dyn Device> {
+        self.devices
+            .iter_mut()
+            .rev()
+            .find(|(addresses, _)| addresses.contains(&address))
+            .map(|(_, device)| device.as_mut() as &mut dyn Device)
+    }
+
+    /// Get what reading the device mapped to `address` would return, without
+    /// its side effects, if a device is mapped to it
+    #[must_use]
+    pub fn peek(&self, address: u16) -> Option<u16> {
+        self.devices
+            .iter()
+            .rev()
+            .find(|(addresses, _)| addresses.contains(&address))
+            .map(|(_, device)| device.peek(address))
+    }
+
+    /// Update all the devices, returning their requests in the order in which
+    /// the devices have been mapped
+    pub fn tick(&mut self) -> Vec<DeviceRequest> {
+        self.devices
+            .iter_mut()
+            .filter_map(|(_, device)| device.tick())
+            .collect()
+    }
+}
+
+impl fmt::Debug for Bus {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list()
+            .entries(self.devices.iter().map(|(addresses, _)| addresses))
+            .finish()
+    }
+}
@@ -0,0 +1,66 @@
+use super::Device;
+
+use std::io::Write;
+
+/// Display that writes the characters to an output stream
+pub struct Display {
+    status_address: u16,
+    data_address: u16,
+    packed: bool,
+    output: Box<dyn Write>,
+}
+
+impl Display {
+    /// Create a display with the Display Status Register at `status_address`
+    /// and the Display Data Register at `data_address`, writing the
+    /// characters to `output`. If `packed` is set, the high byte of the Data
+    /// Register is printed after the low one, unless it's zero
+    #[must_use]
+    pub fn new(
+        status_address: u16,
+        data_address: u16,
+        packed: bool,
+        output: Box<dyn Write>,
+    ) -> Self {
+        Self {
+            status_address,
+            data_address,
+            packed,
+            output,
+        }
+    }
+}
+
+impl Device for Display {
+    fn read(&mut self, address: u16) -> u16 {
+        self.peek(address)
+    }
+
+    fn peek(&self, address: u16) -> u16 {
+        // The display is always ready
+        if address == self.status_address {
+            0x8000
+        } else {
+            0x0000
+        }
+    }
+
+    fn write(&mut self, address: u16, data: u16) {
+        if address != self.data_address {
+            return;
+        }
+
+        // Get the characters to print
+        let [high, low] = data.to_be_bytes();
+        let mut text = String::from(char::from(low));
+        if self.packed && high != 0 {
+            text.push(char::from(high));
+        }
+
+        // The characters are lost if the output can't be written
+        let _ = self
+            .output
+            .write_all(text.as_bytes())
+            .and_then(|()| self.output.flush());
+    }
+}
@@ -0,0 +1,103 @@
+use super::{Device, DeviceRequest};
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+/// Keyboard that reads the characters from a shared input buffer
+///
+/// If it has an interrupt and the bit 14 (Interrupt Enable) of the Keyboard
+/// Status Register is set, it requests the interrupt while a character is
+/// ready. Without an interrupt the Interrupt Enable bit stays clear
+pub struct Keyboard {
+    status_address: u16,
+    data_address: u16,
+    buffer: Arc<Mutex<VecDeque<u8>>>,
+    data: u16,
+    interrupt: Option<u16>,
+    interrupt_enable: bool,
+}
+
+impl Keyboard {
+    /// Create a keyboard with the Keyboard Status Register at
+    /// `status_address` and the Keyboard Data Register at `data_address`,
+    /// reading the characters from `buffer`
+    #[must_use]
+    pub const fn new(
+        status_address: u16,
+        data_address: u16,
+        buffer: Arc<Mutex<VecDeque<u8>>>,
+    ) -> Self {
+        Self {
+            status_address,
+            data_address,
+            buffer,
+            data: 0,
+            interrupt: None,
+            interrupt_enable: false,
+        }
+    }
+
+    /// Let the keyboard request an interrupt, passing `data` to
+    /// `Architecture::interrupt`
+    #[must_use]
+    pub const fn with_interrupt(mut self, data: u16) -> Self {
+        self.interrupt = Some(data);
+        self
+    }
+
+    fn buffer(&self) -> MutexGuard<'_, VecDeque<u8>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Device for Keyboard {
+    fn read(&mut self, address: u16) -> u16 {
+        // Reading the Keyboard Data Register removes the first character from
+        // the buffer, or returns the last one if the buffer is empty
+        if address == self.data_address {
+            let byte = self.buffer().pop_front();
+            if let Some(byte) = byte {
+                self.data = u16::from(byte);
+            }
+            return self.data;
+        }
+
+        self.peek(address)
+    }
+
+    fn peek(&self, address: u16) -> u16 {
+        // The Keyboard Status Register is ready when there's a character in
+        // the buffer
+        if address == self.status_address {
+            let ready = !self.buffer().is_empty();
+            return u16::from(ready) << 15 | u16::from(self.interrupt_enable) << 14;
+        }
+
+        if address == self.data_address {
+            return self
+                .buffer()
+                .front()
+                .map_or(self.data, |byte| u16::from(*byte));
+        }
+
+        0x0000
+    }
+
+    fn write(&mut self, address: u16, data: u16) {
+        // Only the Interrupt Enable bit can be written, and only if the
+        // keyboard has an interrupt
+        if address == self.status_address && self.interrupt.is_some() {
+            self.interrupt_enable = data & 0x4000 != 0;
+        }
+    }
+
+    fn tick(&mut self) -> Option<DeviceRequest> {
+        if !self.interrupt_enable || self.buffer().is_empty() {
+            return None;
+        }
+
+        self.interrupt.map(DeviceRequest::Interrupt)
+    }
+}
@@ -0,0 +1,35 @@
+use super::Device;
+
+/// Machine Control Register, whose bit 15 is set while the processor is
+/// running
+pub struct MachineControl {
+    register: u16,
+}
+
+impl MachineControl {
+    /// Create a Machine Control Register with the processor running
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { register: 0x8000 }
+    }
+}
+
+impl Default for MachineControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Device for MachineControl {
+    fn read(&mut self, address: u16) -> u16 {
+        self.peek(address)
+    }
+
+    fn peek(&self, _address: u16) -> u16 {
+        self.register
+    }
+
+    fn write(&mut self, _address: u16, data: u16) {
+        self.register = data;
+    }
+}
@@ -0,0 +1,43 @@
+mod bus;
+mod display;
+mod keyboard;
+mod machine_control;
+mod timer;
+
+pub use bus::Bus;
+pub use display::Display;
+pub use keyboard::Keyboard;
+pub use machine_control::MachineControl;
+pub use timer::Timer;
+
+/// Request made by a device to the processor it's attached to
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum DeviceRequest {
+    /// Interrupt the processor, with the same data taken by
+    /// `Architecture::interrupt`
+    Interrupt(u16),
+
+    /// Halt the processor, clearing the bit 15 of the Machine Control
+    /// Register
+    Halt,
+}
+
+/// Memory-mapped device, that handles the reads and the writes of the
+/// addresses it's mapped to instead of the memory
+pub trait Device {
+    /// Get the value of the register mapped at `address`
+    fn read(&mut self, address: u16) -> u16;
+
+    /// Get the value that reading the register mapped at `address` would
+    /// return, without the side effects of reading it
+    fn peek(&self, address: u16) -> u16;
+
+    /// Write `data` into the register mapped at `address`
+    fn write(&mut self, address: u16, data: u16);
+
+    /// Update the device after an instruction has been executed, returning a
+    /// request for the processor if it needs one
+    fn tick(&mut self) -> Option<DeviceRequest> {
+        None
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Device, DeviceRequest};
+
+/// Timer that expires every time a number of instructions, set through the
+/// Timer Interval Register, have been executed
+///
+/// If it has an interrupt and the bit 14 (Interrupt Enable) of the Timer
+/// Status Register is set, it requests the interrupt from when it expires
+/// until the Status Register is read. Without an interrupt the Interrupt
+/// Enable bit stays clear
+pub struct Timer {
+    status_address: u16,
+    interval_address: u16,
+    interval: u16,
+    count: u16,
+    expired: bool,
+    interrupt: Option<u16>,
+    interrupt_enable: bool,
+}
+
+impl Timer {
+    /// Create a stopped timer with the Timer Status Register at
+    /// `status_address` and the Timer Interval Register at `interval_address`
+    #[must_use]
+    pub const fn new(status_address: u16, interval_address: u16) -> Self {
+        Self {
+            status_address,
+            interval_address,
+            interval: 0,
+            count: 0,
+            expired: false,
+            interrupt: None,
+            interrupt_enable: false,
+        }
+    }
+
+    /// Let the timer request an interrupt, passing `data` to
+    /// `Architecture::interrupt`
+    #[must_use]
+    pub const fn with_interrupt(mut self, data: u16) -> Self {
+        self.interrupt = Some(data);
+        self
+    }
+}
+
+impl Device for Timer {
+    fn read(&mut self, address: u16) -> u16 {
+        let data = self.peek(address);
+        if address == self.status_address {
+            self.expired = false;
+        }
+
+        data
+    }
+
+    fn peek(&self, address: u16) -> u16 {
+        // The bit 15 of the Timer Status Register is set if the timer has
+        // expired since the last time it has been read
+        if address == self.status_address {
+            return u16::from(self.expired) << 15 | u16::from(self.interrupt_enable) << 14;
+        }
+
+        if address == self.interval_address {
+            return self.interval;
+        }
+
+        0x0000
+    }
+
+    fn write(&mut self, address: u16, data: u16) {
+        // Only the Interrupt Enable bit of the Status Register can be
+        // written, and only if the timer has an interrupt
+        if address == self.status_address && self.interrupt.is_some() {
+            self.interrupt_enable = data & 0x4000 != 0;
+        }
+
+        // Writing the Timer Interval Register restarts the timer, and an
+        // interval of 0 stops it
+        if address == self.interval_address {
+            self.interval = data;
+            self.count = 0;
+            self.expired = false;
+        }
+    }
+
+    fn tick(&mut self) -> Option<DeviceRequest> {
+        if self.interval != 0 {
+            self.count += 1;
+            if self.count >= self.interval {
+                self.count = 0;
+                self.expired = true;
+            }
+        }
+
+        if !self.interrupt_enable || !self.expired {
+            return None;
+        }
+
+        self.interrupt.map(DeviceRequest::Interrupt)
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Errors returned by the architectures
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Error {
+    /// The bytes to load don't fit into the memory after the start address
+    LoadOutOfBounds,
+
+    /// There is no General Purpose Register with the given number
+    InvalidRegister(usize),
+
+    /// The given Processor Status Register doesn't contain exactly one
+    /// Condition Code bit
+    InvalidConditionCode(u16),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LoadOutOfBounds => write!(fmt, "The bytes don't fit into the memory"),
+            Self::InvalidRegister(number) => {
+                write!(fmt, "There is no General Purpose Register R{number}")
+            }
+            Self::InvalidConditionCode(processor_status_register) => write!(
+                fmt,
+                "The Processor Status Register x{processor_status_register:04X} has an invalid \
+                 Condition Code"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
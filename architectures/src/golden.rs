@@ -0,0 +1,291 @@
+//! Regression test harness, that runs the programs of a corpus and compares
+//! the state of the processor after each instruction with a golden trace.
+//!
+//! The corpus of an architecture is in `golden/<architecture>`, where each
+//! program `<name>.program` has its golden trace in `<name>.trace`. A program
+//! is a list of hexadecimal words, with `;` starting a comment and these
+//! directives:
+//!
+//! - `.ORIG <ADDRESS>` places the following words from `ADDRESS`, and the
+//!   first one is the entry point of the program
+//! - `.SET <REGISTER> <VALUE>` sets a register before running the program
+//! - `.INPUT <CHARACTER>...` puts characters in the keyboard buffer
+//!
+//! The program runs with the default devices of the architecture until it
+//! clears the bit 15 of the Machine Control Register, and the trace has a line
+//! per instruction with its address, the instruction, the registers and the
+//! Condition Code after it and the memory cells it has written.
+//!
+//! The traces aren't recorded from this implementation: they're worked out
+//! from the ISA in Appendix A of Patt and Patel, "Introduction to Computing
+//! Systems" (1st edition for the LC-2, 3rd edition for the LC-3), and from the
+//! documentation of the devices in `crate::devices`, and the comments of each
+//! program say what it checks. A divergence must be settled against the book,
+//! never by copying the actual line into the trace.
+
+use crate::{common::ConditionCode, Architecture, WatcherType};
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of instructions executed by a program
+const MAX_INSTRUCTIONS: usize = 10_000;
+
+/// Architecture under test, with the functions needed to run the corpus
+pub struct Harness<A: Architecture> {
+    /// Name of the directory of the corpus in `golden`
+    pub name: &'static str,
+    /// Create a processor starting from an address
+    pub new: fn(u16) -> A,
+    /// Registers written in the trace, with their names
+    pub registers: Vec<(&'static str, A::Register)>,
+    /// Get the text of an instruction stored at an address
+    pub disassemble: fn(u16, u16) -> String,
+    /// Add the default devices, reading the keyboard from a buffer
+    pub devices: fn(&mut A, Arc<Mutex<VecDeque<u8>>>),
+    /// Address of the Machine Control Register, that stops the program
+    pub machine_control_register: u16,
+}
+
+/// Program of the corpus
+struct Program<R> {
+    entry_point: u16,
+    words: Vec<(u16, u16)>,
+    registers: Vec<(R, u16)>,
+    input: VecDeque<u8>,
+}
+
+impl<A> Harness<A>
+where
+    A: Architecture<Address = u16, Data = u16, RegisterData = u16, ConditionCode = ConditionCode>,
+    A::Register: Clone,
+{
+    /// Run all the programs of the corpus, panicking with the first
+    /// divergence of each program from its golden trace
+    pub fn check_corpus(&self) {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("golden")
+            .join(self.name);
+
+        // Get the programs in alphabetical order
+        let mut programs = fs::read_dir(&directory)
+            .expect("The corpus should be readable")
+            .map(|entry| entry.expect("The corpus should be readable").path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "program")
+            })
+            .collect::<Vec<_>>();
+        programs.sort();
+        assert!(!programs.is_empty(), "The corpus should not be empty");
+
+        let mut failures = Vec::new();
+        for path in programs {
+            let name = format!(
+                "{}/{}",
+                self.name,
+                path.file_stem().unwrap_or_default().to_string_lossy()
+            );
+            let trace_path = path.with_extension("trace");
+            let program = fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|source| self.parse(&source));
+            let program = match program {
+                Ok(program) => program,
+                Err(error) => {
+                    failures.push(format!("{name}: invalid program: {error}"));
+                    continue;
+                }
+            };
+
+            let Ok(golden) = fs::read_to_string(&trace_path) else {
+                failures.push(format!("{name}: missing golden trace"));
+                continue;
+            };
+            let golden = golden.lines().map(str::to_owned).collect::<Vec<_>>();
+            if let Err(report) = self.run(&program, &golden) {
+                failures.push(format!("{name}: {}", report.join("\n")));
+            }
+        }
+
+        assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+    }
+
+    /// Parse the source of a program
+    fn parse(&self, source: &str) -> Result<Program<A::Register>, String> {
+        let number = |token: Option<&str>| {
+            let token = token.ok_or("Missing value")?;
+            token
+                .strip_prefix('x')
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("Invalid value \"{token}\""))
+        };
+
+        let mut program = Program {
+            entry_point: 0,
+            words: Vec::new(),
+            registers: Vec::new(),
+            input: VecDeque::new(),
+        };
+        let mut address = None;
+        for line in source.lines() {
+            let line = line.split(';').next().unwrap_or_default();
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                match token {
+                    ".ORIG" => {
+                        let origin = number(tokens.next())?;
+                        if address.is_none() {
+                            program.entry_point = origin;
+                        }
+                        address = Some(origin);
+                    }
+                    ".SET" => {
+                        let name = tokens.next().ok_or("Missing register")?;
+                        let register = self
+                            .registers
+                            .iter()
+                            .find(|(register_name, _)| *register_name == name)
+                            .map(|(_, register)| register.clone())
+                            .ok_or_else(|| format!("Unknown register \"{name}\""))?;
+                        program.registers.push((register, number(tokens.next())?));
+                    }
+                    ".INPUT" => {
+                        for character in tokens.by_ref() {
+                            let character = u8::try_from(number(Some(character))?)
+                                .map_err(|_| format!("Invalid character \"{character}\""))?;
+                            program.input.push_back(character);
+                        }
+                    }
+                    _ => {
+                        let current = address.ok_or("Missing .ORIG")?;
+                        program.words.push((current, number(Some(token))?));
+                        address = Some(current.wrapping_add(1));
+                    }
+                }
+            }
+        }
+
+        Ok(program)
+    }
+
+    /// Run a program, stopping at the first line that diverges from `golden`.
+    /// Return the report of the divergence
+    fn run(&self, program: &Program<A::Register>, golden: &[String]) -> Result<(), Vec<String>> {
+        let mut cpu = (self.new)(program.entry_point);
+        let machine_control_register = self.machine_control_register;
+        (self.devices)(&mut cpu, Arc::new(Mutex::new(program.input.clone())));
+        for (address, word) in &program.words {
+            cpu.set_memory(*address, *word);
+        }
+        for (register, data) in &program.registers {
+            cpu.set_register(register, *data)
+                .map_err(|error| vec![format!("Can't set a register: {error}")])?;
+        }
+        cpu.set_tracing(true);
+
+        let mut trace = Vec::new();
+        let mut written = BTreeMap::new();
+        while cpu.peek_memory(machine_control_register) & 0x8000 != 0
+            && trace.len() < MAX_INSTRUCTIONS
+        {
+            // Execute an instruction and describe the state after it
+            let line = match cpu.step_instruction() {
+                Ok(_) => {
+                    let record = cpu.last_trace().expect("The tracing should be enabled");
+                    let mut line = format!("x{:04X} x{:04X} ", record.address, record.instruction);
+                    for (name, register) in &self.registers {
+                        let _ = write!(line, " {name}=x{:04X}", cpu.get_register(register));
+                    }
+                    let condition_code = match cpu.get_condition_code() {
+                        ConditionCode::Negative => "N",
+                        ConditionCode::Zero => "Z",
+                        ConditionCode::Positive => "P",
+                    };
+                    let _ = write!(line, " CC={condition_code}");
+                    for access in &record.memory {
+                        if access.access_type == WatcherType::OnWrite {
+                            let _ =
+                                write!(line, "  [x{:04X}]=x{:04X}", access.address, access.data);
+                            written.insert(access.address, access.data);
+                        }
+                    }
+                    line
+                }
+                Err(error) => format!("error: {error}"),
+            };
+
+            // Report the first divergence with its context
+            if golden.get(trace.len()) != Some(&line) {
+                return Err(self.report(&cpu, &trace, golden, &line, &written));
+            }
+            let failed = line.starts_with("error");
+            trace.push(line);
+            if failed {
+                break;
+            }
+        }
+
+        if golden.len() != trace.len() {
+            return Err(vec![format!(
+                "the program stopped after {} instructions instead of {}",
+                trace.len(),
+                golden.len()
+            )]);
+        }
+        Ok(())
+    }
+
+    /// Describe the divergence of `line` from the golden trace, after the
+    /// lines of `trace`
+    fn report(
+        &self,
+        cpu: &A,
+        trace: &[String],
+        golden: &[String],
+        line: &str,
+        written: &BTreeMap<u16, u16>,
+    ) -> Vec<String> {
+        let step = trace.len();
+        let mut report = vec![format!("divergence at instruction {}", step + 1)];
+        if let Some(record) = cpu.last_trace() {
+            report.push(format!(
+                "  instruction: x{:04X}: x{:04X}  {}",
+                record.address,
+                record.instruction,
+                (self.disassemble)(record.instruction, record.address)
+            ));
+        }
+        if let Some(previous) = trace.last() {
+            report.push(format!("  previous:    {previous}"));
+        }
+        report.push(format!(
+            "  expected:    {}",
+            golden
+                .get(step)
+                .map_or("the end of the program", String::as_str)
+        ));
+        report.push(format!("  actual:      {line}"));
+
+        // Show the memory written so far and the memory around the Program
+        // Counter
+        report.push("  memory written:".to_owned());
+        for (address, data) in written {
+            report.push(format!("    x{address:04X}: x{data:04X}"));
+        }
+        let program_counter = cpu.last_trace().map_or(0, |record| record.address);
+        report.push("  memory around the instruction:".to_owned());
+        for address in (0..8).map(|offset| program_counter.wrapping_sub(3).wrapping_add(offset)) {
+            let word = cpu.peek_memory(address);
+            report.push(format!(
+                "    x{address:04X}: x{word:04X}  {}",
+                (self.disassemble)(word, address)
+            ));
+        }
+
+        report
+    }
+}
@@ -0,0 +1,84 @@
+use super::Instruction;
+use crate::common::{label, operand_text, register_name, trap_alias, Disassembly, SymbolTable};
+
+/// Disassemble the instruction `word` stored at `address`, replacing the
+/// addresses it references with their labels from `symbols`
+#[must_use]
+pub fn disassemble(
+    word: u16,
+    address: u16,
+    symbols: Option<&SymbolTable>,
+) -> Disassembly<Instruction> {
+    let instruction = Instruction::decode(word);
+    let target = instruction.target(address);
+    let target_label = || label(target.unwrap_or_default(), symbols);
+
+    let text = match &instruction {
+        Instruction::Add { dr, sr1, sr2 } => {
+            format!(
+                "ADD {}, {}, {}",
+                register_name(dr),
+                register_name(sr1),
+                operand_text(sr2)
+            )
+        }
+        Instruction::And { dr, sr1, sr2 } => {
+            format!(
+                "AND {}, {}, {}",
+                register_name(dr),
+                register_name(sr1),
+                operand_text(sr2)
+            )
+        }
+        Instruction::Br { n, z, p, .. } => {
+            if *n || *z || *p {
+                let flags: String = [(*n, 'n'), (*z, 'z'), (*p, 'p')]
+                    .iter()
+                    .filter_map(|(set, flag)| set.then_some(*flag))
+                    .collect();
+                format!("BR{flags} {}", target_label())
+            } else {
+                "NOP".to_owned()
+            }
+        }
+        Instruction::Jsr { link, .. } => {
+            format!("{} {}", if *link { "JSR" } else { "JMP" }, target_label())
+        }
+        Instruction::Jsrr { link, base, index } => format!(
+            "{} {}, #{index}",
+            if *link { "JSRR" } else { "JMPR" },
+            register_name(base)
+        ),
+        Instruction::Ld { dr, .. } => format!("LD {}, {}", register_name(dr), target_label()),
+        Instruction::Ldi { dr, .. } => format!("LDI {}, {}", register_name(dr), target_label()),
+        Instruction::Ldr { dr, base, index } => {
+            format!(
+                "LDR {}, {}, #{index}",
+                register_name(dr),
+                register_name(base)
+            )
+        }
+        Instruction::Lea { dr, .. } => format!("LEA {}, {}", register_name(dr), target_label()),
+        Instruction::Not { dr, sr } => format!("NOT {}, {}", register_name(dr), register_name(sr)),
+        Instruction::Ret => "RET".to_owned(),
+        Instruction::Rti => "RTI".to_owned(),
+        Instruction::St { sr, .. } => format!("ST {}, {}", register_name(sr), target_label()),
+        Instruction::Sti { sr, .. } => format!("STI {}, {}", register_name(sr), target_label()),
+        Instruction::Str { sr, base, index } => {
+            format!(
+                "STR {}, {}, #{index}",
+                register_name(sr),
+                register_name(base)
+            )
+        }
+        Instruction::Trap { vector } => {
+            trap_alias(*vector).map_or_else(|| format!("TRAP x{vector:02X}"), str::to_owned)
+        }
+    };
+
+    Disassembly {
+        instruction,
+        target,
+        text,
+    }
+}
@@ -0,0 +1,234 @@
+use super::Gpr;
+use crate::common::{self, gpr, operand, register, sign_extend};
+
+/// Mnemonics of the opcodes, indexed by the 4 most significant bits of the
+/// instructions
+pub const OPCODES: [&str; 16] = [
+    "BR", "ADD", "LD", "ST", "JSR", "AND", "LDR", "STR", "RTI", "NOT", "LDI", "STI", "JSRR", "RET",
+    "LEA", "TRAP",
+];
+
+/// Second source operand of ADD and AND
+pub type Operand = common::Operand<Gpr>;
+
+/// LC-2 instruction, with its operands as they are encoded
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Instruction {
+    Add {
+        dr: Gpr,
+        sr1: Gpr,
+        sr2: Operand,
+    },
+    And {
+        dr: Gpr,
+        sr1: Gpr,
+        sr2: Operand,
+    },
+    Br {
+        n: bool,
+        z: bool,
+        p: bool,
+        page_offset: u16,
+    },
+    /// JMP, or JSR if `link` is set
+    Jsr {
+        link: bool,
+        page_offset: u16,
+    },
+    /// JMPR, or JSRR if `link` is set
+    Jsrr {
+        link: bool,
+        base: Gpr,
+        index: u16,
+    },
+    Ld {
+        dr: Gpr,
+        page_offset: u16,
+    },
+    Ldi {
+        dr: Gpr,
+        page_offset: u16,
+    },
+    Ldr {
+        dr: Gpr,
+        base: Gpr,
+        index: u16,
+    },
+    Lea {
+        dr: Gpr,
+        page_offset: u16,
+    },
+    Not {
+        dr: Gpr,
+        sr: Gpr,
+    },
+    Ret,
+    Rti,
+    St {
+        sr: Gpr,
+        page_offset: u16,
+    },
+    Sti {
+        sr: Gpr,
+        page_offset: u16,
+    },
+    Str {
+        sr: Gpr,
+        base: Gpr,
+        index: u16,
+    },
+    Trap {
+        vector: u8,
+    },
+}
+
+impl Instruction {
+    /// Decode an instruction. Every word is a valid LC-2 instruction, the bits
+    /// that are not used by an instruction are ignored
+    #[must_use]
+    pub fn decode(word: u16) -> Self {
+        let gpr = |offset| gpr(word, offset);
+        let flag = |bit: u8| (word >> bit) & 1 == 1;
+        let page_offset = word & 0x01ff;
+        let index = word & 0x003f;
+
+        match word >> 12 {
+            0b0001 | 0b0101 => {
+                let sr2 = if flag(5) {
+                    Operand::Immediate(sign_extend(word, 5))
+                } else {
+                    Operand::Register(gpr(0))
+                };
+                if word >> 12 == 0b0001 {
+                    Self::Add {
+                        dr: gpr(9),
+                        sr1: gpr(6),
+                        sr2,
+                    }
+                } else {
+                    Self::And {
+                        dr: gpr(9),
+                        sr1: gpr(6),
+                        sr2,
+                    }
+                }
+            }
+            0b0000 => Self::Br {
+                n: flag(11),
+                z: flag(10),
+                p: flag(9),
+                page_offset,
+            },
+            0b0100 => Self::Jsr {
+                link: flag(11),
+                page_offset,
+            },
+            0b1100 => Self::Jsrr {
+                link: flag(11),
+                base: gpr(6),
+                index,
+            },
+            0b0010 => Self::Ld {
+                dr: gpr(9),
+                page_offset,
+            },
+            0b1010 => Self::Ldi {
+                dr: gpr(9),
+                page_offset,
+            },
+            0b0110 => Self::Ldr {
+                dr: gpr(9),
+                base: gpr(6),
+                index,
+            },
+            0b1110 => Self::Lea {
+                dr: gpr(9),
+                page_offset,
+            },
+            0b1001 => Self::Not {
+                dr: gpr(9),
+                sr: gpr(6),
+            },
+            0b1101 => Self::Ret,
+            0b1000 => Self::Rti,
+            0b0011 => Self::St {
+                sr: gpr(9),
+                page_offset,
+            },
+            0b1011 => Self::Sti {
+                sr: gpr(9),
+                page_offset,
+            },
+            0b0111 => Self::Str {
+                sr: gpr(9),
+                base: gpr(6),
+                index,
+            },
+            0b1111 => Self::Trap {
+                vector: word.to_be_bytes()[1],
+            },
+            0b10000..=u16::MAX => unreachable!(),
+        }
+    }
+
+    /// Encode the instruction. The bits that are not used by the instruction
+    /// are set to 0, except for the lowest 6 bits of NOT that are set to 1,
+    /// and the operands that don't fit into their field are truncated
+    #[must_use]
+    pub fn encode(&self) -> u16 {
+        let flag = |flag: &bool, bit: u8| u16::from(*flag) << bit;
+
+        match self {
+            Self::Add { dr, sr1, sr2 } => {
+                0x1000 | register(dr, 9) | register(sr1, 6) | operand(sr2)
+            }
+            Self::And { dr, sr1, sr2 } => {
+                0x5000 | register(dr, 9) | register(sr1, 6) | operand(sr2)
+            }
+            Self::Br {
+                n,
+                z,
+                p,
+                page_offset,
+            } => flag(n, 11) | flag(z, 10) | flag(p, 9) | (page_offset & 0x01ff),
+            Self::Jsr { link, page_offset } => 0x4000 | flag(link, 11) | (page_offset & 0x01ff),
+            Self::Jsrr { link, base, index } => {
+                0xc000 | flag(link, 11) | register(base, 6) | (index & 0x003f)
+            }
+            Self::Ld { dr, page_offset } => 0x2000 | register(dr, 9) | (page_offset & 0x01ff),
+            Self::Ldi { dr, page_offset } => 0xa000 | register(dr, 9) | (page_offset & 0x01ff),
+            Self::Ldr { dr, base, index } => {
+                0x6000 | register(dr, 9) | register(base, 6) | (index & 0x003f)
+            }
+            Self::Lea { dr, page_offset } => 0xe000 | register(dr, 9) | (page_offset & 0x01ff),
+            Self::Not { dr, sr } => 0x9000 | register(dr, 9) | register(sr, 6) | 0x003f,
+            Self::Ret => 0xd000,
+            Self::Rti => 0x8000,
+            Self::St { sr, page_offset } => 0x3000 | register(sr, 9) | (page_offset & 0x01ff),
+            Self::Sti { sr, page_offset } => 0xb000 | register(sr, 9) | (page_offset & 0x01ff),
+            Self::Str { sr, base, index } => {
+                0x7000 | register(sr, 9) | register(base, 6) | (index & 0x003f)
+            }
+            Self::Trap { vector } => 0xf000 | u16::from(*vector),
+        }
+    }
+
+    /// Get the absolute address referenced by the instruction, if it's stored
+    /// at `address`. The page offsets refer to the page of the incremented
+    /// Program Counter
+    #[must_use]
+    pub const fn target(&self, address: u16) -> Option<u16> {
+        match self {
+            Self::Br { page_offset, .. }
+            | Self::Jsr { page_offset, .. }
+            | Self::Ld { page_offset, .. }
+            | Self::Ldi { page_offset, .. }
+            | Self::Lea { page_offset, .. }
+            | Self::St { page_offset, .. }
+            | Self::Sti { page_offset, .. } => {
+                Some((address.wrapping_add(1) & 0xfe00) | *page_offset)
+            }
+            _ => None,
+        }
+    }
+}
@@ -4,16 +4,57 @@ mod tests;
 mod registers;
 pub use registers::{Gpr, Register};
 
+mod instruction;
+pub use instruction::{Instruction, Operand, OPCODES};
+
+mod disassembler;
+pub use disassembler::disassemble;
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
 use crate::{
     common::{
-        ConditionCode, ConditionCodeWatchersStorage, Memory16x16, MemoryWatchersStorage,
-        RegisterWatchersStorage,
+        ConditionCode, ConditionCodeWatchersStorage, Counter, Coverage, Journal, Memory16x16,
+        MemoryRangeWatchersStorage, MemoryWatchersStorage, Profile, RegisterWatchersStorage,
+        Statistics, TraceRecord, Tracer,
     },
-    Architecture, WatcherType,
+    devices::{Device, DeviceRequest, Display, Keyboard, MachineControl, Timer},
+    Architecture, Error, StepOutcome, WatcherId, WatcherType,
 };
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+/// Address of the Keyboard Status Register
+pub const KEYBOARD_STATUS_REGISTER: u16 = 0xf400;
+/// Address of the Keyboard Data Register
+pub const KEYBOARD_DATA_REGISTER: u16 = 0xf401;
+/// Address of the Display Status Register
+pub const DISPLAY_STATUS_REGISTER: u16 = 0xf3fc;
+/// Address of the Display Data Register
+pub const DISPLAY_DATA_REGISTER: u16 = 0xf3ff;
+/// Address of the Timer Status Register
+pub const TIMER_STATUS_REGISTER: u16 = 0xf402;
+/// Address of the Timer Interval Register
+pub const TIMER_INTERVAL_REGISTER: u16 = 0xf403;
+/// Address of the Machine Control Register
+pub const MACHINE_CONTROL_REGISTER: u16 = 0xffff;
+
+/// Registers saved in the undo journal before each instruction, including
+/// the Machine Control Register to undo a halt
+struct SavedRegisters {
+    general_purpose_register: [u16; 8],
+    condition_code: ConditionCode,
+    program_counter: u16,
+    instruction_register: u16,
+    memory_address_register: u16,
+    memory_data_register: u16,
+    machine_control_register: u16,
+}
 
 #[derive(Default)]
 pub struct Lc2 {
@@ -28,9 +69,25 @@ pub struct Lc2 {
     memory_data_register: u16,
     memory: Memory16x16,
 
+    // Undo journal
+    journal: Journal<SavedRegisters>,
+
+    // Trace of the last instruction
+    tracer: Tracer<Register, ConditionCode>,
+
+    // Statistics of the execution
+    counter: Counter,
+
+    // Coverage of the program, if enabled
+    coverage: Option<Coverage>,
+
+    // Shadow call stack, if the profiling is enabled
+    profile: Option<Profile>,
+
     // Watchers
     register_watchers: RegisterWatchersStorage<Register>,
     memory_watchers: MemoryWatchersStorage<u16>,
+    memory_range_watchers: MemoryRangeWatchersStorage<u16>,
     condition_code_watchers: ConditionCodeWatchersStorage,
 }
 
@@ -42,6 +99,44 @@ impl Lc2 {
             ..Default::default()
         }
     }
+
+    /// Map the keyboard, the display, the timer and the Machine Control
+    /// Register at their standard addresses. The keyboard reads the
+    /// characters from `input_buffer` and the display writes them to
+    /// `output`. The keyboard and the timer don't request interrupts, because
+    /// the LC-2 can't mask them, so their Interrupt Enable bits can't be set:
+    /// the interrupt-driven input is only available on the LC-3
+    pub fn add_default_devices(
+        &mut self,
+        input_buffer: Arc<Mutex<VecDeque<u8>>>,
+        output: Box<dyn Write>,
+    ) {
+        self.add_device(
+            KEYBOARD_STATUS_REGISTER..=KEYBOARD_DATA_REGISTER,
+            Box::new(Keyboard::new(
+                KEYBOARD_STATUS_REGISTER,
+                KEYBOARD_DATA_REGISTER,
+                input_buffer,
+            )),
+        );
+        self.add_device(
+            DISPLAY_STATUS_REGISTER..=DISPLAY_DATA_REGISTER,
+            Box::new(Display::new(
+                DISPLAY_STATUS_REGISTER,
+                DISPLAY_DATA_REGISTER,
+                true,
+                output,
+            )),
+        );
+        self.add_device(
+            TIMER_STATUS_REGISTER..=TIMER_INTERVAL_REGISTER,
+            Box::new(Timer::new(TIMER_STATUS_REGISTER, TIMER_INTERVAL_REGISTER)),
+        );
+        self.add_device(
+            MACHINE_CONTROL_REGISTER..=MACHINE_CONTROL_REGISTER,
+            Box::new(MachineControl::new()),
+        );
+    }
 }
 
 impl fmt::Debug for Lc2 {
@@ -53,23 +148,6 @@ impl fmt::Debug for Lc2 {
             .map(|(i, value)| (Gpr::try_from(i).ok(), value))
             .collect();
 
-        let condition_code_watchers: &Vec<WatcherType> = &self
-            .condition_code_watchers
-            .iter()
-            .enumerate()
-            .filter_map(|(i, watcher)| {
-                if watcher.is_some() {
-                    Some(if i == 0 {
-                        WatcherType::OnWrite
-                    } else {
-                        WatcherType::OnRead
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-
         fmt.debug_struct("Lc2")
             .field("memory", &self.memory)
             .field("general_purpose_registers", gpr)
@@ -78,9 +156,24 @@ impl fmt::Debug for Lc2 {
             .field("instruction_register", &self.instruction_register)
             .field("memory_address_register", &self.memory_address_register)
             .field("memory_data_register", &self.memory_data_register)
+            .field("journal", &self.journal.len())
+            .field("tracer", &self.tracer.last())
+            .field("statistics", self.counter.statistics())
+            .field(
+                "coverage",
+                &self.coverage.as_ref().map(|coverage| coverage.hits.len()),
+            )
+            .field("profile", &self.profile.is_some())
             .field("register_watchers", &self.register_watchers.keys())
             .field("memory_watchers", &self.memory_watchers.keys())
-            .field("condition_code_watchers", &condition_code_watchers)
+            .field(
+                "memory_range_watchers",
+                &self.memory_range_watchers.ranges(),
+            )
+            .field(
+                "condition_code_watchers",
+                &self.condition_code_watchers.keys(),
+            )
             .finish()
     }
 }
@@ -91,18 +184,22 @@ impl Architecture for Lc2 {
     type Register = Register;
     type RegisterData = u16;
     type ConditionCode = ConditionCode;
+    type Snapshot = Snapshot;
 
-    #[must_use]
     fn get_memory(&mut self, address: Self::Address) -> Self::Data {
-        let data = self.memory[address];
+        let data = self.memory.read(address);
 
         self.memory_address_register = address;
         self.memory_data_register = data;
+        self.tracer
+            .record_memory(address, data, WatcherType::OnRead);
+        self.counter.record_memory(&WatcherType::OnRead);
 
-        // If there is a watcher for this address, call it
-        if let Some(function) = self.memory_watchers.get(&(address, WatcherType::OnRead)) {
-            function(data);
-        }
+        // Call the watchers of this address
+        self.memory_watchers
+            .call(&(address, WatcherType::OnRead), &data);
+        self.memory_range_watchers
+            .call(&address, &WatcherType::OnRead, &data);
 
         data
     }
@@ -111,28 +208,31 @@ impl Architecture for Lc2 {
         self.memory_address_register = address;
         self.memory_data_register = data;
 
-        self.memory[address] = data;
+        self.journal.record_memory(address, self.memory[address]);
+        self.tracer
+            .record_memory(address, data, WatcherType::OnWrite);
+        self.counter.record_memory(&WatcherType::OnWrite);
+        self.memory.write(address, data);
 
-        // If there is a watcher for this address, call it
-        if let Some(function) = self.memory_watchers.get(&(address, WatcherType::OnWrite)) {
-            function(data);
-        }
+        // Call the watchers of this address
+        self.memory_watchers
+            .call(&(address, WatcherType::OnWrite), &data);
+        self.memory_range_watchers
+            .call(&address, &WatcherType::OnWrite, &data);
     }
 
-    fn load_bytes(
-        &mut self,
-        start_address: Self::Address,
-        bytes: &[u8],
-    ) -> Result<(), &'static str> {
+    fn peek_memory(&self, address: Self::Address) -> Self::Data {
+        self.memory.peek(address)
+    }
+
+    fn load_bytes(&mut self, start_address: Self::Address, bytes: &[u8]) -> Result<(), Error> {
         // Calculate the address of the last cell
         let data_size: usize = std::mem::size_of::<Self::Data>();
-        let end_address: usize = start_address as usize
-            + (bytes.len() / data_size)
-            + usize::from(bytes.len() % data_size != 0);
+        let end_address: usize = start_address as usize + bytes.len().div_ceil(data_size);
 
         // Return an error if the byte array is too big
         if end_address > Self::Address::MAX as usize {
-            return Err("The array of byte is too big");
+            return Err(Error::LoadOutOfBounds);
         }
 
         // Save the Memory Address Register and the Memory Data Register
@@ -162,13 +262,12 @@ impl Architecture for Lc2 {
             .for_each(|(address, data)| self.set_memory(address, data));
 
         // Restore the Memory Address Register and the Memory Data Register
-        self.set_register(&Register::MemoryAddressRegister, memory_address_register);
-        self.set_register(&Register::MemoryDataRegister, memory_data_register);
+        self.update_register(&Register::MemoryAddressRegister, memory_address_register);
+        self.update_register(&Register::MemoryDataRegister, memory_data_register);
 
         Ok(())
     }
 
-    #[must_use]
     fn get_register(&self, register: &Self::Register) -> Self::RegisterData {
         let data = match register {
             Register::Gpr(gpr) => self.general_purpose_register[u8::from(gpr.clone()) as usize],
@@ -178,53 +277,29 @@ impl Architecture for Lc2 {
             Register::MemoryDataRegister => self.memory_data_register,
         };
 
-        // If there is a watcher for this register, call it
-        if let Some(function) = self
-            .register_watchers
-            .get(&(register.clone(), WatcherType::OnRead))
-        {
-            function(data);
-        }
+        // Call the watchers of this register
+        self.register_watchers
+            .call(&(register.clone(), WatcherType::OnRead), &data);
 
         data
     }
 
-    fn set_register(&mut self, register: &Self::Register, data: Self::RegisterData) {
-        // Get a mutable pointer to the register
-        let register_pointer: &mut u16 = match register {
-            Register::Gpr(gpr) => {
-                // When the register to update is a General Purpose Register
-                // update the condition code
-                self.set_condition_code(&ConditionCode::from(data));
-
-                &mut self.general_purpose_register[u8::from(gpr.clone()) as usize]
-            }
-            Register::ProgramCounter => &mut self.program_counter,
-            Register::InstructionRegister => &mut self.instruction_register,
-            Register::MemoryAddressRegister => &mut self.memory_address_register,
-            Register::MemoryDataRegister => &mut self.memory_data_register,
-        };
-
-        // Update the register
-        *register_pointer = data;
+    fn set_register(
+        &mut self,
+        register: &Self::Register,
+        data: Self::RegisterData,
+    ) -> Result<(), Error> {
+        self.update_register(register, data);
 
-        // If there is a watcher for this register, call it
-        if let Some(function) = self
-            .register_watchers
-            .get(&(register.clone(), WatcherType::OnWrite))
-        {
-            function(data);
-        }
+        Ok(())
     }
 
-    #[must_use]
     fn get_condition_code(&self) -> Self::ConditionCode {
         let condition_code = self.condition_code.clone();
 
-        // If there is a watcher for the condition code, call it
-        if let Some(function) = &self.condition_code_watchers[1] {
-            function(condition_code.clone());
-        }
+        // Call the watchers of the condition code
+        self.condition_code_watchers
+            .call(&WatcherType::OnRead, &condition_code);
 
         condition_code
     }
@@ -232,10 +307,9 @@ impl Architecture for Lc2 {
     fn set_condition_code(&mut self, condition_code: &Self::ConditionCode) {
         self.condition_code = condition_code.clone();
 
-        // If there is a watcher for the condition code, call it
-        if let Some(function) = &self.condition_code_watchers[0] {
-            function(condition_code.clone());
-        }
+        // Call the watchers of the condition code
+        self.condition_code_watchers
+            .call(&WatcherType::OnWrite, condition_code);
     }
 
     fn add_memory_watcher<F>(
@@ -243,15 +317,31 @@ impl Architecture for Lc2 {
         address: Self::Address,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::Data) + 'static,
     {
         self.memory_watchers
-            .insert((address, watcher_type), Box::new(function));
+            .insert((address, watcher_type), Box::new(function))
     }
 
-    fn remove_memory_watcher(&mut self, address: Self::Address, watcher_type: WatcherType) {
-        self.memory_watchers.remove(&(address, watcher_type));
+    fn remove_memory_watcher(&mut self, id: WatcherId) {
+        if !self.memory_watchers.remove(id) {
+            self.memory_range_watchers.remove(id);
+        }
+    }
+
+    fn add_memory_range_watcher<F>(
+        &mut self,
+        addresses: RangeInclusive<Self::Address>,
+        watcher_type: WatcherType,
+        function: F,
+    ) -> WatcherId
+    where
+        F: Fn(Self::Address, Self::Data) + 'static,
+    {
+        self.memory_range_watchers
+            .insert(addresses, watcher_type, Box::new(function))
     }
 
     fn add_register_watcher<F>(
@@ -259,266 +349,478 @@ impl Architecture for Lc2 {
         register: &Self::Register,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::RegisterData) + 'static,
     {
         self.register_watchers
-            .insert((register.clone(), watcher_type), Box::new(function));
+            .insert((register.clone(), watcher_type), Box::new(function))
     }
 
-    fn remove_register_watcher(&mut self, register: &Self::Register, watcher_type: WatcherType) {
-        self.register_watchers
-            .remove(&(register.clone(), watcher_type));
+    fn remove_register_watcher(&mut self, id: WatcherId) {
+        self.register_watchers.remove(id);
     }
 
-    fn add_condition_code_watcher<F>(&mut self, watcher_type: WatcherType, function: F)
+    fn add_condition_code_watcher<F>(&mut self, watcher_type: WatcherType, function: F) -> WatcherId
     where
         F: Fn(Self::ConditionCode) + 'static,
     {
-        let idx = usize::from(watcher_type != WatcherType::OnWrite);
-        self.condition_code_watchers[idx] = Some(Box::new(function));
+        self.condition_code_watchers
+            .insert(watcher_type, Box::new(function))
+    }
+
+    fn remove_condition_code_watcher(&mut self, id: WatcherId) {
+        self.condition_code_watchers.remove(id);
+    }
+
+    fn add_device(&mut self, addresses: RangeInclusive<Self::Address>, device: Box<dyn Device>) {
+        self.memory.map(addresses, device);
+    }
+
+    fn step_instruction(&mut self) -> Result<StepOutcome, Error> {
+        // Save the registers in the undo journal
+        if self.journal.is_enabled() {
+            let registers = self.saved_registers();
+            self.journal.begin(registers);
+        }
+        self.tracer.begin(self.program_counter);
+        self.counter.begin();
+
+        self.execute_instruction();
+
+        // The accesses made by the devices are not counted
+        self.counter.end();
+        self.tick_devices();
+        self.journal.end();
+        self.tracer.end(self.condition_code.clone());
+        Ok(StepOutcome::Executed)
+    }
+
+    fn interrupt(&mut self, routine_address: Self::Data) {
+        self.counter.record_interrupt();
+
+        // Save the Condition Code as a u16
+        let condition_code = u16::from(self.get_condition_code());
+
+        // Get the stack pointer register
+        let register = Register::Gpr(Gpr::R6);
+
+        // Push the Program Counter to the stack
+        self.update_register(&register, self.get_register(&register).wrapping_add(1));
+        self.set_memory(
+            self.get_register(&register),
+            self.get_register(&Register::ProgramCounter),
+        );
+
+        // Push the Condition Code on the stack
+        self.update_register(&register, self.get_register(&register).wrapping_add(1));
+        self.set_memory(self.get_register(&register), condition_code);
+
+        // Set the Program Counter to the interrupt routine address
+        let return_address = self.program_counter;
+        self.update_register(&Register::ProgramCounter, routine_address);
+        self.profile_call(routine_address, return_address);
+    }
+
+    fn set_history_depth(&mut self, depth: usize) {
+        self.journal.set_depth(depth);
+    }
+
+    fn step_back(&mut self) -> bool {
+        let Some(entry) = self.journal.pop() else {
+            return false;
+        };
+
+        // Restore the memory cells in the reverse order in which they have
+        // been written, then the registers
+        for (address, data) in entry.memory.into_iter().rev() {
+            self.memory[address] = data;
+        }
+        self.restore_registers(entry.registers);
+
+        true
+    }
+
+    fn run_back_to(&mut self, address: Self::Address) -> bool {
+        while self.step_back() {
+            if self.program_counter == address {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn set_tracing(&mut self, enabled: bool) {
+        self.tracer.set_enabled(enabled);
+    }
+
+    fn last_trace(&self) -> Option<&TraceRecord<Self::Register, Self::ConditionCode>> {
+        self.tracer.last()
     }
 
-    fn remove_condition_code_watcher(&mut self, watcher_type: WatcherType) {
-        let idx = usize::from(watcher_type != WatcherType::OnWrite);
-        self.condition_code_watchers[idx] = None;
+    fn statistics(&self) -> &Statistics {
+        self.counter.statistics()
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn step_instruction(&mut self) {
+    fn reset_statistics(&mut self) {
+        self.counter.reset();
+    }
+
+    fn set_coverage(&mut self, enabled: bool) {
+        if !enabled {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some(Coverage::default());
+        }
+    }
+
+    fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        if !enabled {
+            self.profile = None;
+        } else if self.profile.is_none() {
+            self.profile = Some(Profile::default());
+        }
+    }
+
+    fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        Snapshot {
+            general_purpose_registers: self.general_purpose_register,
+            condition_code: self.condition_code.clone(),
+            program_counter: self.program_counter,
+            instruction_register: self.instruction_register,
+            memory_address_register: self.memory_address_register,
+            memory_data_register: self.memory_data_register,
+            memory: Box::new(*self.memory.cells()),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Self::Snapshot) -> Result<(), Error> {
+        self.general_purpose_register = snapshot.general_purpose_registers;
+        self.condition_code = snapshot.condition_code.clone();
+        self.program_counter = snapshot.program_counter;
+        self.instruction_register = snapshot.instruction_register;
+        self.memory_address_register = snapshot.memory_address_register;
+        self.memory_data_register = snapshot.memory_data_register;
+        self.memory.set_cells(&snapshot.memory);
+
+        Ok(())
+    }
+}
+
+impl Lc2 {
+    /// Update a register, along with the Condition Code if it's a General
+    /// Purpose Register
+    fn update_register(&mut self, register: &Register, data: u16) {
+        // When the register to update is a General Purpose Register update the
+        // condition code
+        if let Register::Gpr(_) = register {
+            self.set_condition_code(&ConditionCode::from(data));
+        }
+
+        self.write_register(register, data);
+    }
+
+    /// Update a register without touching the Condition Code
+    fn write_register(&mut self, register: &Register, data: u16) {
+        // Get a mutable pointer to the register
+        let register_pointer: &mut u16 = match register {
+            Register::Gpr(gpr) => {
+                &mut self.general_purpose_register[u8::from(gpr.clone()) as usize]
+            }
+            Register::ProgramCounter => &mut self.program_counter,
+            Register::InstructionRegister => &mut self.instruction_register,
+            Register::MemoryAddressRegister => &mut self.memory_address_register,
+            Register::MemoryDataRegister => &mut self.memory_data_register,
+        };
+
+        // Update the register
+        *register_pointer = data;
+        self.tracer.record_register(register.clone(), data);
+
+        // Call the watchers of this register
+        self.register_watchers
+            .call(&(register.clone(), WatcherType::OnWrite), &data);
+    }
+
+    /// Save the registers for the undo journal
+    fn saved_registers(&self) -> SavedRegisters {
+        SavedRegisters {
+            general_purpose_register: self.general_purpose_register,
+            condition_code: self.condition_code.clone(),
+            program_counter: self.program_counter,
+            instruction_register: self.instruction_register,
+            memory_address_register: self.memory_address_register,
+            memory_data_register: self.memory_data_register,
+            machine_control_register: self.memory.peek(MACHINE_CONTROL_REGISTER),
+        }
+    }
+
+    /// Restore the registers saved in the undo journal
+    fn restore_registers(&mut self, registers: SavedRegisters) {
+        self.general_purpose_register = registers.general_purpose_register;
+        self.condition_code = registers.condition_code;
+        self.program_counter = registers.program_counter;
+        self.instruction_register = registers.instruction_register;
+        self.memory_address_register = registers.memory_address_register;
+        self.memory_data_register = registers.memory_data_register;
+        self.memory
+            .write(MACHINE_CONTROL_REGISTER, registers.machine_control_register);
+    }
+
+    /// Fetch the next instruction into the Instruction Register and increment
+    /// the Program Counter, returning the address of the instruction and the
+    /// instruction itself
+    fn fetch_instruction(&mut self) -> (u16, u16) {
         // Get the next instruction
-        let instruction = self.get_memory(self.get_register(&Register::ProgramCounter));
+        let address = self.get_register(&Register::ProgramCounter);
+        let instruction = self.get_memory(address);
 
         // Update the Instruction Register
-        self.set_register(&Register::InstructionRegister, instruction);
+        self.update_register(&Register::InstructionRegister, instruction);
 
         // Increment the Program Counter
-        self.set_register(
+        self.update_register(
             &Register::ProgramCounter,
             self.get_register(&Register::ProgramCounter).wrapping_add(1),
         );
+        self.tracer.fetched(instruction);
+        self.counter.fetched(instruction);
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_instruction(address);
+        }
+        if let Some(profile) = &mut self.profile {
+            profile.record_instruction();
+        }
 
-        // Match the operation
-        let opcode = instruction >> 12;
-        match opcode {
-            // Add and And, both with register and immediate
-            0b0001 | 0b0101 => {
-                // Get the destination register and the first source register
-                // content
-                let dest: Register = reg_from_instr(instruction, 9);
-                let src1: u16 = self.get_register(&reg_from_instr(instruction, 6));
-
-                // If the 6th bit is a 0 then do the operation on a register
-                let src2: u16 = if (instruction >> 5) & 1 == 0 {
-                    self.get_register(&reg_from_instr(instruction, 0))
-                }
-                // Else the operation is to be done on a immediate value
-                else {
-                    let mut src2 = instruction & 0b11111;
-
-                    // Sign-extend the immediate value
-                    if (src2 >> 4) & 1 == 1 {
-                        src2 |= 0xffe0;
-                    }
+        (address, instruction)
+    }
 
-                    src2
-                };
+    /// Fetch, decode and execute the next instruction
+    fn execute_instruction(&mut self) {
+        let (address, instruction) = self.fetch_instruction();
 
-                self.set_register(
-                    &dest,
-                    // Decide if the operation is an ADD or an AND
-                    if (opcode) == 0b0001 {
-                        src1.wrapping_add(src2)
-                    } else {
-                        src1 & src2
-                    },
-                );
+        // Decode the instruction and execute it
+        match Instruction::decode(instruction) {
+            // Add and And, both with register and immediate
+            Instruction::Add { dr, sr1, sr2 } => {
+                let data = self
+                    .get_register(&Register::Gpr(sr1))
+                    .wrapping_add(self.operand(sr2));
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::And { dr, sr1, sr2 } => {
+                let data = self.get_register(&Register::Gpr(sr1)) & self.operand(sr2);
+                self.update_register(&Register::Gpr(dr), data);
             }
 
             // Branch (BR)
-            0b0000 => {
-                // TODO: Refactor
-                if (((instruction >> 11) & 1) == 1
-                    && (self.condition_code == ConditionCode::Negative))
-                    || (((instruction >> 10) & 1) == 1
-                        && (self.condition_code == ConditionCode::Zero))
-                    || (((instruction >> 9) & 1) == 1
-                        && (self.condition_code == ConditionCode::Positive))
-                {
-                    // Get the new address by combining the current Program
-                    // Counter with the page offset
-                    let address = (self.get_register(&Register::ProgramCounter) & 0xfe00)
-                        + (instruction & 0x01ff);
-
-                    // Set the Program Counter to the new address
-                    self.set_register(&Register::ProgramCounter, address);
+            Instruction::Br {
+                n,
+                z,
+                p,
+                page_offset,
+            } => {
+                // Check if one of the condition codes in the instruction
+                // matches the current condition code
+                let taken = match self.condition_code {
+                    ConditionCode::Negative => n,
+                    ConditionCode::Zero => z,
+                    ConditionCode::Positive => p,
+                };
+
+                self.counter.record_branch(taken);
+                self.record_branch_coverage(address, [n, z, p], taken);
+                if taken {
+                    let address = self.page_address(page_offset);
+                    self.update_register(&Register::ProgramCounter, address);
                 }
             }
 
             // Jump (JMP) and Jump to Subroutine (JSR), both immediate and
-            // through registers
-            0b0100 | 0b1100 => {
-                // If the 12th bit is set then save the current Program Counter
-                // into R7
-                if (instruction >> 11) & 1 == 1 {
-                    self.set_register(
-                        &Register::Gpr(Gpr::R7),
-                        self.get_register(&Register::ProgramCounter),
-                    );
+            // through registers. If the link bit is set then save the current
+            // Program Counter into R7, before getting the new address
+            Instruction::Jsr { link, page_offset } => {
+                self.link(link);
+                let address = self.page_address(page_offset);
+                self.update_register(&Register::ProgramCounter, address);
+                if link {
+                    self.profile_call(address, self.general_purpose_register[7]);
                 }
-
-                // If the instruction is a Jump (or Jump to Subroutine), get the
-                // new address by combining the current Program Counter with the
-                // page offset
-                let address = if opcode == 0b0100 {
-                    (self.get_register(&Register::ProgramCounter) & 0xfe00) + (instruction & 0x01ff)
+            }
+            Instruction::Jsrr { link, base, index } => {
+                self.link(link);
+                let address = self.get_register(&Register::Gpr(base)).wrapping_add(index);
+                self.update_register(&Register::ProgramCounter, address);
+                if link {
+                    self.profile_call(address, self.general_purpose_register[7]);
                 }
-                // If the instruction is a Jump (or Jump to Subroutine) through
-                // Register, get the content of the register and add the last 6
-                // bits of the instruction
-                else {
-                    self.get_register(&reg_from_instr(instruction, 6)) + (instruction & 0x003f)
-                };
-
-                // Set the Program Counter to the new address
-                self.set_register(&Register::ProgramCounter, address);
             }
 
-            // Memory Operations:
-            //   - Load (LD)
-            //   - Load Indirect (LDI)
-            //   - Load through Register (LDR)
-            //   - Load Effective Address (LEA)
-            //   - Store (ST)
-            //   - Store Indirect (STI)
-            //   - Store through Register (STR)
-            0b0010 | 0b1010 | 0b0110 | 0b1110 | 0b0011 | 0b1011 | 0b0111 => {
-                // Get the source/destination register
-                let register = reg_from_instr(instruction, 9);
-
-                // If the operation is an operation "through Register", get
-                // address by combining the content of the register and add the
-                // last 6 bits of the instruction
-                let address = if opcode >> 1 == 0b011 {
-                    self.get_register(&reg_from_instr(instruction, 6)) + (instruction & 0x003f)
-                }
-                // Else get the address of the data by combining the current
-                // Program Counter with the page offset
-                else {
-                    let address = (self.get_register(&Register::ProgramCounter) & 0xfe00)
-                        + (instruction & 0x01ff);
-
-                    // If the operation is an indirect memory operation, use the
-                    // address to get the real address from memory
-                    if opcode >> 1 == 0b101 {
-                        self.get_memory(address)
-                    } else {
-                        address
-                    }
-                };
+            // Load (LD), Load Indirect (LDI), Load through Register (LDR) and
+            // Load Effective Address (LEA)
+            Instruction::Ld { dr, page_offset } => {
+                let data = self.get_memory(self.page_address(page_offset));
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Ldi { dr, page_offset } => {
+                let address = self.get_memory(self.page_address(page_offset));
+                let data = self.get_memory(address);
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Ldr { dr, base, index } => {
+                let data =
+                    self.get_memory(self.get_register(&Register::Gpr(base)).wrapping_add(index));
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Lea { dr, page_offset } => {
+                let address = self.page_address(page_offset);
+                self.update_register(&Register::Gpr(dr), address);
+            }
 
-                // If the operation is a Store operation, save the source
-                // register into the memory address
-                if opcode & 1 == 1 {
-                    self.set_memory(address, self.get_register(&register));
-                }
-                // Else if the operation is a Load operation, save the memory
-                // cell pointed by the address into the destination register
-                else {
-                    // If the operation is a Load Effective Address, the data is
-                    // the address itself
-                    let data = if opcode == 0b1110 {
-                        address
-                    } else {
-                        self.get_memory(address)
-                    };
-
-                    // Put the data in the destination register
-                    self.set_register(&register, data);
-                }
+            // Store (ST), Store Indirect (STI) and Store through Register (STR)
+            Instruction::St { sr, page_offset } => {
+                self.set_memory(
+                    self.page_address(page_offset),
+                    self.get_register(&Register::Gpr(sr)),
+                );
+            }
+            Instruction::Sti { sr, page_offset } => {
+                let address = self.get_memory(self.page_address(page_offset));
+                self.set_memory(address, self.get_register(&Register::Gpr(sr)));
+            }
+            Instruction::Str { sr, base, index } => {
+                self.set_memory(
+                    self.get_register(&Register::Gpr(base)).wrapping_add(index),
+                    self.get_register(&Register::Gpr(sr)),
+                );
             }
 
             // Not
-            0b1001 => {
-                self.set_register(
-                    &reg_from_instr(instruction, 9),
-                    !self.get_register(&reg_from_instr(instruction, 6)),
-                );
+            Instruction::Not { dr, sr } => {
+                self.update_register(&Register::Gpr(dr), !self.get_register(&Register::Gpr(sr)));
             }
 
             // Return (RET)
-            0b1101 => {
+            Instruction::Ret => {
                 // Set the Program Counter to the value saved in R7
-                self.set_register(
-                    &Register::ProgramCounter,
-                    self.get_register(&Register::Gpr(Gpr::R7)),
-                );
+                let address = self.get_register(&Register::Gpr(Gpr::R7));
+                self.update_register(&Register::ProgramCounter, address);
+                self.profile_return(address);
             }
 
             // Return from Interrupt (RTI)
-            0b1000 => {
-                // Get the stack pointer register
-                let register = Register::Gpr(Gpr::R6);
-
-                // Get the Condition Code from the stack
-                let condition_code = self.get_memory(self.get_register(&register));
-                self.set_register(&register, self.get_register(&register).wrapping_sub(1));
-
-                // Get the return address from the stack
-                let address = self.get_memory(self.get_register(&register));
-                self.set_register(&register, self.get_register(&register).wrapping_sub(1));
-
-                // Set the Condition Code to the value popped from the stack
-                self.set_condition_code(&ConditionCode::from(condition_code));
-
-                // Set the Program Counter to the value popped from the stack
-                self.set_register(&Register::ProgramCounter, address);
-            }
+            Instruction::Rti => self.return_from_interrupt(),
 
             // Trap
-            0b1111 => {
+            Instruction::Trap { vector } => {
+                self.counter.record_trap(vector);
+
                 // Save the Program Counter into R7
-                self.set_register(
-                    &Register::Gpr(Gpr::R7),
-                    self.get_register(&Register::ProgramCounter),
-                );
+                self.link(true);
 
                 // Load into the Program Counter the address pointed by the trap
                 // vector
-                let address = self.get_memory(instruction & 0x00ff);
-                self.set_register(&Register::ProgramCounter, address);
+                let address = self.get_memory(u16::from(vector));
+                self.update_register(&Register::ProgramCounter, address);
+                self.profile_call(address, self.general_purpose_register[7]);
             }
+        }
+    }
 
-            0b10000..=u16::MAX => unreachable!(),
+    /// Count the direction of the branch at `address` in the coverage, if
+    /// its condition `flags` is neither always nor never true
+    fn record_branch_coverage(&mut self, address: u16, flags: [bool; 3], taken: bool) {
+        if let Some(coverage) = &mut self.coverage {
+            if flags.contains(&true) && flags.contains(&false) {
+                coverage.record_branch(address, taken);
+            }
         }
     }
 
-    fn interrupt(&mut self, routine_address: Self::Data) {
-        // Save the Condition Code as a u16
-        let condition_code = u16::from(self.get_condition_code());
+    /// Enter the routine at `routine`, that returns to `return_address`, in
+    /// the profile
+    fn profile_call(&mut self, routine: u16, return_address: u16) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_call(routine, return_address);
+        }
+    }
 
+    /// Return to `address` in the profile
+    fn profile_return(&mut self, address: u16) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_return(address);
+        }
+    }
+
+    /// Update the devices and handle their requests
+    fn tick_devices(&mut self) {
+        for request in self.memory.tick() {
+            match request {
+                DeviceRequest::Interrupt(data) => self.interrupt(data),
+                DeviceRequest::Halt => {
+                    let data = self.get_memory(MACHINE_CONTROL_REGISTER);
+                    self.set_memory(MACHINE_CONTROL_REGISTER, data & 0x7fff);
+                }
+            }
+        }
+    }
+
+    /// Return from an interrupt, restoring the Program Counter and the
+    /// Condition Code from the stack
+    fn return_from_interrupt(&mut self) {
         // Get the stack pointer register
         let register = Register::Gpr(Gpr::R6);
 
-        // Push the Program Counter to the stack
-        self.set_register(&register, self.get_register(&register).wrapping_add(1));
-        self.set_memory(
-            self.get_register(&register),
-            self.get_register(&Register::ProgramCounter),
-        );
+        // Get the Condition Code from the stack
+        let condition_code = self.get_memory(self.get_register(&register));
+        self.update_register(&register, self.get_register(&register).wrapping_sub(1));
 
-        // Push the Condition Code on the stack
-        self.set_register(&register, self.get_register(&register).wrapping_add(1));
-        self.set_memory(self.get_register(&register), condition_code);
+        // Get the return address from the stack
+        let address = self.get_memory(self.get_register(&register));
+        self.update_register(&register, self.get_register(&register).wrapping_sub(1));
 
-        // Set the Program Counter to the interrupt routine address
-        self.set_register(&Register::ProgramCounter, routine_address);
+        // Set the Condition Code to the value popped from the stack
+        self.set_condition_code(&ConditionCode::from(condition_code));
+
+        // Set the Program Counter to the value popped from the stack
+        self.update_register(&Register::ProgramCounter, address);
+        self.profile_return(address);
     }
-}
 
-fn reg_from_instr(instruction: u16, offset: u8) -> Register {
-    Register::Gpr(
-        Gpr::try_from((instruction >> offset) as usize & 0b111)
-            .expect("Any number & 0b111 should be smaller than 8"),
-    )
+    /// Get the address in the page of the Program Counter at `page_offset`
+    fn page_address(&self, page_offset: u16) -> u16 {
+        (self.get_register(&Register::ProgramCounter) & 0xfe00) | page_offset
+    }
+
+    /// Get the value of the second source operand of ADD and AND
+    fn operand(&self, operand: Operand) -> u16 {
+        match operand {
+            Operand::Register(gpr) => self.get_register(&Register::Gpr(gpr)),
+            Operand::Immediate(value) => u16::from_ne_bytes(value.to_ne_bytes()),
+        }
+    }
+
+    /// If `link` is set, save the Program Counter into R7.
+    ///
+    /// The LC-2 ISA in Appendix A of Patt and Patel, "Introduction to
+    /// Computing Systems" (1st edition), marks ADD, AND, LD, LDI, LDR, LEA and
+    /// NOT as the only instructions that set the Condition Code, so JSR, JSRR
+    /// and TRAP leave it alone
+    fn link(&mut self, link: bool) {
+        if link {
+            self.write_register(
+                &Register::Gpr(Gpr::R7),
+                self.get_register(&Register::ProgramCounter),
+            );
+        }
+    }
 }
@@ -0,0 +1,57 @@
+use crate::common::{read_snapshot, write_snapshot, ConditionCode, SnapshotMemory};
+use std::io::{self, Read, Write};
+
+/// State of an LC-2, without its watchers and the state of its devices
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Snapshot {
+    pub general_purpose_registers: [u16; 8],
+    pub condition_code: ConditionCode,
+    pub program_counter: u16,
+    pub instruction_register: u16,
+    pub memory_address_register: u16,
+    pub memory_data_register: u16,
+    /// Cells of the memory, ignoring the devices mapped to them
+    pub memory: SnapshotMemory,
+}
+
+impl Snapshot {
+    /// Write the snapshot to `writer` in the snapshot file format
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `writer` can't be written
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut registers = self.general_purpose_registers.to_vec();
+        registers.extend([
+            u16::from(self.condition_code.clone()),
+            self.program_counter,
+            self.instruction_register,
+            self.memory_address_register,
+            self.memory_data_register,
+        ]);
+
+        write_snapshot(writer, 2, &registers, &self.memory)
+    }
+
+    /// Read a snapshot written by `write_to`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `reader` can't be read or if it
+    /// doesn't contain a snapshot of an LC-2 in a supported version
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let (registers, memory) = read_snapshot(reader, 2, 13)?;
+        let general_purpose_registers = std::array::from_fn(|i| registers[i]);
+        let registers = &registers[8..];
+
+        Ok(Self {
+            general_purpose_registers,
+            condition_code: ConditionCode::from(registers[0]),
+            program_counter: registers[1],
+            instruction_register: registers[2],
+            memory_address_register: registers[3],
+            memory_data_register: registers[4],
+            memory,
+        })
+    }
+}
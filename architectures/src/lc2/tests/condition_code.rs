@@ -33,13 +33,13 @@ fn set_through_gpr() {
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3002, 0b0001_000_000_1_11111); // ADD R0, R0, #-1
 
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_condition_code(), ConditionCode::Positive);
 
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_condition_code(), ConditionCode::Zero);
 
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
 }
 
@@ -51,7 +51,7 @@ fn watcher_on_write() {
 
     // Create a watcher that transforms the Condition Code into a u16
     let value_watcher = value.clone();
-    cpu.add_condition_code_watcher(WatcherType::OnWrite, move |new_value| {
+    let watcher = cpu.add_condition_code_watcher(WatcherType::OnWrite, move |new_value| {
         value_watcher.store(u16::from(new_value), Ordering::Relaxed);
     });
 
@@ -60,14 +60,14 @@ fn watcher_on_write() {
     cpu.set_memory(0x3000, 0b0001_000_000_1_00001); // ADD R0, R0, #1
 
     // Check if the watcher has been called
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(value.load(Ordering::Relaxed), 1);
 
     // Remove the watcher and assert that nothing changes
-    cpu.remove_condition_code_watcher(WatcherType::OnWrite);
+    cpu.remove_condition_code_watcher(watcher);
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3001, 0b0001_000_000_1_11110); // ADD R0, R0, #-2
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(value.load(Ordering::Relaxed), 1);
 }
 
@@ -79,7 +79,7 @@ fn watcher_on_read() {
 
     // Create a watcher that transforms the Condition Code into a u16
     let value_watcher = value.clone();
-    cpu.add_condition_code_watcher(WatcherType::OnRead, move |new_value| {
+    let watcher = cpu.add_condition_code_watcher(WatcherType::OnRead, move |new_value| {
         value_watcher.store(u16::from(new_value), Ordering::Relaxed);
     });
 
@@ -88,7 +88,7 @@ fn watcher_on_read() {
     cpu.set_memory(0x3000, 0b0001_000_000_1_00001); // ADD R0, R0, #1
 
     // Check that the watcher has NOT been called
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(value.load(Ordering::Relaxed), 0);
 
     // Get the Condition Code
@@ -98,10 +98,10 @@ fn watcher_on_read() {
     assert_eq!(value.load(Ordering::Relaxed), 1);
 
     // Remove the watcher and assert that nothing changes
-    cpu.remove_condition_code_watcher(WatcherType::OnRead);
+    cpu.remove_condition_code_watcher(watcher);
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3001, 0b0001_000_000_1_11110); // ADD R0, R0, #-2
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     let _ = cpu.get_condition_code();
     assert_eq!(value.load(Ordering::Relaxed), 1);
 }
@@ -0,0 +1,63 @@
+use super::*;
+
+use crate::common::BranchCoverage;
+
+#[test]
+fn disabled() {
+    let mut cpu = Lc2::new(0x3000);
+    cpu.step_instruction().unwrap();
+    assert!(cpu.coverage().is_none());
+}
+
+#[test]
+fn hits_and_branches() {
+    // x3000: ADD R1, R1, #-1
+    // x3001: BRp x3000
+    // x3002: BRnzp x3004
+    // x3004: ADD R2, R2, #1
+    let mut cpu = cpu_with_program(&[0x127f, 0x0200, 0x0e04, 0x0000, 0x14a1]);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 2).unwrap();
+    cpu.set_coverage(true);
+    for _ in 0..6 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3005);
+
+    // The unconditional branch is not part of the branch coverage
+    let coverage = cpu.coverage().unwrap();
+    assert_eq!(
+        coverage.hits,
+        [(0x3000, 2), (0x3001, 2), (0x3002, 1), (0x3004, 1)].into()
+    );
+    assert_eq!(
+        coverage.branches,
+        [(
+            0x3001,
+            BranchCoverage {
+                taken: 1,
+                not_taken: 1
+            }
+        )]
+        .into()
+    );
+    assert!(coverage.branches[&0x3001].is_covered());
+}
+
+#[test]
+fn enable_and_disable() {
+    let mut cpu = cpu_with_program(&[0x127f, 0x127f]);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 2).unwrap();
+    cpu.set_coverage(true);
+    cpu.step_instruction().unwrap();
+
+    // Enabling the coverage again keeps what has been collected
+    cpu.set_coverage(true);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.coverage().unwrap().hits.len(), 2);
+
+    // Disabling it forgets everything
+    cpu.set_coverage(false);
+    assert!(cpu.coverage().is_none());
+    cpu.set_coverage(true);
+    assert!(cpu.coverage().unwrap().hits.is_empty());
+}
@@ -0,0 +1,276 @@
+use super::*;
+
+use crate::devices::{Keyboard, Timer};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// Device that counts its reads, saves the last write and makes the same
+/// request after every instruction
+struct Counter {
+    reads: u16,
+    written: Rc<Cell<(u16, u16)>>,
+    request: Option<DeviceRequest>,
+}
+
+impl Device for Counter {
+    fn read(&mut self, _address: u16) -> u16 {
+        self.reads += 1;
+        self.reads
+    }
+
+    fn peek(&self, _address: u16) -> u16 {
+        self.reads
+    }
+
+    fn write(&mut self, address: u16, data: u16) {
+        self.written.set((address, data));
+    }
+
+    fn tick(&mut self) -> Option<DeviceRequest> {
+        self.request
+    }
+}
+
+/// Output stream shared with the test
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn counter(request: Option<DeviceRequest>) -> (Box<Counter>, Rc<Cell<(u16, u16)>>) {
+    let written = Rc::new(Cell::new((0, 0)));
+    let device = Counter {
+        reads: 0,
+        written: written.clone(),
+        request,
+    };
+    (Box::new(device), written)
+}
+
+#[test]
+fn read_and_write() {
+    let mut cpu = Lc2::new(0x3000);
+    let (device, written) = counter(None);
+    cpu.add_device(0x4000..=0x4001, device);
+
+    // The device supplies the values that are read
+    assert_eq!(cpu.get_memory(0x4000), 1);
+    assert_eq!(cpu.get_memory(0x4001), 2);
+    assert_eq!(cpu.get_register(&Register::MemoryDataRegister), 2);
+
+    // The device receives the writes, instead of the memory
+    cpu.set_memory(0x4001, 0x1234);
+    assert_eq!(written.get(), (0x4001, 0x1234));
+    assert_eq!(cpu.get_memory(0x4001), 3);
+
+    // The addresses outside of the range are not mapped
+    cpu.set_memory(0x4002, 0x1234);
+    assert_eq!(cpu.get_memory(0x4002), 0x1234);
+}
+
+#[test]
+fn overlapping_devices() {
+    let mut cpu = Lc2::new(0x3000);
+    let (first, first_written) = counter(None);
+    let (second, second_written) = counter(None);
+    cpu.add_device(0x4000..=0x40ff, first);
+    cpu.add_device(0x4080..=0x4080, second);
+
+    // The last device added handles the addresses in common
+    cpu.set_memory(0x4080, 1);
+    cpu.set_memory(0x4081, 2);
+    assert_eq!(first_written.get(), (0x4081, 2));
+    assert_eq!(second_written.get(), (0x4080, 1));
+}
+
+#[test]
+fn interrupt_request() {
+    let mut cpu = Lc2::new(0x3000);
+    let (device, _) = counter(Some(DeviceRequest::Interrupt(0x6000)));
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x5000).unwrap();
+
+    // After the instruction the processor jumps to the interrupt routine
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_memory(0x5001), 0x3001);
+}
+
+#[test]
+fn halt_request() {
+    let mut cpu = Lc2::new(0x3000);
+    let (device, _) = counter(Some(DeviceRequest::Halt));
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_memory(MACHINE_CONTROL_REGISTER, 0x8123);
+
+    // After the instruction the processor is halted
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x0123);
+}
+
+/// Create a processor with the default devices, returning the output of the
+/// display
+fn with_default_devices(input: &[u8]) -> (Lc2, Rc<RefCell<Vec<u8>>>) {
+    let mut cpu = Lc2::new(0x3000);
+    let output = Rc::new(RefCell::new(Vec::new()));
+    cpu.add_default_devices(
+        Arc::new(Mutex::new(VecDeque::from(input.to_vec()))),
+        Box::new(Output(output.clone())),
+    );
+    (cpu, output)
+}
+
+#[test]
+fn keyboard_and_display() {
+    let (mut cpu, output) = with_default_devices(b"ab");
+
+    // Reading the Keyboard Data Register consumes the input
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0x8000);
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'a'));
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'b'));
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0x0000);
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'b'));
+
+    // The keyboard has no interrupt, so its Interrupt Enable bit can't be set
+    cpu.set_memory(KEYBOARD_STATUS_REGISTER, 0x4000);
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0x0000);
+
+    // The display prints both the characters of a packed word
+    assert_eq!(cpu.get_memory(DISPLAY_STATUS_REGISTER), 0x8000);
+    cpu.set_memory(DISPLAY_DATA_REGISTER, u16::from_be_bytes(*b"ih"));
+    cpu.set_memory(DISPLAY_DATA_REGISTER, u16::from(b'!'));
+    assert_eq!(output.borrow().as_slice(), b"hi!");
+}
+
+#[test]
+fn timer() {
+    let (mut cpu, _) = with_default_devices(b"");
+
+    // The timer expires after the number of instructions in the interval
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 3);
+    assert_eq!(cpu.get_memory(TIMER_INTERVAL_REGISTER), 3);
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x8000);
+
+    // Reading the status register clears it
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+
+    // The timer has no interrupt, so its Interrupt Enable bit can't be set
+    cpu.set_memory(TIMER_STATUS_REGISTER, 0x4000);
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+
+    // An interval of 0 stops the timer
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 0);
+    for _ in 0..5 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+}
+
+#[test]
+fn machine_control() {
+    let (mut cpu, _) = with_default_devices(b"");
+    let (device, _) = counter(Some(DeviceRequest::Halt));
+    cpu.add_device(0x4000..=0x4000, device);
+
+    // The processor starts running and is halted by the request
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x8000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x0000);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn keyboard_interrupt() {
+    let mut cpu = Lc2::new(0x3000);
+    let input = Arc::new(Mutex::new(VecDeque::new()));
+    let keyboard = Keyboard::new(
+        KEYBOARD_STATUS_REGISTER,
+        KEYBOARD_DATA_REGISTER,
+        input.clone(),
+    )
+    .with_interrupt(0x6000);
+    cpu.add_device(
+        KEYBOARD_STATUS_REGISTER..=KEYBOARD_DATA_REGISTER,
+        Box::new(keyboard),
+    );
+
+    // Run an infinite loop, with the keyboard interrupt service routine
+    // reading the character into R0. The LC-2 can't mask the interrupts, so
+    // the routine reads the character right away
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x5000).unwrap();
+    cpu.set_memory(0x3000, 0b0000_111_000000000); // x3000: BRnzp x3000
+    cpu.set_memory(0x6000, 0b1010_000_000000010); // x6000: LDI R0, x6002
+    cpu.set_memory(0x6001, 0b1000_000000000000); // x6001: RTI
+    cpu.set_memory(0x6002, KEYBOARD_DATA_REGISTER);
+
+    // The keyboard doesn't interrupt unless its Interrupt Enable bit is set
+    input.lock().unwrap().push_back(b'a');
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+
+    // When the bit is set the processor jumps to the service routine
+    cpu.set_memory(KEYBOARD_STATUS_REGISTER, 0x4000);
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0xc000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+
+    // The service routine reads the key and returns to the loop
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), u16::from(b'a'));
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x5000);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn timer_interrupt() {
+    let mut cpu = Lc2::new(0x3000);
+    let timer = Timer::new(TIMER_STATUS_REGISTER, TIMER_INTERVAL_REGISTER).with_interrupt(0x6000);
+    cpu.add_device(
+        TIMER_STATUS_REGISTER..=TIMER_INTERVAL_REGISTER,
+        Box::new(timer),
+    );
+
+    // Run an infinite loop, with the timer interrupt service routine counting
+    // the interrupts in R1. The LC-2 can't mask the interrupts, so the routine
+    // reads the Status Register right away
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x5000).unwrap();
+    cpu.set_memory(0x3000, 0b0000_111_000000000); // x3000: BRnzp x3000
+    cpu.set_memory(0x6000, 0b1010_010_000000011); // x6000: LDI R2, x6003
+    cpu.set_memory(0x6001, 0b0001_001_001_1_00001); // x6001: ADD R1, R1, #1
+    cpu.set_memory(0x6002, 0b1000_000000000000); // x6002: RTI
+    cpu.set_memory(0x6003, TIMER_STATUS_REGISTER);
+
+    // Make the timer expire every 4 instructions
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 4);
+    cpu.set_memory(TIMER_STATUS_REGISTER, 0x4000);
+
+    // The first interrupt comes after 4 instructions
+    for _ in 0..4 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+
+    // The timer keeps counting during the service routine
+    for _ in 0..16 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 4);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R2)), 0xc000);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+}
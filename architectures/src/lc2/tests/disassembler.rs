@@ -0,0 +1,75 @@
+use super::*;
+use crate::common::SymbolTable;
+
+fn text(word: u16, address: u16) -> String {
+    disassemble(word, address, None).text
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn operate() {
+    assert_eq!(text(0b0001_001_010_0_00_011, 0x3000), "ADD R1, R2, R3");
+    assert_eq!(text(0b0001_001_010_1_11101, 0x3000), "ADD R1, R2, #-3");
+    assert_eq!(text(0b0101_111_000_1_01111, 0x3000), "AND R7, R0, #15");
+    assert_eq!(text(0b1001_100_101_111111, 0x3000), "NOT R4, R5");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn page_relative() {
+    // The page offset refers to the page of the incremented Program Counter
+    let disassembly = disassemble(0b0010_011_000000101, 0x31ff, None);
+    assert_eq!(
+        disassembly.instruction,
+        Instruction::Ld {
+            dr: Gpr::R3,
+            page_offset: 0x0005
+        }
+    );
+    assert_eq!(disassembly.target, Some(0x3205));
+    assert_eq!(disassembly.text, "LD R3, x3205");
+
+    assert_eq!(text(0b0000_101_000010000, 0x3000), "BRnp x3010");
+    assert_eq!(text(0b0000_000_000010000, 0x3000), "NOP");
+    assert_eq!(text(0b0100_1_00_000000101, 0x3000), "JSR x3005");
+    assert_eq!(text(0b0100_0_00_000000101, 0x3000), "JMP x3005");
+    assert_eq!(text(0b1011_010_111111111, 0x3000), "STI R2, x31FF");
+    assert_eq!(text(0b1110_000_000000001, 0x3000), "LEA R0, x3001");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn register_relative() {
+    let disassembly = disassemble(0b0111_001_110_000011, 0x3000, None);
+    assert_eq!(disassembly.target, None);
+    assert_eq!(disassembly.text, "STR R1, R6, #3");
+
+    assert_eq!(text(0b0110_001_110_111111, 0x3000), "LDR R1, R6, #63");
+    assert_eq!(text(0b1100_1_00_010_000001, 0x3000), "JSRR R2, #1");
+    assert_eq!(text(0b1100_0_00_010_000000, 0x3000), "JMPR R2, #0");
+}
+
+#[test]
+fn control() {
+    assert_eq!(text(0xd000, 0x3000), "RET");
+    assert_eq!(text(0x8000, 0x3000), "RTI");
+    assert_eq!(text(0xf025, 0x3000), "HALT");
+    assert_eq!(text(0xf022, 0x3000), "PUTS");
+    assert_eq!(text(0xf0ff, 0x3000), "TRAP xFF");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn symbols() {
+    let symbols = SymbolTable::from([(0x3005, "LOOP".to_owned())]);
+
+    let disassembly = disassemble(0b0000_010_000000101, 0x3000, Some(&symbols));
+    assert_eq!(disassembly.target, Some(0x3005));
+    assert_eq!(disassembly.text, "BRz LOOP");
+
+    // The addresses without a label are left as they are
+    assert_eq!(
+        disassemble(0b0000_010_000000110, 0x3000, Some(&symbols)).text,
+        "BRz x3006"
+    );
+}
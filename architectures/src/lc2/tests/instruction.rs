@@ -0,0 +1,57 @@
+use super::*;
+
+/// Clear the bits of `word` that are not used by its instruction, setting the
+/// lowest 6 bits of NOT
+const fn canonical(word: u16) -> u16 {
+    match word >> 12 {
+        // Register mode of ADD and AND
+        0b0001 | 0b0101 if (word >> 5) & 1 == 0 => word & 0xffe7,
+        0b1001 => word | 0x003f,
+        0b0100 | 0b1100 => word & 0xf9ff,
+        0b1101 | 0b1000 => word & 0xf000,
+        0b1111 => word & 0xf0ff,
+        _ => word,
+    }
+}
+
+#[test]
+fn round_trip() {
+    for word in 0..=u16::MAX {
+        let instruction = Instruction::decode(word);
+        assert_eq!(instruction.encode(), canonical(word), "x{word:04X}");
+        assert_eq!(Instruction::decode(instruction.encode()), instruction);
+    }
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn encode() {
+    assert_eq!(
+        Instruction::Add {
+            dr: Gpr::R1,
+            sr1: Gpr::R2,
+            sr2: Operand::Immediate(-3)
+        }
+        .encode(),
+        0b0001_001_010_1_11101
+    );
+    assert_eq!(
+        Instruction::Jsrr {
+            link: true,
+            base: Gpr::R2,
+            index: 1
+        }
+        .encode(),
+        0b1100_1_00_010_000001
+    );
+
+    // The operands that don't fit into their field are truncated
+    assert_eq!(
+        Instruction::Ld {
+            dr: Gpr::R0,
+            page_offset: 0x3205
+        }
+        .encode(),
+        0b0010_000_000000101
+    );
+}
@@ -9,11 +9,11 @@ fn register_positive() {
     cpu.set_memory(0x3000, 0b0001_000_001_0_00_010);
 
     // Set R1 and R2 to 5
-    cpu.set_register(&Register::Gpr(Gpr::R1), 5);
-    cpu.set_register(&Register::Gpr(Gpr::R2), 5);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), 5).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 10);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Positive);
 }
@@ -27,11 +27,11 @@ fn register_negative() {
     cpu.set_memory(0x3000, 0b0001_000_001_0_00_010);
 
     // Set R1 and R2 to -5
-    cpu.set_register(&Register::Gpr(Gpr::R1), !5 + 1);
-    cpu.set_register(&Register::Gpr(Gpr::R2), !5 + 1);
+    cpu.set_register(&Register::Gpr(Gpr::R1), !5 + 1).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), !5 + 1).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), !10 + 1);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
 }
@@ -45,11 +45,11 @@ fn register_zero() {
     cpu.set_memory(0x3000, 0b0001_000_001_0_00_010);
 
     // Set R1 to 5 and R2 to -5
-    cpu.set_register(&Register::Gpr(Gpr::R1), 5);
-    cpu.set_register(&Register::Gpr(Gpr::R2), !5 + 1);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), !5 + 1).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 0);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Zero);
 }
@@ -63,11 +63,11 @@ fn register_overflow() {
     cpu.set_memory(0x3000, 0b0001_000_001_0_00_010);
 
     // Set R1 and R2 to 65535
-    cpu.set_register(&Register::Gpr(Gpr::R1), u16::MAX);
-    cpu.set_register(&Register::Gpr(Gpr::R2), u16::MAX);
+    cpu.set_register(&Register::Gpr(Gpr::R1), u16::MAX).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), u16::MAX).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), u16::MAX - 1);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
 }
@@ -81,10 +81,10 @@ fn immediate_positive() {
     cpu.set_memory(0x3000, 0b0001_000_001_1_00101);
 
     // Set R1 to 5
-    cpu.set_register(&Register::Gpr(Gpr::R1), 5);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 10);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Positive);
 }
@@ -98,10 +98,10 @@ fn immediate_negative() {
     cpu.set_memory(0x3000, 0b0001_000_001_1_11011);
 
     // Set R1 to -5
-    cpu.set_register(&Register::Gpr(Gpr::R1), !5 + 1);
+    cpu.set_register(&Register::Gpr(Gpr::R1), !5 + 1).unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), !10 + 1);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
 }
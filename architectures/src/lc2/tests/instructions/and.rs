@@ -9,11 +9,13 @@ fn register() {
     cpu.set_memory(0x3000, 0b0101_000_001_0_00_010);
 
     // Set R1 and R2
-    cpu.set_register(&Register::Gpr(Gpr::R1), 0b0000_0000_1111_1111);
-    cpu.set_register(&Register::Gpr(Gpr::R2), 0b0000_1111_0000_1111);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0b0000_0000_1111_1111)
+        .unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0b0000_1111_0000_1111)
+        .unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(
         cpu.get_register(&Register::Gpr(Gpr::R0)),
         0b0000_0000_0000_1111
@@ -30,10 +32,11 @@ fn immediate() {
     cpu.set_memory(0x3000, 0b0101_000_001_1_01111);
 
     // Set R1 to 5
-    cpu.set_register(&Register::Gpr(Gpr::R1), 0b0000_1111_0000_1111);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0b0000_1111_0000_1111)
+        .unwrap();
 
     // Check if the result is correct and if the Condition Code are updated
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(
         cpu.get_register(&Register::Gpr(Gpr::R0)),
         0b0000_0000_0000_1111
@@ -83,13 +83,13 @@ fn run_test_cases(cpu: &mut Lc2, test_cases: &[(ConditionCode, u16)]) {
     // For each test case...
     for (condition_code, address) in test_cases {
         // Set the Program Counter back to 0x3000
-        cpu.set_register(&Register::ProgramCounter, 0x3000);
+        cpu.set_register(&Register::ProgramCounter, 0x3000).unwrap();
 
         // Setup the Condition Code
         cpu.set_condition_code(condition_code);
 
         // Run the instruction and check if the jump has happened
-        cpu.step_instruction();
+        cpu.step_instruction().unwrap();
         assert_eq!(cpu.get_register(&Register::ProgramCounter), *address);
     }
 }
@@ -1,5 +1,9 @@
 use super::*;
 
+use crate::WatcherType;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
 #[test]
 fn jump() {
     let mut cpu = Lc2::new(0x3000);
@@ -10,7 +14,7 @@ fn jump() {
     cpu.set_memory(0x3000, 0b0100_0_00_000001010);
 
     // Run the instruction and check if the jump has happened
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x300A);
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x0000);
 }
@@ -24,7 +28,7 @@ fn jump_to_subroutine() {
     cpu.set_memory(0x3000, 0b0100_1_00_000001010);
 
     // Run the instruction and check if the jump has happened
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x300A);
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
 }
@@ -37,10 +41,10 @@ fn jump_through_register() {
     // saving the Program Counter to R7
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b1100_0_00_000_111111);
-    cpu.set_register(&Register::Gpr(Gpr::R0), 0x2fcb);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 0x2fcb).unwrap();
 
     // Run the instruction and check if the jump has happened
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x300A);
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x0000);
 }
@@ -53,10 +57,34 @@ fn jump_to_subroutine_through_register() {
     // saving the Program Counter to R7
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b1100_1_00_000_111111);
-    cpu.set_register(&Register::Gpr(Gpr::R0), 0x2fcb);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 0x2fcb).unwrap();
 
     // Run the instruction and check if the jump has happened
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x300A);
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
 }
+
+#[test]
+fn jump_to_subroutine_keeps_condition_code() {
+    let mut cpu = Lc2::new(0x3000);
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Count the writes to the Condition Code
+    let writes = Rc::new(AtomicU16::new(0));
+    let writes_watcher = writes.clone();
+    cpu.add_condition_code_watcher(WatcherType::OnWrite, move |_| {
+        writes_watcher.fetch_add(1, Ordering::Relaxed);
+    });
+
+    // Set the 0x3000 address to jump to 0x300A saving the Program Counter to
+    // R7: a positive value that would set the Condition Code to P
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_1_00_000001010);
+
+    // Run the instruction and check that the Condition Code hasn't been written
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+    assert_eq!(writes.load(Ordering::Relaxed), 0);
+}
@@ -10,7 +10,23 @@ fn load() {
     cpu.set_memory(0x31FF, 42);
 
     // Assert that the memory cell is loaded correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+}
+
+#[test]
+fn load_through_register_wraps_around() {
+    let mut cpu = Lc2::new(0x3000);
+
+    // Set the memory to load 42 into R0, with an address past the end of the
+    // memory
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0110_000_001_000010);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0xFFFF).unwrap();
+    cpu.set_memory(0x0001, 42);
+
+    // Assert that the address wraps around
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
 }
 
@@ -25,7 +41,7 @@ fn load_indirect() {
     cpu.set_memory(0x6000, 42);
 
     // Assert that the memory cell is loaded correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
 }
 
@@ -36,11 +52,11 @@ fn load_through_register() {
     // Set the memory to load 42 into R0, loading the address through R1
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b0110_000_001_111111);
-    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000).unwrap();
     cpu.set_memory(0x603F, 42);
 
     // Assert that the memory cell is loaded correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
 }
 
@@ -53,6 +69,6 @@ fn load_effective_address() {
     cpu.set_memory(0x3000, 0b1110_000_111111111);
 
     // Assert that the address is loaded correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 0x31FF);
 }
@@ -7,8 +7,8 @@ fn not() {
     // Set the memory to negate R1 into R0
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b1001_000_001_111111);
-    cpu.set_register(&Register::Gpr(Gpr::R1), 42);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 42).unwrap();
 
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), !42);
 }
@@ -10,8 +10,8 @@ fn r#return() {
     cpu.set_memory(0x300A, 0b1101_000000000000); // RET
 
     // Assert that the Program Counter is back on the main routine
-    cpu.step_instruction();
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
 }
 
@@ -23,12 +23,12 @@ fn return_from_interrupt() {
     // negative Condition Code and 0x6000 as the return address
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b1000_000000000000);
-    cpu.set_register(&Register::Gpr(Gpr::R6), 0x300B);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x300B).unwrap();
     cpu.set_memory(0x300B, 0x8000);
     cpu.set_memory(0x300A, 0x6000);
 
     // Asserts that the code returns succesfully from an interrupt
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
     assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
 }
@@ -7,10 +7,10 @@ fn load() {
     // Set the memory to store 42 from R0 into memory
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b0011_000_111111111);
-    cpu.set_register(&Register::Gpr(Gpr::R0), 42);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
 
     // Assert that the memory cell is stored correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_memory(0x31FF), 42);
 }
 
@@ -23,10 +23,10 @@ fn store_indirect() {
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b1011_000_111111111);
     cpu.set_memory(0x31FF, 0x6000);
-    cpu.set_register(&Register::Gpr(Gpr::R0), 42);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
 
     // Assert that the memory cell is stored correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_memory(0x6000), 42);
 }
 
@@ -38,10 +38,10 @@ fn store_through_register() {
     // through R1
     #[allow(clippy::unusual_byte_groupings)]
     cpu.set_memory(0x3000, 0b0111_000_001_111111);
-    cpu.set_register(&Register::Gpr(Gpr::R0), 42);
-    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000).unwrap();
 
     // Assert that the memory cell is stored correctly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_memory(0x603F), 42);
 }
@@ -1,5 +1,9 @@
 use super::*;
 
+use crate::WatcherType;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
 #[test]
 fn trap() {
     let mut cpu = Lc2::new(0x3000);
@@ -12,11 +16,11 @@ fn trap() {
     // For every addres in the Trap Vector...
     for i in 0..0xff {
         // Setup the CPU to trap vector element
-        cpu.set_register(&Register::ProgramCounter, 0x3000);
+        cpu.set_register(&Register::ProgramCounter, 0x3000).unwrap();
         cpu.set_memory(0x3000, 0xf000 + i);
 
         // Assert that the jump to the trap address has happened
-        cpu.step_instruction();
+        cpu.step_instruction().unwrap();
         assert_eq!(cpu.get_register(&Register::ProgramCounter), !i);
     }
 }
@@ -32,8 +36,34 @@ fn return_from_trap() {
     cpu.set_memory(0x6000, 0b1101_000000000000);
 
     // Assert that the Program Counter is updated accordingly
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
-    cpu.step_instruction();
+    cpu.step_instruction().unwrap();
     assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
 }
+
+#[test]
+fn trap_keeps_condition_code() {
+    let mut cpu = Lc2::new(0x3000);
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Count the writes to the Condition Code
+    let writes = Rc::new(AtomicU16::new(0));
+    let writes_watcher = writes.clone();
+    cpu.add_condition_code_watcher(WatcherType::OnWrite, move |_| {
+        writes_watcher.fetch_add(1, Ordering::Relaxed);
+    });
+
+    // Set the 0x3000 address to trap through the vector at 42, saving the
+    // Program Counter to R7: a positive value that would set the Condition
+    // Code to P
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1111_0000_00101010);
+    cpu.set_memory(42, 0x6000);
+
+    // Run the instruction and check that the Condition Code hasn't been written
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+    assert_eq!(writes.load(Ordering::Relaxed), 0);
+}
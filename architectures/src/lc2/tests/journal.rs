@@ -0,0 +1,145 @@
+use super::*;
+
+use crate::devices::{Device, MachineControl};
+use crate::WatcherType;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Machine Control Register that counts how many times it's read
+struct ReadCounter(Rc<Cell<u16>>);
+
+impl Device for ReadCounter {
+    fn read(&mut self, address: u16) -> u16 {
+        self.0.set(self.0.get() + 1);
+        self.peek(address)
+    }
+
+    fn peek(&self, _address: u16) -> u16 {
+        0x8000
+    }
+
+    fn write(&mut self, _address: u16, _data: u16) {}
+}
+
+#[test]
+fn step_back() {
+    // ADD R1, R1, #1
+    // STR R1, R2, #0
+    // STR R3, R4, #0
+    let mut cpu = cpu_with_program(&[0x1261, 0x7280, 0x7700]);
+    cpu.set_history_depth(10);
+    cpu.add_device(
+        MACHINE_CONTROL_REGISTER..=MACHINE_CONTROL_REGISTER,
+        Box::new(MachineControl::new()),
+    );
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R4), MACHINE_CONTROL_REGISTER)
+        .unwrap();
+    for _ in 0..3 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 1);
+    assert_eq!(cpu.get_memory(0x4000), 1);
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0);
+
+    // Undo the halt
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x8000);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3002);
+
+    // Undo the store
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_memory(0x4000), 0);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
+
+    // Undo the addition
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 0);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+
+    // The journal is empty
+    assert!(!cpu.step_back());
+}
+
+#[test]
+fn disabled_journal() {
+    // ADD R1, R1, #1
+    let mut cpu = cpu_with_program(&[0x1261]);
+    cpu.set_history_depth(0);
+    cpu.step_instruction().unwrap();
+    assert!(!cpu.step_back());
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 1);
+}
+
+#[test]
+fn bounded_depth() {
+    // ADD R1, R1, #1
+    let mut cpu = cpu_with_program(&[0x1261; 5]);
+    cpu.set_history_depth(3);
+    for _ in 0..5 {
+        cpu.step_instruction().unwrap();
+    }
+
+    // Only the last 3 instructions can be undone
+    assert!(cpu.step_back());
+    cpu.set_history_depth(1);
+    assert!(cpu.step_back());
+    assert!(!cpu.step_back());
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 3);
+}
+
+#[test]
+fn run_back_to() {
+    // ADD R1, R1, #1
+    let mut cpu = cpu_with_program(&[0x1261; 5]);
+    cpu.set_history_depth(10);
+    for _ in 0..5 {
+        cpu.step_instruction().unwrap();
+    }
+
+    assert!(cpu.run_back_to(0x3002));
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 2);
+
+    // Stop at the start of the journal
+    assert!(!cpu.run_back_to(0x5000));
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 0);
+}
+
+#[test]
+fn no_watchers() {
+    // STR R1, R2, #0
+    let mut cpu = cpu_with_program(&[0x7280]);
+    cpu.set_history_depth(10);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.step_instruction().unwrap();
+
+    let called = Rc::new(Cell::new(false));
+    let called_watcher = called.clone();
+    cpu.add_memory_watcher(0x4000, WatcherType::OnWrite, move |_| {
+        called_watcher.set(true);
+    });
+    cpu.add_register_watcher(&Register::Gpr(Gpr::R2), WatcherType::OnWrite, |_| {
+        panic!("The watcher shouldn't be called");
+    });
+    assert!(cpu.step_back());
+    assert!(!called.get());
+}
+
+#[test]
+fn journal_doesnt_read_devices() {
+    // ADD R1, R1, #1
+    let mut cpu = cpu_with_program(&[0x1261]);
+    cpu.set_history_depth(10);
+    let reads = Rc::new(Cell::new(0));
+    cpu.add_device(
+        MACHINE_CONTROL_REGISTER..=MACHINE_CONTROL_REGISTER,
+        Box::new(ReadCounter(reads.clone())),
+    );
+
+    // Saving the Machine Control Register into the journal doesn't read it
+    cpu.step_instruction().unwrap();
+    assert!(cpu.step_back());
+    assert_eq!(reads.get(), 0);
+}
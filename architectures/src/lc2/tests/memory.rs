@@ -1,6 +1,7 @@
 use super::*;
 
 use crate::WatcherType;
+use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU16, Ordering};
 
@@ -37,7 +38,7 @@ fn load_bytes_fails() {
 
     assert_eq!(
         cpu.load_bytes(0xffff, &[1, 2]),
-        Err("The array of byte is too big")
+        Err(crate::Error::LoadOutOfBounds)
     );
 }
 
@@ -57,7 +58,7 @@ fn watchers_on_write() {
 
         // Create a watcher that negates the value in memory
         let value_watcher = value.clone();
-        cpu.add_memory_watcher(address, WatcherType::OnWrite, move |new_value| {
+        let watcher = cpu.add_memory_watcher(address, WatcherType::OnWrite, move |new_value| {
             value_watcher.store(!new_value, Ordering::Relaxed);
         });
 
@@ -74,7 +75,7 @@ fn watchers_on_write() {
         assert_eq!(value.load(Ordering::Relaxed), !address);
 
         // Remove the watcher and assert that nothing changes
-        cpu.remove_memory_watcher(address, WatcherType::OnWrite);
+        cpu.remove_memory_watcher(watcher);
         cpu.set_memory(address, u16::from(address == 0));
         assert_eq!(value.load(Ordering::Relaxed), !address);
     }
@@ -96,7 +97,7 @@ fn watchers_on_read() {
 
         // Create a watcher that negates the value in memory
         let value_watcher = value.clone();
-        cpu.add_memory_watcher(address, WatcherType::OnRead, move |new_value| {
+        let watcher = cpu.add_memory_watcher(address, WatcherType::OnRead, move |new_value| {
             value_watcher.store(!new_value, Ordering::Relaxed);
         });
 
@@ -113,9 +114,78 @@ fn watchers_on_read() {
         assert_eq!(value.load(Ordering::Relaxed), !address);
 
         // Remove the watcher and assert that nothing changes
-        cpu.remove_memory_watcher(address, WatcherType::OnRead);
+        cpu.remove_memory_watcher(watcher);
         cpu.set_memory(address, u16::from(address == 0));
         let _ = cpu.get_memory(address);
         assert_eq!(value.load(Ordering::Relaxed), !address);
     }
 }
+
+#[test]
+fn multiple_watchers() {
+    // Create a new LC2 and a list to store the order of the calls
+    let mut cpu = Lc2::new(0x3000);
+    let calls = Rc::new(RefCell::new(Vec::new()));
+
+    // Add two watchers on the same address
+    let calls_watcher = calls.clone();
+    let first = cpu.add_memory_watcher(0x4000, WatcherType::OnWrite, move |data| {
+        calls_watcher.borrow_mut().push((1, data));
+    });
+    let calls_watcher = calls.clone();
+    cpu.add_memory_watcher(0x4000, WatcherType::OnWrite, move |data| {
+        calls_watcher.borrow_mut().push((2, data));
+    });
+
+    // Check that both the watchers are called, in the order in which they
+    // have been added
+    cpu.set_memory(0x4000, 42);
+    assert_eq!(*calls.borrow(), [(1, 42), (2, 42)]);
+
+    // Remove the first watcher and check that the second one is still called
+    cpu.remove_memory_watcher(first);
+    cpu.set_memory(0x4000, 43);
+    assert_eq!(*calls.borrow(), [(1, 42), (2, 42), (2, 43)]);
+}
+
+#[test]
+fn range_watchers() {
+    // Create a new LC2 and a list to store the accessed cells
+    let mut cpu = Lc2::new(0x3000);
+    let calls = Rc::new(RefCell::new(Vec::new()));
+
+    // Add a watcher for the reads and one for the writes of a range
+    let calls_watcher = calls.clone();
+    let reads = cpu.add_memory_range_watcher(
+        0x4000..=0x40ff,
+        WatcherType::OnRead,
+        move |address, data| calls_watcher.borrow_mut().push(("read", address, data)),
+    );
+    let calls_watcher = calls.clone();
+    cpu.add_memory_range_watcher(
+        0x4000..=0x40ff,
+        WatcherType::OnWrite,
+        move |address, data| calls_watcher.borrow_mut().push(("write", address, data)),
+    );
+
+    // Check that the watchers receive the address, and that the cells
+    // outside of the range are not watched
+    cpu.set_memory(0x4000, 1);
+    cpu.set_memory(0x40ff, 2);
+    cpu.set_memory(0x4100, 3);
+    let _ = cpu.get_memory(0x40ff);
+    let _ = cpu.get_memory(0x3fff);
+    assert_eq!(
+        *calls.borrow(),
+        [
+            ("write", 0x4000, 1),
+            ("write", 0x40ff, 2),
+            ("read", 0x40ff, 2)
+        ]
+    );
+
+    // Remove the read watcher
+    cpu.remove_memory_watcher(reads);
+    let _ = cpu.get_memory(0x4000);
+    assert_eq!(calls.borrow().len(), 3);
+}
@@ -13,7 +13,7 @@ fn get_and_set() {
         let register = Register::Gpr(Gpr::try_from(i as usize).unwrap());
 
         // Set the register
-        cpu.set_register(&register, 3000 + i);
+        cpu.set_register(&register, 3000 + i).unwrap();
 
         // Check if the register has been set
         assert_eq!(cpu.get_register(&register), 3000 + i);
@@ -28,9 +28,8 @@ fn u8_to_gpr() {
 }
 
 #[test]
-#[should_panic(expected = "Only numbers between 0 and 7 can be converted into registers!")]
 fn u8_to_invalid_gpr() {
-    Gpr::try_from(8).unwrap();
+    assert_eq!(Gpr::try_from(8), Err(crate::Error::InvalidRegister(8)));
 }
 
 #[test]
@@ -68,7 +67,7 @@ fn watchers_on_write() {
         // life, the universe, and everything to the value that is put into the
         // register
         let value_watcher = value.clone();
-        cpu.add_register_watcher(&register, WatcherType::OnWrite, move |new_value| {
+        let watcher = cpu.add_register_watcher(&register, WatcherType::OnWrite, move |new_value| {
             value_watcher.store(new_value + 42, Ordering::Relaxed);
         });
 
@@ -79,13 +78,13 @@ fn watchers_on_write() {
         assert_eq!(value.load(Ordering::Relaxed), 0);
 
         // Set the register and check that the watcher has been called
-        cpu.set_register(&register, 3000 + i);
+        cpu.set_register(&register, 3000 + i).unwrap();
 
         assert_eq!(value.load(Ordering::Relaxed), 3042 + i);
 
         // Remove the watcher and assert that nothing changes
-        cpu.remove_register_watcher(&register, WatcherType::OnWrite);
-        cpu.set_register(&register, 6000 + i);
+        cpu.remove_register_watcher(watcher);
+        cpu.set_register(&register, 6000 + i).unwrap();
         assert_eq!(value.load(Ordering::Relaxed), 3042 + i);
     }
 }
@@ -107,12 +106,12 @@ fn watchers_on_read() {
         // life, the universe, and everything to the value that is put into the
         // register
         let value_watcher = value.clone();
-        cpu.add_register_watcher(&register, WatcherType::OnRead, move |new_value| {
+        let watcher = cpu.add_register_watcher(&register, WatcherType::OnRead, move |new_value| {
             value_watcher.store(new_value + 42, Ordering::Relaxed);
         });
 
         // Set the register
-        cpu.set_register(&register, 3000 + i);
+        cpu.set_register(&register, 3000 + i).unwrap();
 
         // Check that the watcher has NOT been called
         assert_eq!(value.load(Ordering::Relaxed), 0);
@@ -124,8 +123,8 @@ fn watchers_on_read() {
         assert_eq!(value.load(Ordering::Relaxed), 3042 + i);
 
         // Remove the watcher and assert that nothing changes
-        cpu.remove_register_watcher(&register, WatcherType::OnRead);
-        cpu.set_register(&register, 6000 + i);
+        cpu.remove_register_watcher(watcher);
+        cpu.set_register(&register, 6000 + i).unwrap();
         let _ = cpu.get_register(&register);
         assert_eq!(value.load(Ordering::Relaxed), 3042 + i);
     }
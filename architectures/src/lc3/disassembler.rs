@@ -0,0 +1,80 @@
+use super::{Gpr, Instruction};
+use crate::common::{label, operand_text, register_name, trap_alias, Disassembly, SymbolTable};
+
+/// Disassemble the instruction `word` stored at `address`, replacing the
+/// addresses it references with their labels from `symbols`
+#[must_use]
+pub fn disassemble(
+    word: u16,
+    address: u16,
+    symbols: Option<&SymbolTable>,
+) -> Disassembly<Instruction> {
+    let instruction = Instruction::decode(word);
+    let target = instruction.target(address);
+    let target_label = || label(target.unwrap_or_default(), symbols);
+
+    let text = match &instruction {
+        Instruction::Add { dr, sr1, sr2 } => {
+            format!(
+                "ADD {}, {}, {}",
+                register_name(dr),
+                register_name(sr1),
+                operand_text(sr2)
+            )
+        }
+        Instruction::And { dr, sr1, sr2 } => {
+            format!(
+                "AND {}, {}, {}",
+                register_name(dr),
+                register_name(sr1),
+                operand_text(sr2)
+            )
+        }
+        Instruction::Br { n, z, p, .. } => {
+            if *n || *z || *p {
+                let flags: String = [(*n, 'n'), (*z, 'z'), (*p, 'p')]
+                    .iter()
+                    .filter_map(|(set, flag)| set.then_some(*flag))
+                    .collect();
+                format!("BR{flags} {}", target_label())
+            } else {
+                "NOP".to_owned()
+            }
+        }
+        Instruction::Jmp { base: Gpr::R7 } => "RET".to_owned(),
+        Instruction::Jmp { base } => format!("JMP {}", register_name(base)),
+        Instruction::Jsr { .. } => format!("JSR {}", target_label()),
+        Instruction::Jsrr { base } => format!("JSRR {}", register_name(base)),
+        Instruction::Ld { dr, .. } => format!("LD {}, {}", register_name(dr), target_label()),
+        Instruction::Ldi { dr, .. } => format!("LDI {}, {}", register_name(dr), target_label()),
+        Instruction::Ldr { dr, base, offset } => {
+            format!(
+                "LDR {}, {}, #{offset}",
+                register_name(dr),
+                register_name(base)
+            )
+        }
+        Instruction::Lea { dr, .. } => format!("LEA {}, {}", register_name(dr), target_label()),
+        Instruction::Not { dr, sr } => format!("NOT {}, {}", register_name(dr), register_name(sr)),
+        Instruction::Rti => "RTI".to_owned(),
+        Instruction::St { sr, .. } => format!("ST {}, {}", register_name(sr), target_label()),
+        Instruction::Sti { sr, .. } => format!("STI {}, {}", register_name(sr), target_label()),
+        Instruction::Str { sr, base, offset } => {
+            format!(
+                "STR {}, {}, #{offset}",
+                register_name(sr),
+                register_name(base)
+            )
+        }
+        Instruction::Trap { vector } => {
+            trap_alias(*vector).map_or_else(|| format!("TRAP x{vector:02X}"), str::to_owned)
+        }
+        Instruction::Reserved => format!(".FILL x{word:04X}"),
+    };
+
+    Disassembly {
+        instruction,
+        target,
+        text,
+    }
+}
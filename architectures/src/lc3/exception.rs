@@ -0,0 +1,26 @@
+/// Exceptions that can be raised by the LC-3 while executing an instruction
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Exception {
+    /// An RTI instruction was executed in "User" privilege mode
+    PrivilegeModeViolation,
+
+    /// An instruction with the reserved opcode (1101) was executed
+    IllegalOpcode,
+
+    /// A memory location of the system space (0x0000-0x2fff) or of the I/O
+    /// page (0xfe00-0xffff) was accessed in "User" privilege mode
+    AccessControlViolation,
+}
+
+impl Exception {
+    /// Get the entry of the interrupt vector table associated with the
+    /// exception
+    #[must_use]
+    pub const fn vector(&self) -> u16 {
+        match self {
+            Self::PrivilegeModeViolation => 0x00,
+            Self::IllegalOpcode => 0x01,
+            Self::AccessControlViolation => 0x02,
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use super::Gpr;
+use crate::common::{self, field, gpr, operand, register, sign_extend};
+
+/// Mnemonics of the opcodes, indexed by the 4 most significant bits of the
+/// instructions
+pub const OPCODES: [&str; 16] = [
+    "BR", "ADD", "LD", "ST", "JSR", "AND", "LDR", "STR", "RTI", "NOT", "LDI", "STI", "JMP",
+    "RESERVED", "LEA", "TRAP",
+];
+
+/// Second source operand of ADD and AND
+pub type Operand = common::Operand<Gpr>;
+
+/// LC-3 instruction, with its operands as they are encoded
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Instruction {
+    Add {
+        dr: Gpr,
+        sr1: Gpr,
+        sr2: Operand,
+    },
+    And {
+        dr: Gpr,
+        sr1: Gpr,
+        sr2: Operand,
+    },
+    Br {
+        n: bool,
+        z: bool,
+        p: bool,
+        pc_offset: i16,
+    },
+    /// JMP, or RET if `base` is R7
+    Jmp {
+        base: Gpr,
+    },
+    Jsr {
+        pc_offset: i16,
+    },
+    Jsrr {
+        base: Gpr,
+    },
+    Ld {
+        dr: Gpr,
+        pc_offset: i16,
+    },
+    Ldi {
+        dr: Gpr,
+        pc_offset: i16,
+    },
+    Ldr {
+        dr: Gpr,
+        base: Gpr,
+        offset: i16,
+    },
+    Lea {
+        dr: Gpr,
+        pc_offset: i16,
+    },
+    Not {
+        dr: Gpr,
+        sr: Gpr,
+    },
+    Rti,
+    St {
+        sr: Gpr,
+        pc_offset: i16,
+    },
+    Sti {
+        sr: Gpr,
+        pc_offset: i16,
+    },
+    Str {
+        sr: Gpr,
+        base: Gpr,
+        offset: i16,
+    },
+    Trap {
+        vector: u8,
+    },
+    /// Opcode 1101, that raises an Illegal Opcode exception
+    Reserved,
+}
+
+impl Instruction {
+    /// Decode an instruction, ignoring the bits that are not used by it
+    #[must_use]
+    pub fn decode(word: u16) -> Self {
+        let gpr = |offset| gpr(word, offset);
+        let flag = |bit: u8| (word >> bit) & 1 == 1;
+        let pc_offset = sign_extend(word, 9);
+
+        match word >> 12 {
+            0b0001 | 0b0101 => {
+                let sr2 = if flag(5) {
+                    Operand::Immediate(sign_extend(word, 5))
+                } else {
+                    Operand::Register(gpr(0))
+                };
+                if word >> 12 == 0b0001 {
+                    Self::Add {
+                        dr: gpr(9),
+                        sr1: gpr(6),
+                        sr2,
+                    }
+                } else {
+                    Self::And {
+                        dr: gpr(9),
+                        sr1: gpr(6),
+                        sr2,
+                    }
+                }
+            }
+            0b0000 => Self::Br {
+                n: flag(11),
+                z: flag(10),
+                p: flag(9),
+                pc_offset,
+            },
+            0b1100 => Self::Jmp { base: gpr(6) },
+            0b0100 if flag(11) => Self::Jsr {
+                pc_offset: sign_extend(word, 11),
+            },
+            0b0100 => Self::Jsrr { base: gpr(6) },
+            0b0010 => Self::Ld {
+                dr: gpr(9),
+                pc_offset,
+            },
+            0b1010 => Self::Ldi {
+                dr: gpr(9),
+                pc_offset,
+            },
+            0b0110 => Self::Ldr {
+                dr: gpr(9),
+                base: gpr(6),
+                offset: sign_extend(word, 6),
+            },
+            0b1110 => Self::Lea {
+                dr: gpr(9),
+                pc_offset,
+            },
+            0b1001 => Self::Not {
+                dr: gpr(9),
+                sr: gpr(6),
+            },
+            0b1000 => Self::Rti,
+            0b0011 => Self::St {
+                sr: gpr(9),
+                pc_offset,
+            },
+            0b1011 => Self::Sti {
+                sr: gpr(9),
+                pc_offset,
+            },
+            0b0111 => Self::Str {
+                sr: gpr(9),
+                base: gpr(6),
+                offset: sign_extend(word, 6),
+            },
+            0b1111 => Self::Trap {
+                vector: word.to_be_bytes()[1],
+            },
+            0b1101 => Self::Reserved,
+            0b10000..=u16::MAX => unreachable!(),
+        }
+    }
+
+    /// Encode the instruction. The bits that are not used by the instruction
+    /// are set to 0, except for the lowest 6 bits of NOT that are set to 1,
+    /// and the operands that don't fit into their field are truncated
+    #[must_use]
+    pub fn encode(&self) -> u16 {
+        let flag = |flag: &bool, bit: u8| u16::from(*flag) << bit;
+
+        match self {
+            Self::Add { dr, sr1, sr2 } => {
+                0x1000 | register(dr, 9) | register(sr1, 6) | operand(sr2)
+            }
+            Self::And { dr, sr1, sr2 } => {
+                0x5000 | register(dr, 9) | register(sr1, 6) | operand(sr2)
+            }
+            Self::Br { n, z, p, pc_offset } => {
+                flag(n, 11) | flag(z, 10) | flag(p, 9) | field(*pc_offset, 9)
+            }
+            Self::Jmp { base } => 0xc000 | register(base, 6),
+            Self::Jsr { pc_offset } => 0x4800 | field(*pc_offset, 11),
+            Self::Jsrr { base } => 0x4000 | register(base, 6),
+            Self::Ld { dr, pc_offset } => 0x2000 | register(dr, 9) | field(*pc_offset, 9),
+            Self::Ldi { dr, pc_offset } => 0xa000 | register(dr, 9) | field(*pc_offset, 9),
+            Self::Ldr { dr, base, offset } => {
+                0x6000 | register(dr, 9) | register(base, 6) | field(*offset, 6)
+            }
+            Self::Lea { dr, pc_offset } => 0xe000 | register(dr, 9) | field(*pc_offset, 9),
+            Self::Not { dr, sr } => 0x9000 | register(dr, 9) | register(sr, 6) | 0x003f,
+            Self::Rti => 0x8000,
+            Self::St { sr, pc_offset } => 0x3000 | register(sr, 9) | field(*pc_offset, 9),
+            Self::Sti { sr, pc_offset } => 0xb000 | register(sr, 9) | field(*pc_offset, 9),
+            Self::Str { sr, base, offset } => {
+                0x7000 | register(sr, 9) | register(base, 6) | field(*offset, 6)
+            }
+            Self::Trap { vector } => 0xf000 | u16::from(*vector),
+            Self::Reserved => 0xd000,
+        }
+    }
+
+    /// Get the absolute address referenced by the instruction, if it's stored
+    /// at `address`. The offsets are relative to the incremented Program
+    /// Counter
+    #[must_use]
+    pub const fn target(&self, address: u16) -> Option<u16> {
+        match self {
+            Self::Br { pc_offset, .. }
+            | Self::Jsr { pc_offset }
+            | Self::Ld { pc_offset, .. }
+            | Self::Ldi { pc_offset, .. }
+            | Self::Lea { pc_offset, .. }
+            | Self::St { pc_offset, .. }
+            | Self::Sti { pc_offset, .. } => {
+                Some(address.wrapping_add(1).wrapping_add_signed(*pc_offset))
+            }
+            _ => None,
+        }
+    }
+}
@@ -4,17 +4,73 @@ mod tests;
 mod registers;
 pub use registers::{Gpr, Register};
 
+mod instruction;
+pub use instruction::{Instruction, Operand, OPCODES};
+
+mod disassembler;
+pub use disassembler::disassemble;
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
+mod exception;
+pub use exception::Exception;
+
 use crate::{
     common::{
-        ConditionCode, ConditionCodeWatchersStorage, Memory16x16, MemoryWatchersStorage,
-        RegisterWatchersStorage,
+        ConditionCode, ConditionCodeWatchersStorage, Counter, Coverage, Journal, Memory16x16,
+        MemoryRangeWatchersStorage, MemoryWatchersStorage, Profile, RegisterWatchersStorage,
+        Statistics, TraceRecord, Tracer, WatchersStorage,
     },
-    Architecture, WatcherType,
+    devices::{Device, DeviceRequest, Display, Keyboard, MachineControl, Timer},
+    Architecture, Error, StepOutcome, WatcherId, WatcherType,
 };
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+/// Address of the Keyboard Status Register
+pub const KEYBOARD_STATUS_REGISTER: u16 = 0xfe00;
+/// Address of the Keyboard Data Register
+pub const KEYBOARD_DATA_REGISTER: u16 = 0xfe02;
+/// Interrupt requested by the keyboard, with priority 4 and vector x80
+pub const KEYBOARD_INTERRUPT: u16 = 0x0480;
+/// Address of the Display Status Register
+pub const DISPLAY_STATUS_REGISTER: u16 = 0xfe04;
+/// Address of the Display Data Register
+pub const DISPLAY_DATA_REGISTER: u16 = 0xfe06;
+/// Address of the Timer Status Register
+pub const TIMER_STATUS_REGISTER: u16 = 0xfe08;
+/// Address of the Timer Interval Register
+pub const TIMER_INTERVAL_REGISTER: u16 = 0xfe0a;
+/// Interrupt requested by the timer, with priority 6 and vector x81
+pub const TIMER_INTERRUPT: u16 = 0x0681;
+/// Address of the Machine Control Register
+pub const MACHINE_CONTROL_REGISTER: u16 = 0xfffe;
 
+/// Registers saved in the undo journal before each instruction, including
+/// the Machine Control Register to undo a halt
+struct SavedRegisters {
+    general_purpose_register: [u16; 8],
+    program_counter: u16,
+    instruction_register: u16,
+    processor_status_register: u16,
+    saved_usp: u16,
+    saved_ssp: u16,
+    memory_address_register: u16,
+    memory_data_register: u16,
+    machine_control_register: u16,
+}
+
+/// LC-3 processor
+///
+/// It follows the ISA in Appendix A of Patt and Patel, "Introduction to
+/// Computing Systems" (3rd edition): LEA doesn't set the Condition Code, and
+/// TRAP leaves R7 alone, saving the Processor Status Register and the Program
+/// Counter on the supervisor stack like an interrupt
 #[derive(Default)]
 pub struct Lc3 {
     // Registers
@@ -30,13 +86,33 @@ pub struct Lc3 {
     memory_data_register: u16,
     memory: Memory16x16,
 
+    // Undo journal
+    journal: Journal<SavedRegisters>,
+
+    // Trace of the last instruction
+    tracer: Tracer<Register, ConditionCode>,
+
+    // Statistics of the execution
+    counter: Counter,
+
+    // Coverage of the program, if enabled
+    coverage: Option<Coverage>,
+
+    // Shadow call stack, if the profiling is enabled
+    profile: Option<Profile>,
+
     // Watchers
     register_watchers: RegisterWatchersStorage<Register>,
     memory_watchers: MemoryWatchersStorage<u16>,
+    memory_range_watchers: MemoryRangeWatchersStorage<u16>,
     condition_code_watchers: ConditionCodeWatchersStorage,
+    exception_watchers: WatchersStorage<(), Exception>,
 }
 
 impl Lc3 {
+    /// Create a processor starting from `initial_address` in "User" privilege
+    /// mode, with priority 0 and the Condition Code Z. The supervisor stack
+    /// starts at x3000 and the user stack at xFE00
     #[must_use]
     pub fn new(initial_address: u16) -> Self {
         Self {
@@ -47,6 +123,62 @@ impl Lc3 {
             ..Default::default()
         }
     }
+
+    /// Map the keyboard, the display, the timer and the Machine Control
+    /// Register at their standard addresses. The keyboard reads the
+    /// characters from `input_buffer` and the display writes them to
+    /// `output`. If enabled, the keyboard and the timer request
+    /// `KEYBOARD_INTERRUPT` and `TIMER_INTERRUPT`
+    pub fn add_default_devices(
+        &mut self,
+        input_buffer: Arc<Mutex<VecDeque<u8>>>,
+        output: Box<dyn Write>,
+    ) {
+        self.add_device(
+            KEYBOARD_STATUS_REGISTER..=KEYBOARD_DATA_REGISTER,
+            Box::new(
+                Keyboard::new(
+                    KEYBOARD_STATUS_REGISTER,
+                    KEYBOARD_DATA_REGISTER,
+                    input_buffer,
+                )
+                .with_interrupt(KEYBOARD_INTERRUPT),
+            ),
+        );
+        self.add_device(
+            DISPLAY_STATUS_REGISTER..=DISPLAY_DATA_REGISTER,
+            Box::new(Display::new(
+                DISPLAY_STATUS_REGISTER,
+                DISPLAY_DATA_REGISTER,
+                false,
+                output,
+            )),
+        );
+        self.add_device(
+            TIMER_STATUS_REGISTER..=TIMER_INTERVAL_REGISTER,
+            Box::new(
+                Timer::new(TIMER_STATUS_REGISTER, TIMER_INTERVAL_REGISTER)
+                    .with_interrupt(TIMER_INTERRUPT),
+            ),
+        );
+        self.add_device(
+            MACHINE_CONTROL_REGISTER..=MACHINE_CONTROL_REGISTER,
+            Box::new(MachineControl::new()),
+        );
+    }
+
+    /// Add a watcher that is called every time an exception is raised, before
+    /// jumping to its service routine
+    pub fn add_exception_watcher<F>(&mut self, function: F) -> WatcherId
+    where
+        F: Fn(Exception) + 'static,
+    {
+        self.exception_watchers.insert((), Box::new(function))
+    }
+
+    pub fn remove_exception_watcher(&mut self, id: WatcherId) {
+        self.exception_watchers.remove(id);
+    }
 }
 
 impl fmt::Debug for Lc3 {
@@ -58,29 +190,7 @@ impl fmt::Debug for Lc3 {
             .map(|(i, value)| (Gpr::try_from(i).ok(), value))
             .collect();
 
-        let condition_code = match self.processor_status_register & 0b111 {
-            0b100 => ConditionCode::Negative,
-            0b010 => ConditionCode::Zero,
-            0b001 => ConditionCode::Positive,
-            _ => todo!("Condition Code is not valid"),
-        };
-
-        let condition_code_watchers: &Vec<WatcherType> = &self
-            .condition_code_watchers
-            .iter()
-            .enumerate()
-            .filter_map(|(i, watcher)| {
-                if watcher.is_some() {
-                    Some(if i == 0 {
-                        WatcherType::OnWrite
-                    } else {
-                        WatcherType::OnRead
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
+        let condition_code = condition_code(self.processor_status_register);
 
         fmt.debug_struct("Lc3")
             .field("memory", &self.memory)
@@ -93,9 +203,25 @@ impl fmt::Debug for Lc3 {
             .field("saved_ssp", &self.saved_ssp)
             .field("memory_address_register", &self.memory_address_register)
             .field("memory_data_register", &self.memory_data_register)
+            .field("journal", &self.journal.len())
+            .field("tracer", &self.tracer.last())
+            .field("statistics", self.counter.statistics())
+            .field(
+                "coverage",
+                &self.coverage.as_ref().map(|coverage| coverage.hits.len()),
+            )
+            .field("profile", &self.profile.is_some())
             .field("register_watchers", &self.register_watchers.keys())
             .field("memory_watchers", &self.memory_watchers.keys())
-            .field("condition_code_watchers", &condition_code_watchers)
+            .field(
+                "memory_range_watchers",
+                &self.memory_range_watchers.ranges(),
+            )
+            .field(
+                "condition_code_watchers",
+                &self.condition_code_watchers.keys(),
+            )
+            .field("exception_watchers", &self.exception_watchers.keys().len())
             .finish()
     }
 }
@@ -106,52 +232,55 @@ impl Architecture for Lc3 {
     type Register = Register;
     type RegisterData = u16;
     type ConditionCode = ConditionCode;
+    type Snapshot = Snapshot;
 
-    #[must_use]
     fn get_memory(&mut self, address: Self::Address) -> Self::Data {
-        // TODO: Implement the Access Control Violation (ACV) exception
-
-        let data = self.memory[address];
+        let data = self.memory.read(address);
 
         self.memory_address_register = address;
         self.memory_data_register = data;
+        self.tracer
+            .record_memory(address, data, WatcherType::OnRead);
+        self.counter.record_memory(&WatcherType::OnRead);
 
-        // If there is a watcher for this address, call it
-        if let Some(function) = self.memory_watchers.get(&(address, WatcherType::OnRead)) {
-            function(data);
-        }
+        // Call the watchers of this address
+        self.memory_watchers
+            .call(&(address, WatcherType::OnRead), &data);
+        self.memory_range_watchers
+            .call(&address, &WatcherType::OnRead, &data);
 
         data
     }
 
     fn set_memory(&mut self, address: Self::Address, data: Self::Data) {
-        // TODO: Implement the Access Control Violation (ACV) exception
-
         self.memory_address_register = address;
         self.memory_data_register = data;
 
-        self.memory[address] = data;
+        self.journal.record_memory(address, self.memory[address]);
+        self.tracer
+            .record_memory(address, data, WatcherType::OnWrite);
+        self.counter.record_memory(&WatcherType::OnWrite);
+        self.memory.write(address, data);
 
-        // If there is a watcher for this address, call it
-        if let Some(function) = self.memory_watchers.get(&(address, WatcherType::OnWrite)) {
-            function(data);
-        }
+        // Call the watchers of this address
+        self.memory_watchers
+            .call(&(address, WatcherType::OnWrite), &data);
+        self.memory_range_watchers
+            .call(&address, &WatcherType::OnWrite, &data);
     }
 
-    fn load_bytes(
-        &mut self,
-        start_address: Self::Address,
-        bytes: &[u8],
-    ) -> Result<(), &'static str> {
+    fn peek_memory(&self, address: Self::Address) -> Self::Data {
+        self.memory.peek(address)
+    }
+
+    fn load_bytes(&mut self, start_address: Self::Address, bytes: &[u8]) -> Result<(), Error> {
         // Calculate the address of the last cell
         let data_size: usize = std::mem::size_of::<Self::Data>();
-        let end_address: usize = start_address as usize
-            + (bytes.len() / data_size)
-            + usize::from(bytes.len() % data_size != 0);
+        let end_address: usize = start_address as usize + bytes.len().div_ceil(data_size);
 
         // Return an error if the byte array is too big
         if end_address > Self::Address::MAX as usize {
-            return Err("The array of byte is too big");
+            return Err(Error::LoadOutOfBounds);
         }
 
         // Save the Memory Address Register and the Memory Data Register
@@ -181,13 +310,12 @@ impl Architecture for Lc3 {
             .for_each(|(address, data)| self.set_memory(address, data));
 
         // Restore the Memory Address Register and the Memory Data Register
-        self.set_register(&Register::MemoryAddressRegister, memory_address_register);
-        self.set_register(&Register::MemoryDataRegister, memory_data_register);
+        self.update_register(&Register::MemoryAddressRegister, memory_address_register);
+        self.update_register(&Register::MemoryDataRegister, memory_data_register);
 
         Ok(())
     }
 
-    #[must_use]
     fn get_register(&self, register: &Self::Register) -> Self::RegisterData {
         let data = match register {
             Register::Gpr(gpr) => self.general_purpose_register[u8::from(gpr.clone()) as usize],
@@ -198,68 +326,41 @@ impl Architecture for Lc3 {
             Register::MemoryDataRegister => self.memory_data_register,
         };
 
-        // If there is a watcher for this register, call it
-        if let Some(function) = self
-            .register_watchers
-            .get(&(register.clone(), WatcherType::OnRead))
-        {
-            function(data);
-        }
+        // Call the watchers of this register
+        self.register_watchers
+            .call(&(register.clone(), WatcherType::OnRead), &data);
 
         data
     }
 
-    fn set_register(&mut self, register: &Self::Register, data: Self::RegisterData) {
-        // Get a mutable pointer to the register
-        let register_pointer: &mut u16 = match register {
-            Register::Gpr(gpr) => {
-                // When the register to update is a General Purpose Register
-                // update the condition code
-                self.set_condition_code(&ConditionCode::from(data));
-
-                &mut self.general_purpose_register[u8::from(gpr.clone()) as usize]
-            }
-            Register::ProgramCounter => &mut self.program_counter,
-            Register::InstructionRegister => &mut self.instruction_register,
-
-            // Check if the condition code is valid
-            Register::ProcessorStatusRegister => {
-                if (data & 0b111).count_ones() != 1 {
-                    todo!("Condition Code is not valid");
-                }
-
-                &mut self.processor_status_register
-            }
-
-            Register::MemoryAddressRegister => &mut self.memory_address_register,
-            Register::MemoryDataRegister => &mut self.memory_data_register,
-        };
+    fn set_register(
+        &mut self,
+        register: &Self::Register,
+        data: Self::RegisterData,
+    ) -> Result<(), Error> {
+        // The Processor Status Register must have exactly one Condition Code
+        // bit set
+        if *register == Register::ProcessorStatusRegister && condition_code(data).is_none() {
+            return Err(Error::InvalidConditionCode(data));
+        }
 
-        // Update the register
-        *register_pointer = data;
+        self.update_register(register, data);
 
-        // If there is a watcher for this register, call it
-        if let Some(function) = self
-            .register_watchers
-            .get(&(register.clone(), WatcherType::OnWrite))
-        {
-            function(data);
-        }
+        Ok(())
     }
 
-    #[must_use]
     fn get_condition_code(&self) -> Self::ConditionCode {
+        // The Processor Status Register can't be written with an invalid
+        // Condition Code, so if neither N nor Z are set then P is
         let condition_code = match self.processor_status_register & 0b111 {
-            0b100 => Self::ConditionCode::Negative,
-            0b010 => Self::ConditionCode::Zero,
-            0b001 => Self::ConditionCode::Positive,
-            _ => todo!("Condition Code is not valid"),
+            0b100 => ConditionCode::Negative,
+            0b010 => ConditionCode::Zero,
+            _ => ConditionCode::Positive,
         };
 
-        // If there is a watcher for the condition code, call it
-        if let Some(function) = &self.condition_code_watchers[1] {
-            function(condition_code.clone());
-        }
+        // Call the watchers of the condition code
+        self.condition_code_watchers
+            .call(&WatcherType::OnRead, &condition_code);
 
         condition_code
     }
@@ -272,10 +373,9 @@ impl Architecture for Lc3 {
                 Self::ConditionCode::Positive => 0b001,
             };
 
-        // If there is a watcher for the condition code, call it
-        if let Some(function) = &self.condition_code_watchers[0] {
-            function(condition_code.clone());
-        }
+        // Call the watchers of the condition code
+        self.condition_code_watchers
+            .call(&WatcherType::OnWrite, condition_code);
     }
 
     fn add_memory_watcher<F>(
@@ -283,15 +383,31 @@ impl Architecture for Lc3 {
         address: Self::Address,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::Data) + 'static,
     {
         self.memory_watchers
-            .insert((address, watcher_type), Box::new(function));
+            .insert((address, watcher_type), Box::new(function))
     }
 
-    fn remove_memory_watcher(&mut self, address: Self::Address, watcher_type: WatcherType) {
-        self.memory_watchers.remove(&(address, watcher_type));
+    fn remove_memory_watcher(&mut self, id: WatcherId) {
+        if !self.memory_watchers.remove(id) {
+            self.memory_range_watchers.remove(id);
+        }
+    }
+
+    fn add_memory_range_watcher<F>(
+        &mut self,
+        addresses: RangeInclusive<Self::Address>,
+        watcher_type: WatcherType,
+        function: F,
+    ) -> WatcherId
+    where
+        F: Fn(Self::Address, Self::Data) + 'static,
+    {
+        self.memory_range_watchers
+            .insert(addresses, watcher_type, Box::new(function))
     }
 
     fn add_register_watcher<F>(
@@ -299,34 +415,74 @@ impl Architecture for Lc3 {
         register: &Self::Register,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::RegisterData) + 'static,
     {
         self.register_watchers
-            .insert((register.clone(), watcher_type), Box::new(function));
+            .insert((register.clone(), watcher_type), Box::new(function))
     }
 
-    fn remove_register_watcher(&mut self, register: &Self::Register, watcher_type: WatcherType) {
-        self.register_watchers
-            .remove(&(register.clone(), watcher_type));
+    fn remove_register_watcher(&mut self, id: WatcherId) {
+        self.register_watchers.remove(id);
     }
 
-    fn add_condition_code_watcher<F>(&mut self, watcher_type: WatcherType, function: F)
+    fn add_condition_code_watcher<F>(&mut self, watcher_type: WatcherType, function: F) -> WatcherId
     where
         F: Fn(Self::ConditionCode) + 'static,
     {
-        let idx = usize::from(watcher_type != WatcherType::OnWrite);
-        self.condition_code_watchers[idx] = Some(Box::new(function));
+        self.condition_code_watchers
+            .insert(watcher_type, Box::new(function))
     }
 
-    fn remove_condition_code_watcher(&mut self, watcher_type: WatcherType) {
-        let idx = usize::from(watcher_type != WatcherType::OnWrite);
-        self.condition_code_watchers[idx] = None;
+    fn remove_condition_code_watcher(&mut self, id: WatcherId) {
+        self.condition_code_watchers.remove(id);
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn step_instruction(&mut self) {
-        todo!()
+    fn add_device(&mut self, addresses: RangeInclusive<Self::Address>, device: Box<dyn Device>) {
+        self.memory.map(addresses, device);
+    }
+
+    fn step_instruction(&mut self) -> Result<StepOutcome, Error> {
+        // Save the registers in the undo journal
+        if self.journal.is_enabled() {
+            let registers = self.saved_registers();
+            self.journal.begin(registers);
+        }
+        self.tracer.begin(self.program_counter);
+        self.counter.begin();
+
+        // Execute the instruction and, if it raises an exception, jump to the
+        // exception service routine
+        let address = self.program_counter;
+        let outcome = match self.execute_instruction() {
+            Ok(()) => StepOutcome::Executed,
+            Err(Fault::Exception(exception)) => {
+                self.raise_exception(&exception);
+                StepOutcome::Exception(exception.vector())
+            }
+
+            // If the instruction can't be executed, point the Program Counter
+            // back to it
+            Err(Fault::Error(error)) => {
+                self.update_register(&Register::ProgramCounter, address);
+                self.journal.discard();
+                self.tracer.discard();
+                self.counter.discard();
+                return Err(error);
+            }
+        };
+
+        // The accesses made by the devices are not counted, while the ones
+        // made to enter an exception service routine are
+        self.counter.end();
+        self.tick_devices();
+        self.journal.end();
+
+        // Record the Condition Code without calling its watchers
+        self.tracer
+            .end(condition_code(self.processor_status_register).unwrap_or_default());
+        Ok(outcome)
     }
 
     fn interrupt(&mut self, data: Self::Data) {
@@ -336,38 +492,544 @@ impl Architecture for Lc3 {
             return;
         }
 
-        // Save the current Process Status Register into a temp variable
-        let temp = self.get_register(&Register::ProcessorStatusRegister);
+        // Set the privilege mode to "Supervisor" and set the correct priority,
+        // then jump to the address pointed by the interrupt vector
+        self.counter.record_interrupt();
+        let processor_status_register = (self.processor_status_register & 0x78ff) | (data & 0x0700);
+        self.enter_service_routine(processor_status_register, 0x0100 | (data & 0x00ff));
+    }
 
-        // Set the privilege mode to "Supervisor" and set the correct priority
-        self.set_register(
-            &Register::ProcessorStatusRegister,
-            (temp & 0x78ff) | (data & 0x0700),
+    fn set_history_depth(&mut self, depth: usize) {
+        self.journal.set_depth(depth);
+    }
+
+    fn step_back(&mut self) -> bool {
+        let Some(entry) = self.journal.pop() else {
+            return false;
+        };
+
+        // Restore the memory cells in the reverse order in which they have
+        // been written, then the registers
+        for (address, data) in entry.memory.into_iter().rev() {
+            self.memory[address] = data;
+        }
+        self.restore_registers(&entry.registers);
+
+        true
+    }
+
+    fn run_back_to(&mut self, address: Self::Address) -> bool {
+        while self.step_back() {
+            if self.program_counter == address {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn set_tracing(&mut self, enabled: bool) {
+        self.tracer.set_enabled(enabled);
+    }
+
+    fn last_trace(&self) -> Option<&TraceRecord<Self::Register, Self::ConditionCode>> {
+        self.tracer.last()
+    }
+
+    fn statistics(&self) -> &Statistics {
+        self.counter.statistics()
+    }
+
+    fn reset_statistics(&mut self) {
+        self.counter.reset();
+    }
+
+    fn set_coverage(&mut self, enabled: bool) {
+        if !enabled {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some(Coverage::default());
+        }
+    }
+
+    fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    fn set_profiling(&mut self, enabled: bool) {
+        if !enabled {
+            self.profile = None;
+        } else if self.profile.is_none() {
+            self.profile = Some(Profile::default());
+        }
+    }
+
+    fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        Snapshot {
+            general_purpose_registers: self.general_purpose_register,
+            program_counter: self.program_counter,
+            instruction_register: self.instruction_register,
+            processor_status_register: self.processor_status_register,
+            saved_usp: self.saved_usp,
+            saved_ssp: self.saved_ssp,
+            memory_address_register: self.memory_address_register,
+            memory_data_register: self.memory_data_register,
+            memory: Box::new(*self.memory.cells()),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &Self::Snapshot) -> Result<(), Error> {
+        // Don't restore a Processor Status Register with an invalid Condition
+        // Code
+        let processor_status_register = snapshot.processor_status_register;
+        if condition_code(processor_status_register).is_none() {
+            return Err(Error::InvalidConditionCode(processor_status_register));
+        }
+
+        self.general_purpose_register = snapshot.general_purpose_registers;
+        self.program_counter = snapshot.program_counter;
+        self.instruction_register = snapshot.instruction_register;
+        self.processor_status_register = processor_status_register;
+        self.saved_usp = snapshot.saved_usp;
+        self.saved_ssp = snapshot.saved_ssp;
+        self.memory_address_register = snapshot.memory_address_register;
+        self.memory_data_register = snapshot.memory_data_register;
+        self.memory.set_cells(&snapshot.memory);
+
+        Ok(())
+    }
+}
+
+impl Lc3 {
+    /// Update a register, along with the Condition Code if it's a General
+    /// Purpose Register
+    fn update_register(&mut self, register: &Register, data: u16) {
+        // When the register to update is a General Purpose Register update the
+        // condition code
+        if let Register::Gpr(_) = register {
+            self.set_condition_code(&ConditionCode::from(data));
+        }
+
+        self.write_register(register, data);
+    }
+
+    /// Update a register without touching the Condition Code
+    fn write_register(&mut self, register: &Register, data: u16) {
+        // Get a mutable pointer to the register
+        let register_pointer: &mut u16 = match register {
+            Register::Gpr(gpr) => {
+                &mut self.general_purpose_register[u8::from(gpr.clone()) as usize]
+            }
+            Register::ProgramCounter => &mut self.program_counter,
+            Register::InstructionRegister => &mut self.instruction_register,
+            Register::ProcessorStatusRegister => &mut self.processor_status_register,
+
+            Register::MemoryAddressRegister => &mut self.memory_address_register,
+            Register::MemoryDataRegister => &mut self.memory_data_register,
+        };
+
+        // Update the register
+        *register_pointer = data;
+        self.tracer.record_register(register.clone(), data);
+
+        // Call the watchers of this register
+        self.register_watchers
+            .call(&(register.clone(), WatcherType::OnWrite), &data);
+    }
+
+    /// Save the registers for the undo journal
+    fn saved_registers(&self) -> SavedRegisters {
+        SavedRegisters {
+            general_purpose_register: self.general_purpose_register,
+            program_counter: self.program_counter,
+            instruction_register: self.instruction_register,
+            processor_status_register: self.processor_status_register,
+            saved_usp: self.saved_usp,
+            saved_ssp: self.saved_ssp,
+            memory_address_register: self.memory_address_register,
+            memory_data_register: self.memory_data_register,
+            machine_control_register: self.memory.peek(MACHINE_CONTROL_REGISTER),
+        }
+    }
+
+    /// Restore the registers saved in the undo journal
+    fn restore_registers(&mut self, registers: &SavedRegisters) {
+        self.general_purpose_register = registers.general_purpose_register;
+        self.program_counter = registers.program_counter;
+        self.instruction_register = registers.instruction_register;
+        self.processor_status_register = registers.processor_status_register;
+        self.saved_usp = registers.saved_usp;
+        self.saved_ssp = registers.saved_ssp;
+        self.memory_address_register = registers.memory_address_register;
+        self.memory_data_register = registers.memory_data_register;
+        self.memory
+            .write(MACHINE_CONTROL_REGISTER, registers.machine_control_register);
+    }
+
+    /// Count the direction of the branch at `address` in the coverage, if
+    /// its condition `flags` is neither always nor never true
+    fn record_branch_coverage(&mut self, address: u16, flags: [bool; 3], taken: bool) {
+        if let Some(coverage) = &mut self.coverage {
+            if flags.contains(&true) && flags.contains(&false) {
+                coverage.record_branch(address, taken);
+            }
+        }
+    }
+
+    /// Enter the routine at `routine`, that returns to `return_address`, in
+    /// the profile
+    fn profile_call(&mut self, routine: u16, return_address: u16) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_call(routine, return_address);
+        }
+    }
+
+    /// Return to `address` in the profile
+    fn profile_return(&mut self, address: u16) {
+        if let Some(profile) = &mut self.profile {
+            profile.record_return(address);
+        }
+    }
+
+    /// Update the devices and handle their requests
+    fn tick_devices(&mut self) {
+        for request in self.memory.tick() {
+            match request {
+                DeviceRequest::Interrupt(data) => self.interrupt(data),
+                DeviceRequest::Halt => {
+                    let data = self.get_memory(MACHINE_CONTROL_REGISTER);
+                    self.set_memory(MACHINE_CONTROL_REGISTER, data & 0x7fff);
+                }
+            }
+        }
+    }
+
+    /// Fetch, decode and execute the next instruction, returning an `Err` if
+    /// it raises an exception or if it can't be executed
+    fn execute_instruction(&mut self) -> Result<(), Fault> {
+        // Get the next instruction
+        let address = self.get_register(&Register::ProgramCounter);
+        self.check_access(address)?;
+        let instruction = self.get_memory(address);
+
+        // Update the Instruction Register
+        self.update_register(&Register::InstructionRegister, instruction);
+
+        // Increment the Program Counter
+        self.update_register(
+            &Register::ProgramCounter,
+            self.get_register(&Register::ProgramCounter).wrapping_add(1),
         );
+        self.tracer.fetched(instruction);
+        self.counter.fetched(instruction);
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record_instruction(address);
+        }
+        if let Some(profile) = &mut self.profile {
+            profile.record_instruction();
+        }
 
-        // Get the stack pointer register
-        let register = Register::Gpr(Gpr::R6);
+        // Decode the instruction and execute it
+        match Instruction::decode(instruction) {
+            // Add and And, both with register and immediate
+            Instruction::Add { dr, sr1, sr2 } => {
+                let data = self
+                    .get_register(&Register::Gpr(sr1))
+                    .wrapping_add(self.operand(sr2));
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::And { dr, sr1, sr2 } => {
+                let data = self.get_register(&Register::Gpr(sr1)) & self.operand(sr2);
+                self.update_register(&Register::Gpr(dr), data);
+            }
+
+            // Branch (BR)
+            Instruction::Br { n, z, p, pc_offset } => {
+                // Check if one of the condition codes in the instruction
+                // matches the condition code in the Processor Status Register
+                let flags = u16::from(n) << 2 | u16::from(z) << 1 | u16::from(p);
+
+                let taken = flags & self.processor_status_register & 0b111 != 0;
+                self.counter.record_branch(taken);
+                self.record_branch_coverage(address, [n, z, p], taken);
+                if taken {
+                    let address = self.pc_relative(pc_offset);
+                    self.update_register(&Register::ProgramCounter, address);
+                }
+            }
+
+            // Jump (JMP) and Return (RET)
+            Instruction::Jmp { base } => {
+                // Set the Program Counter to the content of the base register
+                let address = self.get_register(&Register::Gpr(base.clone()));
+                self.update_register(&Register::ProgramCounter, address);
+                if base == Gpr::R7 {
+                    self.profile_return(address);
+                }
+            }
+
+            // Jump to Subroutine (JSR) and Jump to Subroutine through Register
+            // (JSRR). The new address has to be computed before saving the
+            // Program Counter, in case the base register is R7
+            Instruction::Jsr { pc_offset } => {
+                let address = self.pc_relative(pc_offset);
+                self.jump_to_subroutine(address);
+            }
+            Instruction::Jsrr { base } => {
+                let address = self.get_register(&Register::Gpr(base));
+                self.jump_to_subroutine(address);
+            }
+
+            // Load (LD), Load Indirect (LDI), Load through Register (LDR) and
+            // Load Effective Address (LEA)
+            Instruction::Ld { dr, pc_offset } => {
+                let data = self.load(self.pc_relative(pc_offset))?;
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Ldi { dr, pc_offset } => {
+                let address = self.load(self.pc_relative(pc_offset))?;
+                let data = self.load(address)?;
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Ldr { dr, base, offset } => {
+                let address = self
+                    .get_register(&Register::Gpr(base))
+                    .wrapping_add_signed(offset);
+                let data = self.load(address)?;
+                self.update_register(&Register::Gpr(dr), data);
+            }
+            Instruction::Lea { dr, pc_offset } => {
+                let address = self.pc_relative(pc_offset);
+                self.write_register(&Register::Gpr(dr), address);
+            }
+
+            // Store (ST), Store Indirect (STI) and Store through Register (STR)
+            Instruction::St { sr, pc_offset } => {
+                self.store(self.pc_relative(pc_offset), &sr)?;
+            }
+            Instruction::Sti { sr, pc_offset } => {
+                let address = self.load(self.pc_relative(pc_offset))?;
+                self.store(address, &sr)?;
+            }
+            Instruction::Str { sr, base, offset } => {
+                let address = self
+                    .get_register(&Register::Gpr(base))
+                    .wrapping_add_signed(offset);
+                self.store(address, &sr)?;
+            }
+
+            // Not
+            Instruction::Not { dr, sr } => {
+                self.update_register(&Register::Gpr(dr), !self.get_register(&Register::Gpr(sr)));
+            }
+
+            // Return from Interrupt (RTI)
+            Instruction::Rti => self.return_from_interrupt()?,
+
+            // Trap
+            Instruction::Trap { vector } => {
+                self.counter.record_trap(vector);
+
+                // Switch to "Supervisor" privilege mode, keeping the current
+                // priority, and jump to the address pointed by the trap vector
+                let processor_status_register = self.processor_status_register & 0x7fff;
+                self.enter_service_routine(processor_status_register, u16::from(vector));
+            }
+
+            // Reserved
+            Instruction::Reserved => return Err(Exception::IllegalOpcode.into()),
+        }
+
+        Ok(())
+    }
+
+    /// Return an Access Control Violation exception if the address is in the
+    /// system space or in the I/O page and the processor is in "User"
+    /// privilege mode. This is checked only on the accesses made by the
+    /// instructions, so that `get_memory` and `set_memory` can still be used to
+    /// setup the machine
+    const fn check_access(&self, address: u16) -> Result<(), Exception> {
+        if self.processor_status_register >> 15 == 1 && !matches!(address, 0x3000..=0xfdff) {
+            return Err(Exception::AccessControlViolation);
+        }
+
+        Ok(())
+    }
+
+    /// Call the exception watcher, switch to "Supervisor" privilege mode,
+    /// keeping the current priority, and jump to the exception service routine
+    fn raise_exception(&mut self, exception: &Exception) {
+        // Call the watchers of the exceptions
+        self.exception_watchers.call(&(), exception);
+
+        let processor_status_register = self.processor_status_register & 0x7fff;
+        self.enter_service_routine(processor_status_register, 0x0100 | exception.vector());
+    }
+
+    /// Switch to the supervisor stack if needed, push the current Processor
+    /// Status Register and Program Counter on it, load the new Processor Status
+    /// Register and jump to the address contained in `vector_address`
+    fn enter_service_routine(&mut self, processor_status_register: u16, vector_address: u16) {
+        // Save the current Process Status Register into a temp variable
+        let temp = self.get_register(&Register::ProcessorStatusRegister);
 
         // If the interrupted process is in "User" privilege mode then save the
         // current stack pointer into the "Saved USP" and load the "Saved SSP"
         if temp >> 15 == 1 {
+            let register = Register::Gpr(Gpr::R6);
             self.saved_usp = self.get_register(&register);
-            self.set_register(&register, self.saved_ssp);
+            self.update_register(&register, self.saved_ssp);
+        }
+
+        // Push the Process Status Register and the Program Counter into the
+        // stack
+        self.push(temp);
+        self.push(self.get_register(&Register::ProgramCounter));
+
+        // Update the Processor Status Register. This has to be done after
+        // updating R6, otherwise the Condition Code would be overwritten
+        self.update_register(
+            &Register::ProcessorStatusRegister,
+            processor_status_register,
+        );
+
+        // Set the Program Counter to the routine address
+        let return_address = self.program_counter;
+        let routine_address = self.get_memory(vector_address);
+        self.update_register(&Register::ProgramCounter, routine_address);
+        self.profile_call(routine_address, return_address);
+    }
+
+    /// Return from an interrupt or a service routine, restoring the Program
+    /// Counter and the Processor Status Register from the stack
+    fn return_from_interrupt(&mut self) -> Result<(), Fault> {
+        // RTI can only be executed in "Supervisor" privilege mode
+        if self.processor_status_register >> 15 == 1 {
+            return Err(Exception::PrivilegeModeViolation.into());
         }
 
-        // Push the Process Status Register into the stack
-        self.set_register(&register, self.get_register(&register).wrapping_sub(1));
-        self.set_memory(self.get_register(&register), temp);
+        // Check the Processor Status Register on the stack before changing
+        // anything
+        let stack_pointer = self.get_register(&Register::Gpr(Gpr::R6));
+        let processor_status_register = self.get_memory(stack_pointer.wrapping_add(1));
+        if condition_code(processor_status_register).is_none() {
+            return Err(Error::InvalidConditionCode(processor_status_register).into());
+        }
 
-        // Push the Program Counter to the stack
-        self.set_register(&register, self.get_register(&register).wrapping_sub(1));
-        self.set_memory(
-            self.get_register(&register),
+        // Pop the Program Counter and the Processor Status Register
+        // from the stack
+        let address = self.pop();
+        let processor_status_register = self.pop();
+
+        // If the interrupted process was in "User" privilege mode then
+        // save the current stack pointer into the "Saved SSP" and load
+        // the "Saved USP"
+        if processor_status_register >> 15 == 1 {
+            let register = Register::Gpr(Gpr::R6);
+            self.saved_ssp = self.get_register(&register);
+            self.update_register(&register, self.saved_usp);
+        }
+
+        // Restore the Processor Status Register. This has to be done
+        // after updating R6, otherwise the Condition Code would be
+        // overwritten
+        self.update_register(
+            &Register::ProcessorStatusRegister,
+            processor_status_register,
+        );
+
+        // Set the Program Counter to the value popped from the stack
+        self.update_register(&Register::ProgramCounter, address);
+        self.profile_return(address);
+
+        Ok(())
+    }
+
+    /// Get the address at `pc_offset` from the Program Counter
+    fn pc_relative(&self, pc_offset: i16) -> u16 {
+        self.get_register(&Register::ProgramCounter)
+            .wrapping_add_signed(pc_offset)
+    }
+
+    /// Get the value of the second source operand of ADD and AND
+    fn operand(&self, operand: Operand) -> u16 {
+        match operand {
+            Operand::Register(gpr) => self.get_register(&Register::Gpr(gpr)),
+            Operand::Immediate(value) => u16::from_ne_bytes(value.to_ne_bytes()),
+        }
+    }
+
+    /// Save the Program Counter into R7 and jump to `address`. JSR and JSRR
+    /// don't update the Condition Code
+    fn jump_to_subroutine(&mut self, address: u16) {
+        self.write_register(
+            &Register::Gpr(Gpr::R7),
             self.get_register(&Register::ProgramCounter),
         );
+        self.update_register(&Register::ProgramCounter, address);
+        self.profile_call(address, self.general_purpose_register[7]);
+    }
+
+    /// Read the memory on behalf of an instruction
+    fn load(&mut self, address: u16) -> Result<u16, Exception> {
+        self.check_access(address)?;
+        Ok(self.get_memory(address))
+    }
+
+    /// Write a register into the memory on behalf of an instruction
+    fn store(&mut self, address: u16, sr: &Gpr) -> Result<(), Exception> {
+        self.check_access(address)?;
+        self.set_memory(address, self.get_register(&Register::Gpr(sr.clone())));
+        Ok(())
+    }
+
+    /// Push a word on the stack pointed by R6
+    fn push(&mut self, data: u16) {
+        let register = Register::Gpr(Gpr::R6);
+
+        self.update_register(&register, self.get_register(&register).wrapping_sub(1));
+        self.set_memory(self.get_register(&register), data);
+    }
+
+    /// Pop a word from the stack pointed by R6
+    fn pop(&mut self) -> u16 {
+        let register = Register::Gpr(Gpr::R6);
+
+        let data = self.get_memory(self.get_register(&register));
+        self.update_register(&register, self.get_register(&register).wrapping_add(1));
+
+        data
+    }
+}
+
+/// Reason why an instruction hasn't been completed
+enum Fault {
+    Exception(Exception),
+    Error(Error),
+}
+
+impl From<Exception> for Fault {
+    fn from(exception: Exception) -> Self {
+        Self::Exception(exception)
+    }
+}
+
+impl From<Error> for Fault {
+    fn from(error: Error) -> Self {
+        Self::Error(error)
+    }
+}
 
-        // Set the Program Counter to the interrupt routine address
-        let routine_address = self.get_memory(0x0100 | (data & 0x00ff));
-        self.set_register(&Register::ProgramCounter, routine_address);
+/// Get the Condition Code of a Processor Status Register, if it's valid
+const fn condition_code(processor_status_register: u16) -> Option<ConditionCode> {
+    match processor_status_register & 0b111 {
+        0b100 => Some(ConditionCode::Negative),
+        0b010 => Some(ConditionCode::Zero),
+        0b001 => Some(ConditionCode::Positive),
+        _ => None,
     }
 }
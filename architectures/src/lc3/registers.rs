@@ -13,7 +13,7 @@ pub enum Register {
 }
 
 impl TryFrom<usize> for Gpr {
-    type Error = &'static str;
+    type Error = crate::Error;
 
     fn try_from(value: usize) -> Result<Self, Self::Error> {
         match value {
@@ -25,7 +25,7 @@ impl TryFrom<usize> for Gpr {
             5 => Ok(Self::R5),
             6 => Ok(Self::R6),
             7 => Ok(Self::R7),
-            _ => Err("Only numbers between 0 and 7 can be converted into registers!"),
+            _ => Err(crate::Error::InvalidRegister(value)),
         }
     }
 }
@@ -0,0 +1,63 @@
+use crate::common::{read_snapshot, write_snapshot, SnapshotMemory};
+use std::io::{self, Read, Write};
+
+/// State of an LC-3, without its watchers and the state of its devices
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Snapshot {
+    pub general_purpose_registers: [u16; 8],
+    pub program_counter: u16,
+    pub instruction_register: u16,
+    pub processor_status_register: u16,
+    pub saved_usp: u16,
+    pub saved_ssp: u16,
+    pub memory_address_register: u16,
+    pub memory_data_register: u16,
+    /// Cells of the memory, ignoring the devices mapped to them
+    pub memory: SnapshotMemory,
+}
+
+impl Snapshot {
+    /// Write the snapshot to `writer` in the snapshot file format
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `writer` can't be written
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut registers = self.general_purpose_registers.to_vec();
+        registers.extend([
+            self.program_counter,
+            self.instruction_register,
+            self.processor_status_register,
+            self.saved_usp,
+            self.saved_ssp,
+            self.memory_address_register,
+            self.memory_data_register,
+        ]);
+
+        write_snapshot(writer, 3, &registers, &self.memory)
+    }
+
+    /// Read a snapshot written by `write_to`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `reader` can't be read or if it
+    /// doesn't contain a snapshot of an LC-3 in a supported version
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        let (registers, memory) = read_snapshot(reader, 3, 15)?;
+        let general_purpose_registers = std::array::from_fn(|i| registers[i]);
+        let registers = &registers[8..];
+
+        Ok(Self {
+            general_purpose_registers,
+            program_counter: registers[0],
+            instruction_register: registers[1],
+            processor_status_register: registers[2],
+            saved_usp: registers[3],
+            saved_ssp: registers[4],
+            memory_address_register: registers[5],
+            memory_data_register: registers[6],
+            memory,
+        })
+    }
+}
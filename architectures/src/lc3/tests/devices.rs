@@ -0,0 +1,321 @@
+use super::*;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// Device that counts its reads, saves the last write and makes the same
+/// request after every instruction
+struct Counter {
+    reads: u16,
+    written: Rc<Cell<(u16, u16)>>,
+    request: Option<DeviceRequest>,
+}
+
+impl Device for Counter {
+    fn read(&mut self, _address: u16) -> u16 {
+        self.reads += 1;
+        self.reads
+    }
+
+    fn peek(&self, _address: u16) -> u16 {
+        self.reads
+    }
+
+    fn write(&mut self, address: u16, data: u16) {
+        self.written.set((address, data));
+    }
+
+    fn tick(&mut self) -> Option<DeviceRequest> {
+        self.request
+    }
+}
+
+/// Output stream shared with the test
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn counter(request: Option<DeviceRequest>) -> (Box<Counter>, Rc<Cell<(u16, u16)>>) {
+    let written = Rc::new(Cell::new((0, 0)));
+    let device = Counter {
+        reads: 0,
+        written: written.clone(),
+        request,
+    };
+    (Box::new(device), written)
+}
+
+#[test]
+fn read_and_write() {
+    let mut cpu = Lc3::new(0x3000);
+    let (device, written) = counter(None);
+    cpu.add_device(0x4000..=0x4001, device);
+
+    // The device supplies the values that are read
+    assert_eq!(cpu.get_memory(0x4000), 1);
+    assert_eq!(cpu.get_memory(0x4001), 2);
+    assert_eq!(cpu.get_register(&Register::MemoryDataRegister), 2);
+
+    // The device receives the writes, instead of the memory
+    cpu.set_memory(0x4001, 0x1234);
+    assert_eq!(written.get(), (0x4001, 0x1234));
+    assert_eq!(cpu.get_memory(0x4001), 3);
+
+    // The addresses outside of the range are not mapped
+    cpu.set_memory(0x4002, 0x1234);
+    assert_eq!(cpu.get_memory(0x4002), 0x1234);
+}
+
+#[test]
+fn peek() {
+    let mut cpu = Lc3::new(0x3000);
+    let (device, _) = counter(None);
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_memory(0x4001, 0x1234);
+
+    // Peeking doesn't read the device nor change the registers
+    assert_eq!(cpu.get_memory(0x4000), 1);
+    assert_eq!(cpu.peek_memory(0x4000), 1);
+    assert_eq!(cpu.peek_memory(0x4001), 0x1234);
+    assert_eq!(cpu.get_register(&Register::MemoryAddressRegister), 0x4000);
+    assert_eq!(cpu.get_register(&Register::MemoryDataRegister), 1);
+    assert_eq!(cpu.get_memory(0x4000), 2);
+}
+
+#[test]
+fn overlapping_devices() {
+    let mut cpu = Lc3::new(0x3000);
+    let (first, first_written) = counter(None);
+    let (second, second_written) = counter(None);
+    cpu.add_device(0x4000..=0x40ff, first);
+    cpu.add_device(0x4080..=0x4080, second);
+
+    // The last device added handles the addresses in common
+    cpu.set_memory(0x4080, 1);
+    cpu.set_memory(0x4081, 2);
+    assert_eq!(first_written.get(), (0x4081, 2));
+    assert_eq!(second_written.get(), (0x4080, 1));
+}
+
+#[test]
+fn interrupt_request() {
+    let mut cpu = Lc3::new(0x3000);
+    let (device, _) = counter(Some(DeviceRequest::Interrupt(0x0480)));
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_memory(0x0180, 0x6000);
+
+    // After the instruction the processor jumps to the interrupt routine
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0402);
+}
+
+#[test]
+fn masked_interrupt_request() {
+    let mut cpu = Lc3::new(0x3000);
+    let (device, _) = counter(Some(DeviceRequest::Interrupt(0x0480)));
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x8402)
+        .unwrap();
+
+    // An interrupt with the same priority of the program is ignored
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
+}
+
+#[test]
+fn halt_request() {
+    let mut cpu = Lc3::new(0x3000);
+    let (device, _) = counter(Some(DeviceRequest::Halt));
+    cpu.add_device(0x4000..=0x4000, device);
+    cpu.set_memory(MACHINE_CONTROL_REGISTER, 0x8123);
+
+    // After the instruction the processor is halted
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x0123);
+}
+
+/// Create a processor with the default devices, returning the output of the
+/// display
+fn with_default_devices(input: &[u8]) -> (Lc3, Rc<RefCell<Vec<u8>>>) {
+    let mut cpu = Lc3::new(0x3000);
+    let output = Rc::new(RefCell::new(Vec::new()));
+    cpu.add_default_devices(
+        Arc::new(Mutex::new(VecDeque::from(input.to_vec()))),
+        Box::new(Output(output.clone())),
+    );
+    (cpu, output)
+}
+
+#[test]
+fn keyboard_and_display() {
+    let (mut cpu, output) = with_default_devices(b"ab");
+
+    // Reading the Keyboard Data Register consumes the input
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0x8000);
+    assert_eq!(cpu.peek_memory(KEYBOARD_DATA_REGISTER), u16::from(b'a'));
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'a'));
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'b'));
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0x0000);
+    assert_eq!(cpu.get_memory(KEYBOARD_DATA_REGISTER), u16::from(b'b'));
+
+    // The display prints only the low byte
+    assert_eq!(cpu.get_memory(DISPLAY_STATUS_REGISTER), 0x8000);
+    cpu.set_memory(DISPLAY_DATA_REGISTER, u16::from_be_bytes(*b"ih"));
+    cpu.set_memory(DISPLAY_DATA_REGISTER, u16::from(b'!'));
+    assert_eq!(output.borrow().as_slice(), b"h!");
+}
+
+#[test]
+fn timer() {
+    let (mut cpu, _) = with_default_devices(b"");
+
+    // The timer expires after the number of instructions in the interval
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 3);
+    assert_eq!(cpu.get_memory(TIMER_INTERVAL_REGISTER), 3);
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.peek_memory(TIMER_STATUS_REGISTER), 0x8000);
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x8000);
+
+    // Reading the status register clears it
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+
+    // An interval of 0 stops the timer
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 0);
+    for _ in 0..5 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_memory(TIMER_STATUS_REGISTER), 0x0000);
+}
+
+#[test]
+fn machine_control() {
+    let (mut cpu, _) = with_default_devices(b"");
+    let (device, _) = counter(Some(DeviceRequest::Halt));
+    cpu.add_device(0x4000..=0x4000, device);
+
+    // The processor starts running and is halted by the request
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x8000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(MACHINE_CONTROL_REGISTER), 0x0000);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn keyboard_interrupt() {
+    let mut cpu = Lc3::new(0x3000);
+    let input = Arc::new(Mutex::new(VecDeque::new()));
+    cpu.add_default_devices(input.clone(), Box::new(io::sink()));
+
+    // Run an infinite loop in supervisor mode, with the keyboard interrupt
+    // service routine reading the character into R0
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x3000).unwrap();
+    cpu.set_memory(0x3000, 0b0000_111_111111111); // x3000: BRnzp x3000
+    cpu.set_memory(0x0180, 0x6000);
+    cpu.set_memory(0x6000, 0b1010_000_000000001); // x6000: LDI R0, x6002
+    cpu.set_memory(0x6001, 0b1000_000000000000); // x6001: RTI
+    cpu.set_memory(0x6002, KEYBOARD_DATA_REGISTER);
+    cpu.set_memory(KEYBOARD_STATUS_REGISTER, 0x4000);
+
+    // Without input the program keeps looping
+    for _ in 0..3 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+
+    // When a key arrives the processor jumps to the service routine, with
+    // priority 4
+    input.lock().unwrap().push_back(b'a');
+    assert_eq!(cpu.get_memory(KEYBOARD_STATUS_REGISTER), 0xc000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0401);
+
+    // The service routine reads the key and returns to the loop
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), u16::from(b'a'));
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x3000);
+}
+
+#[test]
+fn keyboard_interrupt_masked() {
+    let (mut cpu, _) = with_default_devices(b"a");
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+
+    // The keyboard doesn't interrupt unless its Interrupt Enable bit is set
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
+
+    // The keyboard doesn't interrupt a program with the same priority
+    cpu.set_memory(KEYBOARD_STATUS_REGISTER, 0x4000);
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0402)
+        .unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3002);
+
+    // But it interrupts one with a lower priority
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0302)
+        .unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x0000);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn timer_interrupt() {
+    let (mut cpu, _) = with_default_devices(b"");
+
+    // Run an infinite loop in supervisor mode, with the timer interrupt
+    // service routine counting the interrupts in R1
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x3000).unwrap();
+    cpu.set_memory(0x3000, 0b0000_111_111111111); // x3000: BRnzp x3000
+    cpu.set_memory(0x0181, 0x6000);
+    cpu.set_memory(0x6000, 0b0001_001_001_1_00001); // x6000: ADD R1, R1, #1
+    cpu.set_memory(0x6001, 0b1010_010_000000001); // x6001: LDI R2, x6003
+    cpu.set_memory(0x6002, 0b1000_000000000000); // x6002: RTI
+    cpu.set_memory(0x6003, TIMER_STATUS_REGISTER);
+
+    // Make the timer expire every 4 instructions
+    cpu.set_memory(TIMER_INTERVAL_REGISTER, 4);
+    cpu.set_memory(TIMER_STATUS_REGISTER, 0x4000);
+
+    // The first interrupt comes after 4 instructions, with priority 6
+    for _ in 0..4 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(
+        cpu.get_register(&Register::ProcessorStatusRegister) & 0x0700,
+        0x0600
+    );
+
+    // The timer keeps counting during the service routine
+    for _ in 0..16 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R1)), 4);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R2)), 0xc000);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+}
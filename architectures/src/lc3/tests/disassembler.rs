@@ -0,0 +1,76 @@
+use super::*;
+use crate::common::SymbolTable;
+
+fn text(word: u16, address: u16) -> String {
+    disassemble(word, address, None).text
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn operate() {
+    assert_eq!(text(0b0001_001_010_0_00_011, 0x3000), "ADD R1, R2, R3");
+    assert_eq!(text(0b0001_001_010_1_11101, 0x3000), "ADD R1, R2, #-3");
+    assert_eq!(text(0b0101_111_000_1_01111, 0x3000), "AND R7, R0, #15");
+    assert_eq!(text(0b1001_100_101_111111, 0x3000), "NOT R4, R5");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn pc_relative() {
+    // The offset is relative to the incremented Program Counter
+    let disassembly = disassemble(0b0010_011_111111101, 0x3000, None);
+    assert_eq!(
+        disassembly.instruction,
+        Instruction::Ld {
+            dr: Gpr::R3,
+            pc_offset: -3
+        }
+    );
+    assert_eq!(disassembly.target, Some(0x2ffe));
+    assert_eq!(disassembly.text, "LD R3, x2FFE");
+
+    assert_eq!(text(0b0000_101_000010000, 0x3000), "BRnp x3011");
+    assert_eq!(text(0b0000_111_111111111, 0x3000), "BRnzp x3000");
+    assert_eq!(text(0b0000_000_000010000, 0x3000), "NOP");
+    assert_eq!(text(0b0100_1_01000001001, 0x3000), "JSR x320A");
+    assert_eq!(text(0b1011_010_011111111, 0x3000), "STI R2, x3100");
+    assert_eq!(text(0b1110_000_000000001, 0xffff), "LEA R0, x0001");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn register_relative() {
+    let disassembly = disassemble(0b0111_001_110_111101, 0x3000, None);
+    assert_eq!(disassembly.target, None);
+    assert_eq!(disassembly.text, "STR R1, R6, #-3");
+
+    assert_eq!(text(0b0110_001_110_011111, 0x3000), "LDR R1, R6, #31");
+    assert_eq!(text(0b1100_000_010_000000, 0x3000), "JMP R2");
+    assert_eq!(text(0b0100_0_00_011_000000, 0x3000), "JSRR R3");
+}
+
+#[test]
+fn control() {
+    assert_eq!(text(0xc1c0, 0x3000), "RET");
+    assert_eq!(text(0x8000, 0x3000), "RTI");
+    assert_eq!(text(0xf025, 0x3000), "HALT");
+    assert_eq!(text(0xf024, 0x3000), "PUTSP");
+    assert_eq!(text(0xf0ff, 0x3000), "TRAP xFF");
+    assert_eq!(text(0xd123, 0x3000), ".FILL xD123");
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn symbols() {
+    let symbols = SymbolTable::from([(0x2ffd, "LOOP".to_owned())]);
+
+    let disassembly = disassemble(0b0000_010_111111100, 0x3000, Some(&symbols));
+    assert_eq!(disassembly.target, Some(0x2ffd));
+    assert_eq!(disassembly.text, "BRz LOOP");
+
+    // The addresses without a label are left as they are
+    assert_eq!(
+        disassemble(0b0000_010_111111101, 0x3000, Some(&symbols)).text,
+        "BRz x2FFE"
+    );
+}
@@ -0,0 +1,191 @@
+use super::*;
+
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+#[test]
+fn privilege_mode_violation() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the exception service routine and an RTI in "User" mode
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1000_000000000000);
+    cpu.set_memory(0x0100, 0x1000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x4000).unwrap();
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Assert that the processor jumped to the service routine in "Supervisor"
+    // mode, saving the user stack and pushing the Processor Status Register
+    // and the Program Counter on the supervisor stack
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x1000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0004);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x2FFE);
+    assert_eq!(cpu.get_memory(0x2FFE), 0x3001);
+    assert_eq!(cpu.get_memory(0x2FFF), 0x8004);
+}
+
+#[test]
+fn illegal_opcode() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the exception service routine and an instruction with the
+    // reserved opcode
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1101_000000000000);
+    cpu.set_memory(0x0101, 0x1000);
+
+    // Assert that the processor jumped to the service routine
+    assert_eq!(cpu.step_instruction(), Ok(StepOutcome::Exception(0x01)));
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x1000);
+    assert_eq!(
+        cpu.get_register(&Register::ProcessorStatusRegister) >> 15,
+        0
+    );
+    assert_eq!(cpu.get_memory(0x2FFE), 0x3001);
+}
+
+#[test]
+fn illegal_opcode_keeps_priority() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the exception service routine and an instruction with the
+    // reserved opcode, running with priority 3
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1101_000000000000);
+    cpu.set_memory(0x0101, 0x1000);
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x8302)
+        .unwrap();
+
+    // Assert that the priority has not changed
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0302);
+}
+
+#[test]
+fn access_control_violation() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the exception service routine, the pointers used by the indirect
+    // instructions and the base register
+    cpu.set_memory(0x0102, 0x1000);
+    cpu.set_memory(0x3100, 0xfe00);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0xfffe).unwrap();
+
+    // For every instruction that accesses the system space or the I/O page...
+    #[allow(clippy::unusual_byte_groupings)]
+    for instruction in [
+        0b0010_000_100000000,  // LD R0, 0x2F01
+        0b1010_000_011111111,  // LDI R0, 0x3100 (pointing to 0xFE00)
+        0b0110_000_001_000000, // LDR R0, R1, #0
+        0b0011_000_100000000,  // ST R0, 0x2F01
+        0b1011_000_011111111,  // STI R0, 0x3100 (pointing to 0xFE00)
+        0b0111_000_001_000000, // STR R0, R1, #0
+    ] {
+        // Run the instruction in "User" mode
+        cpu.set_register(&Register::ProcessorStatusRegister, 0x8002)
+            .unwrap();
+        cpu.set_register(&Register::ProgramCounter, 0x3000).unwrap();
+        cpu.set_register(&Register::Gpr(Gpr::R6), 0x4000).unwrap();
+        cpu.set_memory(0x3000, instruction);
+        cpu.step_instruction().unwrap();
+
+        // Assert that the processor jumped to the service routine
+        assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x1000);
+        assert_eq!(
+            cpu.get_register(&Register::ProcessorStatusRegister) >> 15,
+            0
+        );
+        assert_eq!(cpu.get_memory(0x2FFE), 0x3001);
+    }
+}
+
+#[test]
+fn access_control_violation_on_fetch() {
+    let mut cpu = Lc3::new(0x0200);
+
+    // Setup the exception service routine
+    cpu.set_memory(0x0102, 0x1000);
+
+    // Assert that the processor jumped to the service routine, saving the
+    // address of the instruction that couldn't be fetched
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x1000);
+    assert_eq!(cpu.get_memory(0x2FFE), 0x0200);
+}
+
+#[test]
+fn no_access_control_violation_in_supervisor_mode() {
+    let mut cpu = Lc3::new(0x0200);
+
+    // Setup a load from the I/O page in "Supervisor" mode
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x0200, 0b1010_000_000000000);
+    cpu.set_memory(0x0201, 0xfe00);
+    cpu.set_memory(0xfe00, 42);
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+
+    // Assert that the load was executed
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x0201);
+}
+
+#[test]
+fn watcher() {
+    // Create a new LC3 and an atomic u16 to store the watcher results
+    let mut cpu = Lc3::new(0x3000);
+    let value = Rc::new(AtomicU16::new(0xffff));
+
+    // Create a watcher that stores the vector of the exception
+    let value_watcher = value.clone();
+    let watcher = cpu.add_exception_watcher(move |exception| {
+        value_watcher.store(exception.vector(), Ordering::Relaxed);
+    });
+
+    // Raise an Illegal Opcode exception and check if the watcher has been
+    // called
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1101_000000000000);
+    cpu.step_instruction().unwrap();
+    assert_eq!(value.load(Ordering::Relaxed), 0x01);
+
+    // Remove the watcher and assert that nothing changes
+    cpu.remove_exception_watcher(watcher);
+    value.store(0xffff, Ordering::Relaxed);
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x8002)
+        .unwrap();
+    cpu.set_register(&Register::ProgramCounter, 0x3000).unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(value.load(Ordering::Relaxed), 0xffff);
+}
+
+#[test]
+fn return_from_interrupt_with_invalid_condition_code() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the stack to have a Processor Status Register with two Condition
+    // Code bits set
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1000_000000000000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x2FFE).unwrap();
+    cpu.set_memory(0x2FFE, 0x6000);
+    cpu.set_memory(0x2FFF, 0x8003);
+
+    // Assert that the RTI fails without changing the stack and the Processor
+    // Status Register, and that the Program Counter still points to it
+    let processor_status_register = cpu.get_register(&Register::ProcessorStatusRegister);
+    assert_eq!(
+        cpu.step_instruction(),
+        Err(Error::InvalidConditionCode(0x8003))
+    );
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x2FFE);
+    assert_eq!(
+        cpu.get_register(&Register::ProcessorStatusRegister),
+        processor_status_register
+    );
+}
@@ -0,0 +1,29 @@
+use super::*;
+
+use crate::golden::Harness;
+
+use std::io;
+
+#[test]
+fn golden_traces() {
+    Harness {
+        name: "lc3",
+        new: Lc3::new,
+        registers: vec![
+            ("R0", Register::Gpr(Gpr::R0)),
+            ("R1", Register::Gpr(Gpr::R1)),
+            ("R2", Register::Gpr(Gpr::R2)),
+            ("R3", Register::Gpr(Gpr::R3)),
+            ("R4", Register::Gpr(Gpr::R4)),
+            ("R5", Register::Gpr(Gpr::R5)),
+            ("R6", Register::Gpr(Gpr::R6)),
+            ("R7", Register::Gpr(Gpr::R7)),
+            ("PC", Register::ProgramCounter),
+            ("PSR", Register::ProcessorStatusRegister),
+        ],
+        disassemble: |word, address| disassemble(word, address, None).text,
+        devices: |cpu, input| cpu.add_default_devices(input, Box::new(io::sink())),
+        machine_control_register: MACHINE_CONTROL_REGISTER,
+    }
+    .check_corpus();
+}
@@ -0,0 +1,60 @@
+use super::*;
+
+/// Clear the bits of `word` that are not used by its instruction, setting the
+/// lowest 6 bits of NOT
+const fn canonical(word: u16) -> u16 {
+    match word >> 12 {
+        // Register mode of ADD and AND
+        0b0001 | 0b0101 if (word >> 5) & 1 == 0 => word & 0xffe7,
+        0b1001 => word | 0x003f,
+        // JSRR
+        0b0100 if (word >> 11) & 1 == 0 => word & 0xf1c0,
+        0b1100 => word & 0xf1c0,
+        0b1000 | 0b1101 => word & 0xf000,
+        0b1111 => word & 0xf0ff,
+        _ => word,
+    }
+}
+
+#[test]
+fn round_trip() {
+    for word in 0..=u16::MAX {
+        let instruction = Instruction::decode(word);
+        assert_eq!(instruction.encode(), canonical(word), "x{word:04X}");
+        assert_eq!(Instruction::decode(instruction.encode()), instruction);
+    }
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn encode() {
+    assert_eq!(
+        Instruction::Ldr {
+            dr: Gpr::R1,
+            base: Gpr::R6,
+            offset: -3
+        }
+        .encode(),
+        0b0110_001_110_111101
+    );
+    assert_eq!(
+        Instruction::Jsr { pc_offset: -1 }.encode(),
+        0b0100_1_11111111111
+    );
+    assert_eq!(
+        Instruction::Jmp { base: Gpr::R7 }.encode(),
+        0b1100_000_111_000000
+    );
+
+    // The operands that don't fit into their field are truncated
+    assert_eq!(
+        Instruction::Br {
+            n: true,
+            z: false,
+            p: false,
+            pc_offset: 0x0105
+        }
+        .encode(),
+        0b0000_100_100000101
+    );
+}
@@ -0,0 +1,105 @@
+use super::*;
+
+use crate::WatcherType;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+#[test]
+fn jump() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the 0x3000 address to jump to the address contained in R2 without
+    // saving the Program Counter to R7
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1100_000_010_000000);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x6000).unwrap();
+
+    // Run the instruction and check if the jump has happened
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x0000);
+}
+
+#[test]
+fn jump_to_subroutine() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the 0x3000 address to jump to 0x320A saving the Program Counter to R7
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_1_01000001001);
+
+    // Run the instruction and check if the jump has happened
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x320A);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+}
+
+#[test]
+fn jump_to_subroutine_backwards() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the 0x3000 address to jump to 0x2C01 saving the Program Counter to R7
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_1_10000000000);
+
+    // Run the instruction and check if the jump has happened
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x2C01);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+}
+
+#[test]
+fn jump_to_subroutine_through_register() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the 0x3000 address to jump to the address contained in R0 saving the
+    // Program Counter to R7
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_0_00_000_000000);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 0x6000).unwrap();
+
+    // Run the instruction and check if the jump has happened
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+}
+
+#[test]
+fn jump_to_subroutine_through_r7() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the 0x3000 address to jump to the address contained in R7 saving the
+    // Program Counter to R7
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_0_00_111_000000);
+    cpu.set_register(&Register::Gpr(Gpr::R7), 0x6000).unwrap();
+
+    // Run the instruction and check that the old value of R7 has been used
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+}
+
+#[test]
+fn jump_to_subroutine_keeps_condition_code() {
+    let mut cpu = Lc3::new(0x3000);
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Count the writes to the Condition Code
+    let writes = Rc::new(AtomicU16::new(0));
+    let writes_watcher = writes.clone();
+    cpu.add_condition_code_watcher(WatcherType::OnWrite, move |_| {
+        writes_watcher.fetch_add(1, Ordering::Relaxed);
+    });
+
+    // Set the 0x3000 address to jump to 0x3001 saving the Program Counter to R7,
+    // a positive value that would set the Condition Code to P
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_1_00000000000);
+
+    // Run the instruction and check that the Condition Code hasn't been written
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x3001);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+    assert_eq!(writes.load(Ordering::Relaxed), 0);
+}
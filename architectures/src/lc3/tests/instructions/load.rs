@@ -0,0 +1,94 @@
+use super::*;
+
+#[test]
+fn load() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to load 42 into R0
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0010_000_011111111);
+    cpu.set_memory(0x3100, 42);
+
+    // Assert that the memory cell is loaded correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Positive);
+}
+
+#[test]
+fn load_backwards() {
+    let mut cpu = Lc3::new(0x4000);
+
+    // Set the memory to load -42 into R0 from an address before the
+    // instruction
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x4000, 0b0010_000_100000000);
+    cpu.set_memory(0x3F01, !42 + 1);
+
+    // Assert that the memory cell is loaded correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), !42 + 1);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+}
+
+#[test]
+fn load_indirect() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to load 42 into R0, loading the address through memory
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1010_000_011111111);
+    cpu.set_memory(0x3100, 0x6000);
+    cpu.set_memory(0x6000, 42);
+
+    // Assert that the memory cell is loaded correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+}
+
+#[test]
+fn load_through_register() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to load 42 into R0, loading the address through R1
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0110_000_001_011111);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000).unwrap();
+    cpu.set_memory(0x601F, 42);
+
+    // Assert that the memory cell is loaded correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+}
+
+#[test]
+fn load_through_register_backwards() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to load 42 into R0, loading the address through R1 with a
+    // negative offset
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0110_000_001_111111);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000).unwrap();
+    cpu.set_memory(0x5FFF, 42);
+
+    // Assert that the memory cell is loaded correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 42);
+}
+
+#[test]
+fn load_effective_address() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to load the address 0x3100 into R0
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1110_000_011111111);
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Assert that the address is loaded correctly, and that the Condition
+    // Code is left alone
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R0)), 0x3100);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+}
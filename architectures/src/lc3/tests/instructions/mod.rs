@@ -0,0 +1,11 @@
+use super::*;
+
+mod add;
+mod and;
+mod branch;
+mod jump;
+mod load;
+mod not;
+mod r#return;
+mod store;
+mod trap;
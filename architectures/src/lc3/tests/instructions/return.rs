@@ -0,0 +1,63 @@
+use super::*;
+
+#[test]
+fn r#return() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to jump to 0x300B and immediatly return
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0100_1_00000001010); // JSR 0x300B
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x300B, 0b1100_000_111_000000); // RET
+
+    // Assert that the Program Counter is back on the main routine
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
+}
+
+#[test]
+fn return_from_interrupt() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the processor in "Supervisor" mode and set the stack to have a
+    // "Supervisor" Processor Status Register with a negative Condition Code
+    // and 0x6000 as the return address
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+    cpu.set_memory(0x3000, 0b1000_000000000000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x2FFE).unwrap();
+    cpu.set_memory(0x2FFE, 0x6000);
+    cpu.set_memory(0x2FFF, 0x0004);
+
+    // Asserts that the code returns succesfully from an interrupt, without
+    // switching stack
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x3000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0004);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+}
+
+#[test]
+fn return_from_interrupt_to_user_mode() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the processor in "Supervisor" mode and set the stack to have a
+    // "User" Processor Status Register with a positive Condition Code and
+    // 0x6000 as the return address
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x0002)
+        .unwrap();
+    cpu.set_memory(0x3000, 0b1000_000000000000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x2FFE).unwrap();
+    cpu.set_memory(0x2FFE, 0x6000);
+    cpu.set_memory(0x2FFF, 0x8001);
+
+    // Asserts that the code returns succesfully from an interrupt, restoring
+    // the user stack
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0xFE00);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x8001);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Positive);
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+#[test]
+fn store() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to store 42 from R0 into memory
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0011_000_011111111);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
+
+    // Assert that the memory cell is stored correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(0x3100), 42);
+}
+
+#[test]
+fn store_indirect() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to store 42 from R0 into memory loading the address
+    // indirectly
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1011_000_011111111);
+    cpu.set_memory(0x3100, 0x6000);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
+
+    // Assert that the memory cell is stored correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(0x6000), 42);
+}
+
+#[test]
+fn store_through_register() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the memory to store 42 from R0 into memory loading the address
+    // through R1
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b0111_000_001_100000);
+    cpu.set_register(&Register::Gpr(Gpr::R0), 42).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R1), 0x6000).unwrap();
+
+    // Assert that the memory cell is stored correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_memory(0x5FE0), 42);
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+#[test]
+fn trap() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the Trap Vector
+    for i in 0..0xff {
+        cpu.set_memory(i, !i);
+    }
+
+    // For every addres in the Trap Vector...
+    for i in 0..0xff {
+        // Setup the CPU to trap vector element
+        cpu.set_register(&Register::ProcessorStatusRegister, 0x8002)
+            .unwrap();
+        cpu.set_register(&Register::ProgramCounter, 0x3000).unwrap();
+        cpu.set_memory(0x3000, 0xf000 + i);
+
+        // Assert that the jump to the trap address has happened
+        cpu.step_instruction().unwrap();
+        assert_eq!(cpu.get_register(&Register::ProgramCounter), !i);
+    }
+}
+
+#[test]
+fn trap_switches_to_supervisor_stack() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the memory to trap to address 0x0400 with a negative Condition
+    // Code and a user stack pointer
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1111_0000_00100101);
+    cpu.set_memory(0x0025, 0x0400);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x4000).unwrap();
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Assert that the processor is in "Supervisor" mode and that the
+    // Processor Status Register and the Program Counter are on the supervisor
+    // stack
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x0400);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0004);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x2FFE);
+    assert_eq!(cpu.get_memory(0x2FFE), 0x3001);
+    assert_eq!(cpu.get_memory(0x2FFF), 0x8004);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R7)), 0x0000);
+}
+
+#[test]
+fn return_from_trap() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the memory to trap to address 0x0400 and return immediatly
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x3000, 0b1111_0000_00101010);
+    cpu.set_memory(42, 0x0400);
+    cpu.set_memory(0x0400, 0b1000_000000000000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x4000).unwrap();
+
+    // Assert that the Program Counter, the stack and the Processor Status
+    // Register (with the positive Condition Code set by R6) are restored
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x0400);
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3001);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x4000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x8001);
+}
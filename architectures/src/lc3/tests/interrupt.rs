@@ -1 +1,41 @@
+use super::*;
 
+#[test]
+fn r#return() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Setup the interrupt vector, the interrupt routine, the stack pointer and
+    // the condition code
+    #[allow(clippy::unusual_byte_groupings)]
+    cpu.set_memory(0x6000, 0b1000_000000000000);
+    cpu.set_memory(0x0180, 0x6000);
+    cpu.set_register(&Register::Gpr(Gpr::R6), 0x5000).unwrap();
+    cpu.set_condition_code(&ConditionCode::Negative);
+
+    // Initialize the interrupt with priority 4 and assert that the processor
+    // is in "Supervisor" mode
+    cpu.interrupt(0x0480);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x6000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x0404);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x2FFE);
+
+    // Assert that the routine returns correctly
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::Gpr(Gpr::R6)), 0x5000);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+}
+
+#[test]
+fn lower_priority() {
+    let mut cpu = Lc3::new(0x3000);
+
+    // Set the program priority to 4
+    cpu.set_register(&Register::ProcessorStatusRegister, 0x8402)
+        .unwrap();
+
+    // Assert that an interrupt with the same priority is ignored
+    cpu.interrupt(0x0480);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_register(&Register::ProcessorStatusRegister), 0x8402);
+}
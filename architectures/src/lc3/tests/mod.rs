@@ -1,7 +1,29 @@
 use super::*;
 use crate::Architecture;
 
+mod instructions;
+
 mod condition_code;
+mod coverage;
+mod devices;
+mod disassembler;
+mod exception;
+mod golden;
+mod instruction;
 mod interrupt;
+mod journal;
 mod memory;
+mod profile;
 mod registers;
+mod snapshot;
+mod statistics;
+mod trace;
+
+/// Create an LC-3 that executes `program` from x3000
+fn cpu_with_program(program: &[u16]) -> Lc3 {
+    let mut cpu = Lc3::new(0x3000);
+    for (address, instruction) in (0x3000..).zip(program) {
+        cpu.set_memory(address, *instruction);
+    }
+    cpu
+}
@@ -0,0 +1,74 @@
+use super::*;
+
+use crate::common::StackProfile;
+
+#[test]
+fn disabled() {
+    let mut cpu = Lc3::new(0x3000);
+    cpu.step_instruction().unwrap();
+    assert!(cpu.profile().is_none());
+}
+
+#[test]
+fn call_stacks() {
+    // x3000: JSR x3004
+    // x3001: JSR x3004
+    // x3002: TRAP x26
+    // x3003: ADD R1, R1, #1
+    // x3004: ADD R1, R1, #1
+    // x3005: RET
+    // x3007: RTI
+    let mut cpu = cpu_with_program(&[
+        0x4803, 0x4802, 0xf026, 0x1261, 0x1261, 0xc1c0, 0x0000, 0x8000,
+    ]);
+    cpu.set_profiling(true);
+    cpu.set_memory(0x0026, 0x3007);
+    for _ in 0..9 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3004);
+
+    // The calls are counted by the caller, the returns by the routine
+    let stacks = cpu.profile().unwrap().stacks();
+    assert_eq!(
+        stacks,
+        [
+            (
+                vec![],
+                StackProfile {
+                    instructions: 4,
+                    calls: 0
+                }
+            ),
+            (
+                vec![0x3004],
+                StackProfile {
+                    instructions: 4,
+                    calls: 2
+                }
+            ),
+            (
+                vec![0x3007],
+                StackProfile {
+                    instructions: 1,
+                    calls: 1
+                }
+            ),
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn return_as_jump() {
+    // A RET that doesn't return from a routine doesn't change the call stack
+    let mut cpu = cpu_with_program(&[0xc1c0, 0x0000, 0x1261]);
+    cpu.set_profiling(true);
+    cpu.set_register(&Register::Gpr(Gpr::R7), 0x3002).unwrap();
+    cpu.step_instruction().unwrap();
+    cpu.step_instruction().unwrap();
+
+    let stacks = cpu.profile().unwrap().stacks();
+    assert_eq!(stacks.keys().collect::<Vec<_>>(), [&vec![]]);
+    assert_eq!(stacks[&vec![]].instructions, 2);
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+use crate::WatcherType;
+use std::cell::Cell;
+use std::io;
+use std::rc::Rc;
+
+/// Create an LC-3 with a non-default state
+fn prepared_cpu() -> Lc3 {
+    let mut cpu = Lc3::new(0x3000);
+    cpu.set_register(&Register::Gpr(Gpr::R3), 0x1234).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R7), 0x8000).unwrap();
+    cpu.set_memory(0x4000, 0xabcd);
+    cpu.set_memory(0xfffe, 0x8000);
+    cpu
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let mut cpu = prepared_cpu();
+    let snapshot = cpu.snapshot();
+    assert_eq!(snapshot.general_purpose_registers[3], 0x1234);
+    assert_eq!(snapshot.processor_status_register, 0x8004);
+    assert_eq!(snapshot.saved_ssp, 0x3000);
+    assert_eq!(snapshot.memory[0x4000], 0xabcd);
+
+    // Change the state
+    cpu.set_register(&Register::Gpr(Gpr::R3), 0).unwrap();
+    cpu.set_register(&Register::ProgramCounter, 0x5000).unwrap();
+    cpu.set_memory(0x4000, 0);
+
+    // Restore the snapshot, without calling the watchers
+    let called = Rc::new(Cell::new(false));
+    let called_watcher = called.clone();
+    cpu.add_memory_watcher(0x4000, WatcherType::OnWrite, move |_| {
+        called_watcher.set(true);
+    });
+    cpu.restore(&snapshot).unwrap();
+    assert!(!called.get());
+    assert_eq!(cpu.snapshot(), snapshot);
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+    assert_eq!(cpu.get_condition_code(), ConditionCode::Negative);
+    assert_eq!(cpu.get_memory(0x4000), 0xabcd);
+}
+
+#[test]
+fn restore_invalid_condition_code() {
+    let mut cpu = prepared_cpu();
+    let mut snapshot = cpu.snapshot();
+    snapshot.processor_status_register = 0x8000;
+    snapshot.program_counter = 0x5000;
+
+    // Nothing is restored
+    assert_eq!(
+        cpu.restore(&snapshot),
+        Err(crate::Error::InvalidConditionCode(0x8000))
+    );
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x3000);
+}
+
+#[test]
+fn file_format() {
+    let snapshot = prepared_cpu().snapshot();
+    let mut file = Vec::new();
+    snapshot.write_to(&mut file).unwrap();
+
+    // Check the header and the size
+    assert_eq!(&file[..14], b"LEMUSNAP\x00\x01\x00\x03\x00\x0f");
+    assert_eq!(file.len(), 14 + (15 + 65536) * 2);
+
+    // Read the snapshot back
+    assert_eq!(Snapshot::read_from(file.as_slice()).unwrap(), snapshot);
+}
+
+#[test]
+fn invalid_files() {
+    let mut file = Vec::new();
+    prepared_cpu().snapshot().write_to(&mut file).unwrap();
+    let error = |file: &[u8]| Snapshot::read_from(file).unwrap_err();
+
+    // Truncated file
+    assert_eq!(
+        error(&file[..file.len() - 1]).kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // Snapshot of another architecture
+    let mut other = file.clone();
+    other[11] = 2;
+    assert_eq!(error(&other).kind(), io::ErrorKind::InvalidData);
+
+    // Unsupported version
+    let mut other = file.clone();
+    other[9] = 2;
+    assert_eq!(error(&other).kind(), io::ErrorKind::InvalidData);
+
+    // Not a snapshot
+    assert_eq!(error(&file[8..]).kind(), io::ErrorKind::InvalidData);
+}
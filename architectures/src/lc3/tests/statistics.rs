@@ -0,0 +1,97 @@
+use super::*;
+
+use crate::common::{INSTRUCTION_CYCLES, MEMORY_ACCESS_CYCLES};
+
+#[test]
+fn counters() {
+    // x3000: ADD R1, R1, #1
+    // x3001: BRz x3000
+    // x3002: BRp x3004
+    // x3004: LDI R3, x3010
+    // x3005: STR R1, R2, #0
+    // x3006: TRAP x25
+    let mut cpu = cpu_with_program(&[0x1261, 0x05fe, 0x0201, 0x0000, 0xa60b, 0x7280, 0xf025]);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.set_memory(0x3010, 0x4000);
+    cpu.set_memory(0x0025, 0x0500);
+    for _ in 0..6 {
+        cpu.step_instruction().unwrap();
+    }
+    assert_eq!(cpu.get_register(&Register::ProgramCounter), 0x0500);
+
+    // The TRAP pushes the Processor Status Register and the Program Counter
+    let statistics = cpu.statistics();
+    assert_eq!(statistics.instructions, 6);
+    assert_eq!(statistics.opcodes[0b0000], 2);
+    assert_eq!(statistics.opcodes[0b0001], 1);
+    assert_eq!(statistics.opcodes[0b1010], 1);
+    assert_eq!(statistics.opcodes[0b0111], 1);
+    assert_eq!(statistics.opcodes[0b1111], 1);
+    assert_eq!(statistics.opcodes.iter().sum::<u64>(), 6);
+    assert_eq!(statistics.memory_reads, 9);
+    assert_eq!(statistics.memory_writes, 3);
+    assert_eq!(statistics.branches_taken, 1);
+    assert_eq!(statistics.branches_not_taken, 1);
+    assert_eq!(statistics.traps, [(0x25, 1)].into());
+    assert_eq!(statistics.interrupts, 0);
+    assert_eq!(
+        statistics.cycles,
+        6 * INSTRUCTION_CYCLES + 12 * MEMORY_ACCESS_CYCLES
+    );
+}
+
+#[test]
+fn exception() {
+    // LDR R3, R4, #0, that reads x0000 in "User" privilege mode and raises
+    // an Access Control Violation, whose service routine is entered by
+    // pushing two words and reading the exception vector
+    let mut cpu = cpu_with_program(&[0x6700]);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    assert_eq!(
+        cpu.step_instruction().unwrap(),
+        StepOutcome::Exception(0x02)
+    );
+
+    let statistics = cpu.statistics();
+    assert_eq!(statistics.instructions, 1);
+    assert_eq!(statistics.opcodes[0b0110], 1);
+    assert_eq!(statistics.memory_reads, 2);
+    assert_eq!(statistics.memory_writes, 2);
+}
+
+#[test]
+fn outside_instructions() {
+    // The accesses made to set the machine up are not counted
+    let mut cpu = cpu_with_program(&[0x1261]);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    let _ = cpu.get_memory(0x3000);
+    assert_eq!(cpu.statistics(), &Statistics::default());
+
+    // Neither are the ones made by the interrupts, that are counted apart
+    cpu.interrupt(KEYBOARD_INTERRUPT);
+    assert_eq!(cpu.statistics().interrupts, 1);
+    assert_eq!(cpu.statistics().memory_writes, 0);
+
+    // An interrupt with a lower priority is not accepted
+    cpu.interrupt(0x0180);
+    assert_eq!(cpu.statistics().interrupts, 1);
+}
+
+#[test]
+fn reset() {
+    let mut cpu = cpu_with_program(&[0x1261, 0x1261]);
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.step_instruction().unwrap();
+    cpu.reset_statistics();
+    assert_eq!(cpu.statistics(), &Statistics::default());
+
+    cpu.step_instruction().unwrap();
+    assert_eq!(cpu.statistics().instructions, 1);
+    assert_eq!(cpu.statistics().opcodes[0b0001], 1);
+
+    // Undoing an instruction doesn't change the statistics
+    cpu.set_history_depth(1);
+    cpu.step_instruction().unwrap();
+    assert!(cpu.step_back());
+    assert_eq!(cpu.statistics().instructions, 2);
+}
@@ -0,0 +1,86 @@
+use super::*;
+
+use crate::common::MemoryAccess;
+use crate::WatcherType;
+
+#[test]
+fn disabled() {
+    // STR R1, R2, #0
+    let mut cpu = cpu_with_program(&[0x7280]);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.step_instruction().unwrap();
+    assert!(cpu.last_trace().is_none());
+}
+
+#[test]
+fn memory_accesses() {
+    // STR R1, R2, #0
+    // LDR R3, R2, #0
+    let mut cpu = cpu_with_program(&[0x7280, 0x6680]);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.set_tracing(true);
+
+    cpu.step_instruction().unwrap();
+    let record = cpu.last_trace().unwrap();
+    assert_eq!(record.address, 0x3000);
+    assert_eq!(record.instruction, 0x7280);
+    assert!(record.registers.is_empty());
+    assert_eq!(
+        record.memory,
+        vec![MemoryAccess {
+            address: 0x4000,
+            data: 5,
+            access_type: WatcherType::OnWrite,
+        }]
+    );
+
+    cpu.step_instruction().unwrap();
+    let record = cpu.last_trace().unwrap();
+    assert_eq!(record.address, 0x3001);
+    assert_eq!(record.registers, vec![(Register::Gpr(Gpr::R3), 5)]);
+    assert_eq!(
+        record.memory,
+        vec![MemoryAccess {
+            address: 0x4000,
+            data: 5,
+            access_type: WatcherType::OnRead,
+        }]
+    );
+    assert_eq!(record.condition_code, ConditionCode::Positive);
+
+    // Disabling the tracing forgets the last record
+    cpu.set_tracing(false);
+    assert!(cpu.last_trace().is_none());
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn registers() {
+    let mut cpu = cpu_with_program(&[
+        0b0100_1_00000000011,   // x3000: JSR x3004
+        0,                      // x3001
+        0,                      // x3002
+        0,                      // x3003
+        0b0001_001_001_1_11010, // x3004: ADD R1, R1, #-6
+    ]);
+    cpu.set_register(&Register::Gpr(Gpr::R1), 5).unwrap();
+    cpu.set_register(&Register::Gpr(Gpr::R2), 0x4000).unwrap();
+    cpu.set_tracing(true);
+
+    // The jumps write the Program Counter
+    cpu.step_instruction().unwrap();
+    assert_eq!(
+        cpu.last_trace().unwrap().registers,
+        vec![
+            (Register::Gpr(Gpr::R7), 0x3001),
+            (Register::ProgramCounter, 0x3004),
+        ]
+    );
+
+    cpu.step_instruction().unwrap();
+    let record = cpu.last_trace().unwrap();
+    assert_eq!(record.registers, vec![(Register::Gpr(Gpr::R1), 0xffff)]);
+    assert_eq!(record.condition_code, ConditionCode::Negative);
+}
@@ -2,6 +2,21 @@ pub mod lc2;
 pub mod lc3;
 
 pub mod common;
+pub mod devices;
+
+mod error;
+pub use error::Error;
+
+#[cfg(test)]
+mod golden;
+
+use common::{Coverage, Profile, Statistics, TraceRecord};
+use devices::Device;
+
+use std::{
+    ops::RangeInclusive,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub enum WatcherType {
@@ -9,59 +24,195 @@ pub enum WatcherType {
     OnWrite,
 }
 
+/// Identifier returned when a watcher is added, used to remove it
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct WatcherId(u64);
+
+impl WatcherId {
+    /// Get an id that is different from all the ones returned before
+    fn unique() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// What happened while executing an instruction
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum StepOutcome {
+    /// The instruction has been executed
+    Executed,
+
+    /// The instruction has raised the exception with the given vector, and
+    /// the processor has jumped to its service routine
+    Exception(u16),
+}
+
 pub trait Architecture {
     type Address;
     type Data;
     type Register;
     type RegisterData;
     type ConditionCode;
+    type Snapshot;
 
     #[must_use]
     fn get_memory(&mut self, address: Self::Address) -> Self::Data;
     fn set_memory(&mut self, address: Self::Address, data: Self::Data);
 
+    /// Read the memory like the processor would, but without calling the
+    /// watchers, changing any register or triggering the side effects of the
+    /// devices
+    #[must_use]
+    fn peek_memory(&self, address: Self::Address) -> Self::Data;
+
     /// # Errors
     ///
     /// This method will return an `Err` if an attempt is made to insert a
     /// quantity of bytes exceeding `Self::Address`
-    fn load_bytes(
-        &mut self,
-        start_address: Self::Address,
-        bytes: &[u8],
-    ) -> Result<(), &'static str>;
+    fn load_bytes(&mut self, start_address: Self::Address, bytes: &[u8]) -> Result<(), Error>;
 
     #[must_use]
     fn get_register(&self, register: &Self::Register) -> Self::RegisterData;
-    fn set_register(&mut self, register: &Self::Register, data: Self::RegisterData);
+
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the value would put the processor
+    /// in an invalid state
+    fn set_register(
+        &mut self,
+        register: &Self::Register,
+        data: Self::RegisterData,
+    ) -> Result<(), Error>;
 
     #[must_use]
     fn get_condition_code(&self) -> Self::ConditionCode;
     fn set_condition_code(&mut self, condition_code: &Self::ConditionCode);
 
+    /// Add a watcher for a memory cell, that is called after the other
+    /// watchers of the same cell
     fn add_memory_watcher<F>(
         &mut self,
         address: Self::Address,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::Data) + 'static;
-    fn remove_memory_watcher(&mut self, address: Self::Address, watcher_type: WatcherType);
+    /// Remove a memory watcher, either of a single cell or of a range
+    fn remove_memory_watcher(&mut self, id: WatcherId);
 
+    /// Add a watcher for the memory cells in `addresses`, that receives the
+    /// address of the accessed cell along with its data. It's called after
+    /// the watchers of the single cells and the range watchers added before
+    fn add_memory_range_watcher<F>(
+        &mut self,
+        addresses: RangeInclusive<Self::Address>,
+        watcher_type: WatcherType,
+        function: F,
+    ) -> WatcherId
+    where
+        F: Fn(Self::Address, Self::Data) + 'static;
+
+    /// Add a watcher for a register, that is called after the other watchers
+    /// of the same register
     fn add_register_watcher<F>(
         &mut self,
         register: &Self::Register,
         watcher_type: WatcherType,
         function: F,
-    ) where
+    ) -> WatcherId
+    where
         F: Fn(Self::RegisterData) + 'static;
-    fn remove_register_watcher(&mut self, register: &Self::Register, watcher_type: WatcherType);
+    fn remove_register_watcher(&mut self, id: WatcherId);
 
-    fn add_condition_code_watcher<F>(&mut self, watcher_type: WatcherType, function: F)
+    /// Add a watcher for the condition code, that is called after the other
+    /// watchers of the same type
+    fn add_condition_code_watcher<F>(
+        &mut self,
+        watcher_type: WatcherType,
+        function: F,
+    ) -> WatcherId
     where
         F: Fn(Self::ConditionCode) + 'static;
-    fn remove_condition_code_watcher(&mut self, watcher_type: WatcherType);
+    fn remove_condition_code_watcher(&mut self, id: WatcherId);
 
-    fn step_instruction(&mut self);
+    /// Map `device` to `addresses`, so that it handles their reads and writes
+    /// instead of the memory. A device added later takes precedence over the
+    /// ones already mapped to the same addresses
+    fn add_device(&mut self, addresses: RangeInclusive<Self::Address>, device: Box<dyn Device>);
+
+    /// Fetch, decode and execute the next instruction, then update the devices
+    /// and handle their requests
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err`, without executing anything, if the
+    /// processor is in an invalid state
+    fn step_instruction(&mut self) -> Result<StepOutcome, Error>;
 
     fn interrupt(&mut self, data: Self::Data);
+
+    /// Keep a journal of the last `depth` instructions executed, so that they
+    /// can be undone. A depth of 0, the default, disables the journal
+    fn set_history_depth(&mut self, depth: usize);
+
+    /// Undo the last instruction in the journal, restoring the registers, the
+    /// Machine Control Register and the memory cells it has written, without
+    /// calling the watchers. The state of the other devices is not restored.
+    /// Return `false` if the journal is empty
+    fn step_back(&mut self) -> bool;
+
+    /// Undo the instructions in the journal until the Program Counter is at
+    /// `address`, undoing at least one. Return `false` if the journal runs out
+    /// before reaching `address`
+    fn run_back_to(&mut self, address: Self::Address) -> bool;
+
+    /// Record the registers written and the memory accessed by each
+    /// instruction executed, to get them with `last_trace`. Tracing is
+    /// disabled by default
+    fn set_tracing(&mut self, enabled: bool);
+
+    /// Get the record of the last instruction executed while tracing, unless
+    /// it couldn't be executed
+    fn last_trace(&self) -> Option<&TraceRecord<Self::Register, Self::ConditionCode>>;
+
+    /// Get the counters of the instructions executed and of the interrupts
+    /// accepted since the processor has been created or the counters have
+    /// been reset. Undoing an instruction or restoring a snapshot doesn't
+    /// change them
+    fn statistics(&self) -> &Statistics;
+
+    /// Set all the counters returned by `statistics` to zero
+    fn reset_statistics(&mut self);
+
+    /// Count how many times each address is executed and which way each
+    /// conditional branch goes, to get them with `coverage`. Disabling the
+    /// coverage forgets what has been collected. It's disabled by default
+    fn set_coverage(&mut self, enabled: bool);
+
+    /// Get the coverage collected since it has been enabled, if it is
+    fn coverage(&self) -> Option<&Coverage>;
+
+    /// Keep a shadow call stack of the subroutines, trap routines and service
+    /// routines, counting the instructions executed by each call stack, to
+    /// get them with `profile`. Disabling the profiling forgets what has been
+    /// collected. It's disabled by default
+    fn set_profiling(&mut self, enabled: bool);
+
+    /// Get the profile collected since the profiling has been enabled, if it
+    /// is
+    fn profile(&self) -> Option<&Profile>;
+
+    /// Capture the state of the processor and of the memory, without the
+    /// watchers and the state of the devices
+    #[must_use]
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Restore a state captured by `snapshot`, without calling the watchers
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err`, without changing anything, if the
+    /// snapshot contains an invalid state
+    fn restore(&mut self, snapshot: &Self::Snapshot) -> Result<(), Error>;
 }
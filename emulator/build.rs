@@ -1,4 +1,4 @@
-use assemblers::{lc2::Lc2AssemblerBuilder, Assembler};
+use assemblers::{lc2::Lc2AssemblerBuilder, lc3::Lc3AssemblerBuilder, Assembler};
 use std::{
     env,
     fs::{self, File},
@@ -15,19 +15,24 @@ fn main() {
 /// build them into "$OUT_DIR/<architecture>/<routine_name>.asm.o"
 #[allow(clippy::doc_markdown)]
 fn build_trap_routines() {
-    // Create a new assembler
-    let assembler = Lc2AssemblerBuilder::new()
+    // Create a new assembler for every architecture
+    let lc2_assembler = Lc2AssemblerBuilder::new()
         .prepend_start_address(false)
         .optional_end(true)
         .enable_stringzp(true)
         .build();
+    let lc3_assembler = Lc3AssemblerBuilder::new()
+        .prepend_start_address(false)
+        .optional_end(true)
+        .build();
 
-    // Get all the architectures dirs
+    // Get all the architectures dirs, that are the ones containing a
+    // "trap_routines/" directory
     let architectures_dirs: Vec<fs::DirEntry> =
         fs::read_dir(Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src"))
             .unwrap()
             .flatten()
-            .filter(|x| x.metadata().unwrap().is_dir())
+            .filter(|x| x.metadata().unwrap().is_dir() && x.path().join("trap_routines").is_dir())
             .collect();
 
     // For every architecture...
@@ -55,8 +60,16 @@ fn build_trap_routines() {
             // Get the contents of the file
             let assembly = fs::read_to_string(assembly_file.path()).unwrap();
 
-            // Compile the assembly
-            let (binary, _) = assembler.assemble(&assembly).unwrap();
+            // Compile the assembly with the assembler of the architecture
+            let (binary, _) = match architecture_dir.file_name().to_str() {
+                Some("lc2") => lc2_assembler.assemble(&assembly),
+                Some("lc3") => lc3_assembler.assemble(&assembly),
+                _ => panic!(
+                    "There is no assembler for the \"{}\" architecture",
+                    architecture_dir.file_name().to_string_lossy()
+                ),
+            }
+            .unwrap();
 
             // Write the binary into the output file
             let output_file_name = format!("{}.o", assembly_file.file_name().to_string_lossy());
@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, error, fmt};
+
+use architectures::common::SymbolTable;
+use assemblers::{lc2::Lc2AssemblerBuilder, lc3::Lc3AssemblerBuilder, Assembler};
+
+use crate::{
+    cli::{parse_address, Isa},
+    coverage::{LineTable, SourceLine},
+    image::Block,
+};
+
+/// Mnemonics of the instructions and of the trap aliases, besides the
+/// branches
+const MNEMONICS: [&str; 24] = [
+    "ADD", "AND", "NOT", "LD", "LDI", "LDR", "LEA", "ST", "STI", "STR", "JMP", "JMPR", "JSR",
+    "JSRR", "RET", "RTI", "TRAP", "NOP", "GETC", "OUT", "PUTS", "IN", "PUTSP", "HALT",
+];
+
+/// Error in an assembly source
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct SourceError {
+    pub file: String,
+    /// Message of the assembler, that doesn't tell the position of its errors
+    pub message: String,
+}
+
+impl SourceError {
+    /// Create an error in the source `file`
+    #[must_use]
+    pub fn new(file: &str, message: impl fmt::Display) -> Self {
+        Self {
+            file: file.to_owned(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+impl error::Error for SourceError {}
+
+/// Program assembled from a source, with its labels and the source line of
+/// each instruction
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Assembly {
+    /// Words placed after each `.ORIG`, in the order of the source
+    pub blocks: Vec<Block>,
+    pub symbols: SymbolTable,
+    /// Source lines of the instructions, without the data words. It's empty if
+    /// the words placed by each line can't be told for sure
+    pub lines: LineTable,
+}
+
+/// Addresses of the lines of a source, worked out without the assembler
+#[derive(Default)]
+struct Layout<'a> {
+    /// Origin and number of words of each block
+    blocks: Vec<(u16, usize)>,
+    labels: HashMap<&'a str, u16>,
+    /// Address and index of the line of each instruction
+    instructions: Vec<(u16, usize)>,
+}
+
+impl Layout<'_> {
+    /// Check if the layout places the labels where the assembler has found
+    /// them, and as many words as it has placed
+    fn agrees(&self, length: usize, found: &HashMap<String, u16>) -> bool {
+        self.blocks.iter().map(|(_, length)| length).sum::<usize>() == length
+            && self.labels.len() == found.len()
+            && self
+                .labels
+                .iter()
+                .all(|(label, address)| found.get(*label) == Some(address))
+    }
+}
+
+/// Assemble the source of the file `file` for `architecture`.
+///
+/// The assembler gives the words and the labels of the source, but not the
+/// words placed by each line. They are worked out from the size of the
+/// instructions and of the directives, and trusted only if they agree with
+/// the labels found by the assembler
+///
+/// # Errors
+///
+/// This function will return an `Err` if the source can't be assembled, or if
+/// the words placed by the assembler can't be split into its blocks
+pub fn assemble(file: &str, source: &str, architecture: Isa) -> Result<Assembly, SourceError> {
+    let (binary, found) = match architecture {
+        Isa::Lc2 => Lc2AssemblerBuilder::new()
+            .prepend_start_address(false)
+            .optional_end(true)
+            .enable_stringzp(true)
+            .build()
+            .assemble(source),
+        Isa::Lc3 => Lc3AssemblerBuilder::new()
+            .prepend_start_address(false)
+            .optional_end(true)
+            .build()
+            .assemble(source),
+    }
+    .map_err(|error| SourceError::new(file, error))?;
+
+    // The assembler places the words of the blocks one after the other
+    let mut words = binary
+        .chunks(2)
+        .map(|word| u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or_default()]))
+        .collect::<Vec<_>>();
+    let found = found.into_iter().collect::<HashMap<_, _>>();
+    let symbols = found
+        .iter()
+        .map(|(label, address)| (*address, label.clone()))
+        .collect();
+
+    // If the layout doesn't agree with the assembler only its origin is
+    // trusted, and only if it has a single block
+    let layout = lay_out(source, &found)
+        .ok_or_else(|| SourceError::new(file, "Can't tell where the words of the source go"))?;
+    if !layout.agrees(words.len(), &found) {
+        let [(origin, _)] = layout.blocks[..] else {
+            return Err(SourceError::new(
+                file,
+                "Can't tell where the blocks of the source start",
+            ));
+        };
+        return Ok(Assembly {
+            blocks: vec![(origin, words)],
+            symbols,
+            lines: LineTable::new(),
+        });
+    }
+
+    let lines = layout
+        .instructions
+        .iter()
+        .map(|(address, index)| {
+            let source = SourceLine {
+                file: file.to_owned(),
+                line: u32::try_from(index + 1).unwrap_or(u32::MAX),
+            };
+            (*address, source)
+        })
+        .collect();
+    let blocks = layout
+        .blocks
+        .into_iter()
+        .filter(|(_, length)| *length > 0)
+        .map(|(origin, length)| (origin, words.drain(..length).collect()))
+        .collect();
+
+    Ok(Assembly {
+        blocks,
+        symbols,
+        lines,
+    })
+}
+
+/// Find the address of the lines of a source that has been assembled, with
+/// the labels found by the assembler. Return `None` if a line can't be laid
+/// out
+fn lay_out<'a>(source: &'a str, found: &HashMap<String, u16>) -> Option<Layout<'a>> {
+    let mut layout = Layout::default();
+    let mut address = None;
+    for (index, line) in source.lines().enumerate() {
+        let mut tokens = tokenize(line)?.into_iter();
+        let mut operation = tokens.next();
+
+        // The first token is a label if the assembler has found it
+        if let Some(label) = operation.map(|token| token.strip_suffix(':').unwrap_or(token)) {
+            if found.contains_key(label) {
+                if layout.labels.insert(label, address?).is_some() {
+                    return None;
+                }
+                operation = tokens.next();
+            }
+        }
+
+        let Some(operation) = operation.map(str::to_uppercase) else {
+            continue;
+        };
+        let size = match operation.as_str() {
+            ".ORIG" => {
+                let origin = parse_address(tokens.next()?).ok()?;
+                layout.blocks.push((origin, 0));
+                address = Some(origin);
+                continue;
+            }
+            ".END" => break,
+            ".FILL" => 1,
+            ".BLKW" => parse_address(tokens.next()?.trim_start_matches('#')).ok()?,
+            ".STRINGZ" => string_length(tokens.next()?)?.checked_add(1)?,
+            ".STRINGZP" => string_length(tokens.next()?)?.checked_add(2)? / 2,
+            _ if is_mnemonic(&operation) => {
+                layout.instructions.push((address?, index));
+                1
+            }
+            _ => return None,
+        };
+        layout.blocks.last_mut()?.1 += usize::from(size);
+        address = address?.checked_add(size);
+    }
+
+    Some(layout)
+}
+
+/// Get the number of characters of a quoted string, counting an escape
+/// sequence as one
+fn string_length(string: &str) -> Option<u16> {
+    let string = string.strip_prefix('"')?.strip_suffix('"')?;
+    let mut length = 0_u16;
+    let mut escaped = false;
+    for character in string.chars() {
+        if escaped || character != '\\' {
+            length = length.checked_add(1)?;
+            escaped = false;
+        } else {
+            escaped = true;
+        }
+    }
+    Some(length)
+}
+
+/// Check if `name` is the mnemonic of an instruction
+fn is_mnemonic(name: &str) -> bool {
+    let name = name.to_uppercase();
+    MNEMONICS.contains(&name.as_str())
+        || name
+            .strip_prefix("BR")
+            .is_some_and(|flags| flags.chars().all(|flag| "NZP".contains(flag)))
+}
+
+/// Split a line into its tokens, without the comment. The tokens are
+/// separated by spaces or commas, and a string is a single token. Return
+/// `None` if a string isn't terminated
+fn tokenize(line: &str) -> Option<Vec<&str>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut end = line.len();
+    let mut string = None;
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        if let Some(string_start) = string {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    tokens.push(&line[string_start..=index]);
+                    string = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match character {
+            ';' => {
+                end = index;
+                break;
+            }
+            '"' if start.is_none() => string = Some(index),
+            _ if character.is_whitespace() || character == ',' => {
+                if let Some(token_start) = start.take() {
+                    tokens.push(&line[token_start..index]);
+                }
+            }
+            _ => {
+                start.get_or_insert(index);
+            }
+        }
+    }
+
+    if string.is_some() {
+        return None;
+    }
+    if let Some(token_start) = start {
+        tokens.push(&line[token_start..end]);
+    }
+    Some(tokens)
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+const PROGRAM: &str = "\
+; Add R2 to R1 until it's not positive
+.orig 0x3000
+  LD R1, count
+loop:
+  ADD R1, R1, R2
+  BRp loop
+  HALT
+count: .fill 0x0003
+.end
+";
+
+#[test]
+fn program() {
+    let assembly = assemble("prog.asm", PROGRAM, Isa::Lc3).unwrap();
+    assert_eq!(
+        assembly.blocks,
+        [(0x3000, vec![0x2203, 0x1242, 0x03fe, 0xf025, 0x0003])]
+    );
+    assert_eq!(
+        assembly.symbols,
+        SymbolTable::from([(0x3001, "loop".to_owned()), (0x3004, "count".to_owned())])
+    );
+    assert_eq!(
+        assembly
+            .lines
+            .iter()
+            .map(|(address, source)| (*address, source.file.as_str(), source.line))
+            .collect::<Vec<_>>(),
+        [
+            (0x3000, "prog.asm", 3),
+            (0x3001, "prog.asm", 5),
+            (0x3002, "prog.asm", 6),
+            (0x3003, "prog.asm", 7),
+        ]
+    );
+}
+
+#[test]
+fn blocks() {
+    let source = "\
+.ORIG x4000
+text .STRINGZ \"a;b\" ; comment
+table: .BLKW #3
+.ORIG x5000
+packed: .stringzp \"abc\"
+after
+";
+    let assembly = assemble("data.asm", source, Isa::Lc2).unwrap();
+    assert_eq!(
+        assembly
+            .blocks
+            .iter()
+            .map(|(origin, words)| (*origin, words.len()))
+            .collect::<Vec<_>>(),
+        [(0x4000, 4 + 3), (0x5000, 2)]
+    );
+    assert_eq!(
+        assembly.symbols,
+        SymbolTable::from([
+            (0x4000, "text".to_owned()),
+            (0x4004, "table".to_owned()),
+            (0x5000, "packed".to_owned()),
+            (0x5002, "after".to_owned()),
+        ])
+    );
+    // The data words have no source line
+    assert!(assembly.lines.is_empty());
+}
+
+#[test]
+fn layout() {
+    // The lines after the strings and the blocks of words are laid out too
+    let source = "\
+.ORIG x3000
+  LEA R0, text
+  PUTS
+  BR done
+text: .STRINGZ \"a\\\"b\"
+      .BLKW #2
+done: HALT
+";
+    let assembly = assemble("data.asm", source, Isa::Lc3).unwrap();
+    assert_eq!(
+        assembly
+            .lines
+            .iter()
+            .map(|(address, source)| (*address, source.line))
+            .collect::<Vec<_>>(),
+        [(0x3000, 2), (0x3001, 3), (0x3002, 4), (0x3009, 7)]
+    );
+
+    // A layout that doesn't place the labels where the assembler has found
+    // them isn't trusted
+    let found = HashMap::from([("text".to_owned(), 0x3003), ("done".to_owned(), 0x3008)]);
+    let layout = lay_out(source, &found).unwrap();
+    assert!(!layout.agrees(10, &found));
+    let found = HashMap::from([("text".to_owned(), 0x3003), ("done".to_owned(), 0x3009)]);
+    assert!(lay_out(source, &found).unwrap().agrees(10, &found));
+}
+
+#[test]
+fn errors() {
+    // The errors are the ones of the assembler, with the name of the file
+    let error = assemble("prog.asm", ".orig x3000\n  ADD R9, R1, R2\n", Isa::Lc3).unwrap_err();
+    assert_eq!(error.file, "prog.asm");
+    assert_eq!(error.to_string(), format!("prog.asm: {}", error.message));
+    assert!(!error.message.starts_with('"'));
+    assert!(assemble("prog.asm", "  HALT\n", Isa::Lc3).is_err());
+}
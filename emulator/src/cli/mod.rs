@@ -0,0 +1,307 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: emulator [OPTIONS] <FILE>...
+       emulator [OPTIONS] -s <SNAPSHOT> [FILE]...
+       emulator disasm [-a <lc2|lc3>] [-r <START>-<END>] [FILE]...
+
+Run one or more object files, starting from the origin of the first one, or
+disassemble them. The files ending in .ihex, .hex and .bin are read as Intel
+HEX, LC-3 hexadecimal text and binary text images, and the ones ending in .asm
+are assembled. With a snapshot, the object files are loaded on top of the saved
+machine, that resumes from where it was. With a range, `disasm` disassembles
+the memory of the machine, operating system included.
+
+Options:
+  -a, --arch <lc2|lc3>          Architecture to emulate [default: lc2]
+  -e, --entry <ADDRESS>         Address of the first instruction to execute
+  -m, --max-instructions <N>    Stop after executing N instructions
+  -i, --input <stdin|FILE>      Source of the keyboard input [default: stdin]
+  -s, --snapshot <FILE>         Restore the machine from a snapshot, saved by
+                                the debugger with the `save` command
+  -t, --trace <FILE>            Write a line per executed instruction, with the
+                                registers and the memory cells it accesses
+      --trace-format <text|jsonl>
+                                Format of the trace, either readable text or
+                                JSON Lines to compare runs [default: text]
+  -c, --coverage <FILE>         Write the addresses executed and the directions
+                                of the branches as an lcov report, and print a
+                                summary to stderr at exit
+  -p, --profile <FILE>          Write the instructions executed by each call
+                                stack in the collapsed format of the flame
+                                graph tools, and print the instructions of
+                                each routine to stderr at exit
+      --stats                   Print the number of instructions, cycles,
+                                memory accesses, branches, traps and
+                                interrupts to stderr at exit
+  -d, --debug                   Run the program in the interactive debugger,
+                                reading the commands from stdin
+  -g, --gdb <HOST:PORT|unix:PATH>
+                                Wait for a GDB connection on a TCP port or on
+                                a Unix socket, and let GDB control the program
+  -r, --range <START>-<END>     Addresses to disassemble, instead of the
+                                ones occupied by the object files
+  -h, --help                    Print this message
+
+Exit codes:
+  0    The program executed a HALT
+  2    The maximum number of instructions has been executed
+  3    An object file couldn't be loaded
+  4    The program executed a trap with an invalid vector
+  5    The processor reached a state in which it can't execute
+  130  The emulation has been interrupted with Ctrl-C
+";
+
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub enum Isa {
+    #[default]
+    Lc2,
+    Lc3,
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub enum InputSource {
+    #[default]
+    Stdin,
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub enum TraceFormat {
+    #[default]
+    Text,
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum GdbAddress {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Options {
+    pub files: Vec<String>,
+    pub architecture: Isa,
+    pub entry_point: Option<u16>,
+    pub max_instructions: Option<u64>,
+    pub input: InputSource,
+    pub debug: bool,
+    pub gdb: Option<GdbAddress>,
+    pub snapshot: Option<String>,
+    pub trace: Option<String>,
+    pub trace_format: TraceFormat,
+    pub statistics: bool,
+    pub coverage: Option<String>,
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct DisassembleOptions {
+    pub files: Vec<String>,
+    pub architecture: Isa,
+    pub range: Option<(u16, u16)>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Command {
+    Help,
+    Run(Options),
+    Disassemble(DisassembleOptions),
+}
+
+impl Command {
+    /// Parse the command line arguments, without the name of the executable
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` with a message for the user if an
+    /// argument is unknown, if its value is missing or invalid, or if neither
+    /// an object file nor a snapshot is given
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut range = None;
+        let mut args = args.into_iter().peekable();
+
+        // Check if the `disasm` subcommand is used
+        let disassemble = args.next_if(|arg| arg == "disasm").is_some();
+
+        while let Some(arg) = args.next() {
+            // Split the `--option=value` arguments
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_owned(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+
+            // Get the value of the option, either inline or as the next
+            // argument
+            let mut value = || {
+                inline_value
+                    .map(str::to_owned)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Missing value for \"{name}\""))
+            };
+
+            match name.as_str() {
+                "-h" | "--help" => return Ok(Self::Help),
+
+                "-a" | "--arch" => {
+                    options.architecture = match value()?.to_lowercase().as_str() {
+                        "lc2" => Isa::Lc2,
+                        "lc3" => Isa::Lc3,
+                        other => return Err(format!("Unknown architecture \"{other}\"")),
+                    };
+                }
+
+                "-e" | "--entry" | "-m" | "--max-instructions" | "-i" | "--input" | "-d"
+                | "--debug" | "-g" | "--gdb" | "-s" | "--snapshot" | "-t" | "--trace"
+                | "--trace-format" | "--stats" | "-c" | "--coverage" | "-p" | "--profile"
+                    if disassemble =>
+                {
+                    return Err(format!("\"{name}\" can't be used with disasm"));
+                }
+
+                "-r" | "--range" if disassemble => range = Some(parse_range(&value()?)?),
+
+                "-e" | "--entry" => options.entry_point = Some(parse_address(&value()?)?),
+
+                "-m" | "--max-instructions" => {
+                    let value = value()?;
+                    options.max_instructions = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid number of instructions \"{value}\""))?,
+                    );
+                }
+
+                "-i" | "--input" => {
+                    options.input = match value()?.as_str() {
+                        "stdin" | "-" => InputSource::Stdin,
+                        path => InputSource::File(PathBuf::from(path)),
+                    };
+                }
+
+                "-d" | "--debug" => options.debug = true,
+
+                "-s" | "--snapshot" => options.snapshot = Some(value()?),
+
+                "-t" | "--trace" => options.trace = Some(value()?),
+
+                "--trace-format" => {
+                    options.trace_format = match value()?.to_lowercase().as_str() {
+                        "text" => TraceFormat::Text,
+                        "jsonl" => TraceFormat::JsonLines,
+                        other => return Err(format!("Unknown trace format \"{other}\"")),
+                    };
+                }
+
+                "--stats" => options.statistics = true,
+
+                "-c" | "--coverage" => options.coverage = Some(value()?),
+
+                "-p" | "--profile" => options.profile = Some(value()?),
+
+                "-g" | "--gdb" => {
+                    let value = value()?;
+                    options.gdb = Some(value.strip_prefix("unix:").map_or_else(
+                        || GdbAddress::Tcp(value.clone()),
+                        |path| GdbAddress::Unix(PathBuf::from(path)),
+                    ));
+                }
+
+                _ if name.starts_with('-') && name.len() > 1 => {
+                    return Err(format!("Unknown option \"{name}\""));
+                }
+
+                _ => options.files.push(arg),
+            }
+        }
+
+        if disassemble {
+            if options.files.is_empty() && range.is_none() {
+                return Err("Either an object file or a range is required".to_owned());
+            }
+
+            return Ok(Self::Disassemble(DisassembleOptions {
+                files: options.files,
+                architecture: options.architecture,
+                range,
+            }));
+        }
+
+        options.check()?;
+        Ok(Self::Run(options))
+    }
+}
+
+impl Options {
+    /// Check that the options can be used together
+    fn check(&self) -> Result<(), String> {
+        if self.debug && self.gdb.is_some() {
+            return Err("The debugger and the GDB stub can't be used together".to_owned());
+        }
+
+        if self.trace.is_some() && (self.debug || self.gdb.is_some()) {
+            return Err("The trace can't be used with the debugger or the GDB stub".to_owned());
+        }
+
+        if self.statistics && (self.debug || self.gdb.is_some()) {
+            return Err(
+                "The statistics can't be used with the debugger or the GDB stub".to_owned(),
+            );
+        }
+
+        if self.coverage.is_some() && (self.debug || self.gdb.is_some()) {
+            return Err("The coverage can't be used with the debugger or the GDB stub".to_owned());
+        }
+
+        if self.profile.is_some() && (self.debug || self.gdb.is_some()) {
+            return Err("The profile can't be used with the debugger or the GDB stub".to_owned());
+        }
+
+        if self.files.is_empty() && self.snapshot.is_none() {
+            return Err("At least one object file or a snapshot is required".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse an address written in decimal or in hexadecimal, with either the
+/// `0x` or the `x` prefix
+///
+/// # Errors
+///
+/// This function will return an `Err` if the address is not a valid 16-bit
+/// number
+pub fn parse_address(address: &str) -> Result<u16, String> {
+    let hex = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .or_else(|| address.strip_prefix('x'))
+        .or_else(|| address.strip_prefix('X'));
+
+    hex.map_or_else(|| address.parse(), |hex| u16::from_str_radix(hex, 16))
+        .map_err(|_| format!("Invalid address \"{address}\""))
+}
+
+/// Parse a range of addresses written as `<START>-<END>`
+///
+/// # Errors
+///
+/// This function will return an `Err` if the range doesn't have two valid
+/// addresses or if it starts after its end
+pub fn parse_range(range: &str) -> Result<(u16, u16), String> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| format!("Invalid range \"{range}\""))?;
+    let (start, end) = (parse_address(start)?, parse_address(end)?);
+    if start > end {
+        return Err(format!("Range \"{range}\" starts after its end"));
+    }
+
+    Ok((start, end))
+}
@@ -0,0 +1,195 @@
+use super::*;
+
+fn parse(args: &[&str]) -> Result<Command, String> {
+    Command::parse(args.iter().map(ToString::to_string))
+}
+
+#[test]
+fn defaults() {
+    assert_eq!(
+        parse(&["test.obj"]),
+        Ok(Command::Run(Options {
+            files: vec!["test.obj".to_owned()],
+            ..Default::default()
+        }))
+    );
+}
+
+#[test]
+fn all_options() {
+    assert_eq!(
+        parse(&[
+            "--arch",
+            "lc3",
+            "-e",
+            "x3100",
+            "--max-instructions=1000",
+            "-i",
+            "input.txt",
+            "--debug",
+            "main.obj",
+            "lib.obj",
+        ]),
+        Ok(Command::Run(Options {
+            files: vec!["main.obj".to_owned(), "lib.obj".to_owned()],
+            architecture: Isa::Lc3,
+            entry_point: Some(0x3100),
+            max_instructions: Some(1000),
+            input: InputSource::File(PathBuf::from("input.txt")),
+            debug: true,
+            gdb: None,
+            snapshot: None,
+            trace: None,
+            trace_format: TraceFormat::Text,
+            statistics: false,
+            coverage: None,
+            profile: None,
+        }))
+    );
+}
+
+#[test]
+fn snapshot() {
+    assert_eq!(
+        parse(&["-s", "state.snap"]),
+        Ok(Command::Run(Options {
+            snapshot: Some("state.snap".to_owned()),
+            ..Default::default()
+        }))
+    );
+    assert!(parse(&["disasm", "--snapshot", "state.snap", "test.obj"]).is_err());
+}
+
+#[test]
+fn trace() {
+    assert_eq!(
+        parse(&["-t", "trace.jsonl", "--trace-format=JSONL", "test.obj"]),
+        Ok(Command::Run(Options {
+            files: vec!["test.obj".to_owned()],
+            trace: Some("trace.jsonl".to_owned()),
+            trace_format: TraceFormat::JsonLines,
+            ..Default::default()
+        }))
+    );
+    assert!(parse(&["--trace-format", "xml", "test.obj"]).is_err());
+    assert!(parse(&["-t", "trace.txt", "-d", "test.obj"]).is_err());
+    assert!(parse(&["disasm", "-t", "trace.txt", "test.obj"]).is_err());
+}
+
+#[test]
+fn statistics() {
+    assert_eq!(
+        parse(&["--stats", "test.obj"]),
+        Ok(Command::Run(Options {
+            files: vec!["test.obj".to_owned()],
+            statistics: true,
+            ..Default::default()
+        }))
+    );
+    assert!(parse(&["--stats", "-g", "1234", "test.obj"]).is_err());
+    assert!(parse(&["disasm", "--stats", "test.obj"]).is_err());
+}
+
+#[test]
+fn coverage() {
+    assert_eq!(
+        parse(&["-c", "coverage.info", "test.obj"]),
+        Ok(Command::Run(Options {
+            files: vec!["test.obj".to_owned()],
+            coverage: Some("coverage.info".to_owned()),
+            ..Default::default()
+        }))
+    );
+    assert!(parse(&["--coverage=coverage.info", "-d", "test.obj"]).is_err());
+    assert!(parse(&["disasm", "-c", "coverage.info", "test.obj"]).is_err());
+}
+
+#[test]
+fn profile() {
+    assert_eq!(
+        parse(&["-p", "profile.folded", "test.obj"]),
+        Ok(Command::Run(Options {
+            files: vec!["test.obj".to_owned()],
+            profile: Some("profile.folded".to_owned()),
+            ..Default::default()
+        }))
+    );
+    assert!(parse(&[
+        "--profile=profile.folded",
+        "-g",
+        "localhost:1234",
+        "test.obj"
+    ])
+    .is_err());
+    assert!(parse(&["disasm", "-p", "profile.folded", "test.obj"]).is_err());
+}
+
+#[test]
+fn gdb() {
+    let gdb = |address| match parse(&["--gdb", address, "test.obj"]) {
+        Ok(Command::Run(options)) => options.gdb,
+        _ => None,
+    };
+
+    assert_eq!(
+        gdb("localhost:1234"),
+        Some(GdbAddress::Tcp("localhost:1234".to_owned()))
+    );
+    assert_eq!(
+        gdb("unix:/tmp/lc3.sock"),
+        Some(GdbAddress::Unix(PathBuf::from("/tmp/lc3.sock")))
+    );
+    assert!(parse(&["--gdb", ":1234", "--debug", "test.obj"]).is_err());
+}
+
+#[test]
+fn disassemble() {
+    assert_eq!(
+        parse(&["disasm", "-a", "lc3", "--range=x0400-x042f"]),
+        Ok(Command::Disassemble(DisassembleOptions {
+            files: vec![],
+            architecture: Isa::Lc3,
+            range: Some((0x0400, 0x042f)),
+        }))
+    );
+    assert_eq!(
+        parse(&["disasm", "test.obj"]),
+        Ok(Command::Disassemble(DisassembleOptions {
+            files: vec!["test.obj".to_owned()],
+            ..Default::default()
+        }))
+    );
+
+    assert!(parse(&["disasm"]).is_err());
+    assert!(parse(&["disasm", "--range", "x3000", "test.obj"]).is_err());
+    assert!(parse(&["disasm", "--debug", "test.obj"]).is_err());
+    assert!(parse(&["--range", "x3000-x3010", "test.obj"]).is_err());
+}
+
+#[test]
+fn help() {
+    assert_eq!(parse(&["test.obj", "--help"]), Ok(Command::Help));
+}
+
+#[test]
+fn errors() {
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--arch", "lc4", "test.obj"]).is_err());
+    assert!(parse(&["--entry", "0x10000", "test.obj"]).is_err());
+    assert!(parse(&["--max-instructions"]).is_err());
+    assert!(parse(&["--verbose", "test.obj"]).is_err());
+}
+
+#[test]
+fn addresses() {
+    assert_eq!(parse_address("0x3000"), Ok(0x3000));
+    assert_eq!(parse_address("X3000"), Ok(0x3000));
+    assert_eq!(parse_address("12288"), Ok(0x3000));
+    assert!(parse_address("0xg000").is_err());
+
+    assert_eq!(parse_range("x3000-x30ff"), Ok((0x3000, 0x30ff)));
+    assert!(parse_range("x3000").is_err());
+    assert_eq!(parse_range("x3000-x3000"), Ok((0x3000, 0x3000)));
+    assert!(parse_range("x30ff-x3000").is_err());
+    assert!(parse(&["disasm", "--range=x30ff-x3000", "test.obj"]).is_err());
+}
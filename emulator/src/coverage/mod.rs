@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    ops::RangeInclusive,
+};
+
+use architectures::common::{Coverage, SymbolTable};
+
+use crate::{debugger::Debuggable, statistics::hundredths};
+
+/// Line of a source file
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SourceLine {
+    pub file: String,
+    /// Number of the line, starting from 1
+    pub line: u32,
+}
+
+/// Source lines of the addresses of a program
+pub type LineTable = BTreeMap<u16, SourceLine>;
+
+/// Report of the coverage of the object files loaded into memory
+pub struct CoverageReport<'a> {
+    coverage: &'a Coverage,
+    files: &'a [(String, RangeInclusive<u16>)],
+    symbols: Option<&'a SymbolTable>,
+    lines: Option<&'a LineTable>,
+}
+
+impl<'a> CoverageReport<'a> {
+    /// Report `coverage` for the object files in `files`, with the addresses
+    /// they occupy
+    #[must_use]
+    pub const fn new(coverage: &'a Coverage, files: &'a [(String, RangeInclusive<u16>)]) -> Self {
+        Self {
+            coverage,
+            files,
+            symbols: None,
+            lines: None,
+        }
+    }
+
+    /// Report the coverage of the labels in `symbols` as functions
+    #[must_use]
+    pub const fn with_symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Map the addresses to the source lines in `lines`
+    #[must_use]
+    pub const fn with_lines(mut self, lines: &'a LineTable) -> Self {
+        self.lines = Some(lines);
+        self
+    }
+
+    /// Write the report in the lcov tracefile format, with a record for each
+    /// source file. Without a line table there is a record for each object
+    /// file, whose line numbers are the addresses
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write_lcov<W: Write>(&self, mut output: W) -> io::Result<()> {
+        // Group the addresses by source file and line
+        let mut sources: BTreeMap<&str, BTreeMap<u32, Vec<u16>>> = BTreeMap::new();
+        for (file_name, addresses) in self.files {
+            for address in addresses.clone() {
+                let (file, line) = match self.lines {
+                    Some(lines) => match lines.get(&address) {
+                        Some(source) => (source.file.as_str(), source.line),
+                        None => continue,
+                    },
+                    None => (file_name.as_str(), u32::from(address)),
+                };
+                sources
+                    .entry(file)
+                    .or_default()
+                    .entry(line)
+                    .or_default()
+                    .push(address);
+            }
+        }
+
+        for (file, lines) in sources {
+            writeln!(output, "TN:\nSF:{file}")?;
+
+            // The labels are the functions, starting at their line
+            let mut functions = Vec::new();
+            for (line, addresses) in &lines {
+                for address in addresses {
+                    if let Some(label) = self.symbols.and_then(|symbols| symbols.get(address)) {
+                        functions.push((*line, label, self.hits(*address)));
+                    }
+                }
+            }
+            for (line, label, _) in &functions {
+                writeln!(output, "FN:{line},{label}")?;
+            }
+            for (_, label, hits) in &functions {
+                writeln!(output, "FNDA:{hits},{label}")?;
+            }
+            writeln!(output, "FNF:{}", functions.len())?;
+            writeln!(
+                output,
+                "FNH:{}",
+                functions.iter().filter(|(_, _, hits)| *hits > 0).count()
+            )?;
+
+            // A line is executed as many times as its most executed address,
+            // and has the branches of all its addresses
+            let (mut branches_found, mut branches_hit) = (0, 0);
+            for (line, addresses) in &lines {
+                let line_hits = addresses.iter().map(|address| self.hits(*address)).max();
+                for (block, address) in addresses.iter().enumerate() {
+                    if let Some(branch) = self.coverage.branches.get(address) {
+                        for (index, count) in [branch.taken, branch.not_taken].iter().enumerate() {
+                            if line_hits == Some(0) {
+                                writeln!(output, "BRDA:{line},{block},{index},-")?;
+                            } else {
+                                writeln!(output, "BRDA:{line},{block},{index},{count}")?;
+                            }
+                            branches_found += 1;
+                            branches_hit += usize::from(*count > 0);
+                        }
+                    }
+                }
+            }
+            writeln!(output, "BRF:{branches_found}\nBRH:{branches_hit}")?;
+            for (line, addresses) in &lines {
+                let hits = addresses.iter().map(|address| self.hits(*address)).max();
+                writeln!(output, "DA:{line},{}", hits.unwrap_or_default())?;
+            }
+            writeln!(output, "LF:{}", lines.len())?;
+            writeln!(
+                output,
+                "LH:{}",
+                lines
+                    .values()
+                    .filter(|addresses| addresses.iter().any(|address| self.hits(*address) > 0))
+                    .count()
+            )?;
+            writeln!(output, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a summary of the coverage of each object file, with the coverage
+    /// of its labels and the branches that haven't gone both ways, which are
+    /// disassembled from the memory of `cpu`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write_summary<E: Debuggable, W: Write>(
+        &self,
+        cpu: &mut E,
+        mut output: W,
+    ) -> io::Result<()> {
+        for (file_name, addresses) in self.files {
+            writeln!(
+                output,
+                "Coverage of {file_name} (x{:04X}-x{:04X}):",
+                addresses.start(),
+                addresses.end()
+            )?;
+            let (executed, total) = self.executed(addresses.clone());
+            writeln!(
+                output,
+                "  Addresses:          {}",
+                fraction(executed, total)
+            )?;
+
+            // Each branch can go two ways
+            let branches = self
+                .coverage
+                .branches
+                .range(addresses.clone())
+                .collect::<Vec<_>>();
+            let directions = branches
+                .iter()
+                .map(|(_, branch)| u64::from(branch.taken > 0) + u64::from(branch.not_taken > 0))
+                .sum();
+            writeln!(
+                output,
+                "  Branch directions:  {}",
+                fraction(directions, 2 * branches.len() as u64)
+            )?;
+
+            // Each label covers the addresses up to the next one
+            if let Some(symbols) = self.symbols {
+                let labels = symbols.range(addresses.clone()).collect::<Vec<_>>();
+                if !labels.is_empty() {
+                    writeln!(output, "  Labels:")?;
+                }
+                for (index, (address, label)) in labels.iter().enumerate() {
+                    let end = labels
+                        .get(index + 1)
+                        .map_or_else(|| *addresses.end(), |(next, _)| next.wrapping_sub(1));
+                    let (executed, total) = self.executed(**address..=end);
+                    writeln!(output, "    {label:<10}  {}", fraction(executed, total))?;
+                }
+            }
+
+            // List the branches that haven't gone both ways
+            let uncovered = branches
+                .iter()
+                .filter(|(_, branch)| !branch.is_covered())
+                .collect::<Vec<_>>();
+            if !uncovered.is_empty() {
+                writeln!(output, "  Branches not taken both ways:")?;
+            }
+            for (address, branch) in uncovered {
+                let text = E::disassemble(cpu.peek_memory(**address), **address, self.symbols);
+                write!(
+                    output,
+                    "    x{address:04X}  {text:<16}  taken {}, not taken {}",
+                    branch.taken, branch.not_taken
+                )?;
+                if let Some(source) = self.lines.and_then(|lines| lines.get(address)) {
+                    write!(output, "  ({}:{})", source.file, source.line)?;
+                }
+                writeln!(output)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get how many times the instruction at `address` has been executed
+    fn hits(&self, address: u16) -> u64 {
+        self.coverage
+            .hits
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Count the addresses executed among `addresses`, returning them along
+    /// with the total. With a line table only the addresses of the
+    /// instructions, which have a source line, are counted
+    fn executed(&self, addresses: RangeInclusive<u16>) -> (u64, u64) {
+        let Some(lines) = self.lines else {
+            let total = addresses.clone().count() as u64;
+            let executed = self.coverage.hits.range(addresses).count() as u64;
+            return (executed, total);
+        };
+
+        let total = lines.range(addresses.clone()).count() as u64;
+        let executed = self
+            .coverage
+            .hits
+            .range(addresses)
+            .filter(|(address, _)| lines.contains_key(address))
+            .count() as u64;
+        (executed, total)
+    }
+}
+
+/// Write `part` out of `total`, with the percentage if `total` is not zero
+fn fraction(part: u64, total: u64) -> String {
+    if total == 0 {
+        format!("{part}/{total}")
+    } else {
+        format!("{part}/{total} ({}%)", hundredths(part * 100, total))
+    }
+}
@@ -0,0 +1,120 @@
+use super::*;
+use architectures::{common::BranchCoverage, lc3::Lc3, Architecture};
+
+/// Coverage of a program at x3000-x3003 whose branch at x3001 has always been
+/// taken, and whose data at x3003 has never been executed
+fn coverage() -> Coverage {
+    Coverage {
+        hits: [(0x3000, 2), (0x3001, 2), (0x3002, 1)].into(),
+        branches: [(
+            0x3001,
+            BranchCoverage {
+                taken: 2,
+                not_taken: 0,
+            },
+        )]
+        .into(),
+    }
+}
+
+fn files() -> Vec<(String, RangeInclusive<u16>)> {
+    vec![("prog.obj".to_owned(), 0x3000..=0x3003)]
+}
+
+fn symbols() -> SymbolTable {
+    [(0x3000, "MAIN".to_owned()), (0x3003, "DATA".to_owned())].into()
+}
+
+fn lines() -> LineTable {
+    (0x3000..=0x3002)
+        .zip(3..)
+        .map(|(address, line)| {
+            let file = "prog.asm".to_owned();
+            (address, SourceLine { file, line })
+        })
+        .collect()
+}
+
+#[test]
+fn lcov_addresses() {
+    let (coverage, files, symbols) = (coverage(), files(), symbols());
+    let mut output = Vec::new();
+    CoverageReport::new(&coverage, &files)
+        .with_symbols(&symbols)
+        .write_lcov(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "TN:\nSF:prog.obj\n\
+         FN:12288,MAIN\nFN:12291,DATA\nFNDA:2,MAIN\nFNDA:0,DATA\nFNF:2\nFNH:1\n\
+         BRDA:12289,0,0,2\nBRDA:12289,0,1,0\nBRF:2\nBRH:1\n\
+         DA:12288,2\nDA:12289,2\nDA:12290,1\nDA:12291,0\nLF:4\nLH:3\n\
+         end_of_record\n"
+    );
+}
+
+#[test]
+fn lcov_lines() {
+    // The data has no source line, so it's not part of the report
+    let (coverage, files, lines) = (coverage(), files(), lines());
+    let mut output = Vec::new();
+    CoverageReport::new(&coverage, &files)
+        .with_lines(&lines)
+        .write_lcov(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "TN:\nSF:prog.asm\nFNF:0\nFNH:0\n\
+         BRDA:4,0,0,2\nBRDA:4,0,1,0\nBRF:2\nBRH:1\n\
+         DA:3,2\nDA:4,2\nDA:5,1\nLF:3\nLH:3\n\
+         end_of_record\n"
+    );
+}
+
+#[test]
+fn summary() {
+    // The data has no source line, so only the instructions are counted
+    let (coverage, files, symbols, lines) = (coverage(), files(), symbols(), lines());
+
+    // BRp x3000
+    let mut cpu = Lc3::new(0x3000);
+    cpu.set_memory(0x3001, 0x03fe);
+
+    let mut output = Vec::new();
+    CoverageReport::new(&coverage, &files)
+        .with_symbols(&symbols)
+        .with_lines(&lines)
+        .write_summary(&mut cpu, &mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Coverage of prog.obj (x3000-x3003):\n  \
+           Addresses:          3/3 (100.00%)\n  \
+           Branch directions:  1/2 (50.00%)\n  \
+           Labels:\n    \
+             MAIN        3/3 (100.00%)\n    \
+             DATA        0/0\n  \
+           Branches not taken both ways:\n    \
+             x3001  BRp MAIN          taken 2, not taken 0  (prog.asm:4)\n"
+    );
+}
+
+#[test]
+fn empty() {
+    let coverage = Coverage::default();
+    let files = files();
+    let mut output = Vec::new();
+    CoverageReport::new(&coverage, &files)
+        .write_summary(&mut Lc3::new(0x3000), &mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Coverage of prog.obj (x3000-x3003):\n  \
+           Addresses:          0/4 (0.00%)\n  \
+           Branch directions:  0/0\n"
+    );
+}
@@ -0,0 +1,536 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    sync::{Arc, Mutex},
+};
+
+use architectures::{
+    common::{ConditionCode, SymbolTable},
+    Error, StepOutcome,
+};
+
+use crate::{
+    cli::{parse_address, parse_range},
+    coverage::LineTable,
+    image::{write_memory, ImageFormat},
+    trace::condition_code_name,
+    Emulator, ExitStatus,
+};
+
+pub const HELP: &str = "\
+Commands:
+  b, break <ADDRESS>            Add a breakpoint
+  d, delete <ADDRESS>           Remove a breakpoint
+  breakpoints                   List the breakpoints
+  s, step [N]                   Execute N instructions [default: 1]
+  n, next                       Execute an instruction, running the called
+                                subroutine or trap routine as a single step
+  c, continue                   Run until a breakpoint is hit
+  f, finish                     Run until the current subroutine returns
+  bs, back [N]                  Undo the last N instructions [default: 1]
+  bc, rcontinue                 Undo the instructions until a breakpoint is hit
+  history [N]                   Remember the last N instructions to undo them
+                                [default: 10000]
+  r, registers                  Print the registers
+  set <REGISTER> <VALUE>        Change the value of a register
+  x, memory <ADDRESS> [COUNT]   Print COUNT memory cells [default: 1]
+  w, write <ADDRESS> <VALUE>... Write consecutive memory cells
+  cc                            Print the condition code
+  input <TEXT>                  Send a line of text to the keyboard
+  save <FILE>                   Save the state of the machine to a snapshot
+  load <FILE>                   Restore the state of the machine from a
+                                snapshot
+  dump <START>-<END> <FILE>     Write the memory cells from START to END to an
+                                image, whose format is chosen by the extension:
+                                .ihex, .hex, .bin or an object file
+  h, help                       Print this message
+  q, quit                       Stop the debugger
+
+An empty line repeats the last command.
+";
+
+/// Number of instructions that can be undone, unless changed by the `history`
+/// command
+pub const HISTORY_DEPTH: usize = 10_000;
+
+/// How an instruction changes the flow of the program
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum ControlFlow {
+    /// The instruction jumps to a subroutine or to a trap routine
+    Call,
+    /// The instruction returns from a subroutine or from a service routine
+    Return,
+    Other,
+}
+
+/// Architecture specific functionality needed by the debugger
+pub trait Debuggable:
+    Emulator<
+    Address = u16,
+    Data = u16,
+    Register: PartialEq + fmt::Debug,
+    RegisterData = u16,
+    ConditionCode = ConditionCode,
+>
+{
+    /// GDB target description of the registers, in the same order as
+    /// `registers()`
+    const TARGET_DESCRIPTION: &'static str;
+
+    /// Mnemonics of the opcodes, indexed by the 4 most significant bits of
+    /// the instructions
+    const OPCODES: [&'static str; 16];
+
+    /// Get the text of the instruction `word` stored at `address`, naming the
+    /// addresses it references with their labels from `symbols`
+    fn disassemble(word: u16, address: u16, symbols: Option<&SymbolTable>) -> String;
+
+    /// Registers shown by the debugger, with their names
+    fn registers() -> Vec<(&'static str, Self::Register)>;
+
+    /// Get how `instruction` changes the flow of the program
+    fn control_flow(instruction: u16) -> ControlFlow;
+
+    /// Write a memory cell without changing the Memory Address Register and
+    /// the Memory Data Register
+    fn poke_memory(&mut self, address: u16, data: u16);
+
+    /// Set a register without changing the condition code
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the value would put the processor
+    /// in an invalid state
+    fn poke_register(&mut self, register: &Self::Register, data: u16) -> Result<(), Error>;
+}
+
+/// Reason why the execution has been paused
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+enum Stop {
+    Done,
+    Breakpoint,
+    Halted,
+    InstructionLimit,
+    InvalidTrap,
+    Fault(Error),
+    HistoryStart,
+}
+
+/// How long the execution should go on
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+enum Resume {
+    Step(u64),
+    Next,
+    Continue,
+    Finish,
+}
+
+pub struct Debugger<E: Debuggable> {
+    cpu: E,
+    input_buffer: Arc<Mutex<VecDeque<u8>>>,
+    breakpoints: BTreeSet<u16>,
+    max_instructions: Option<u64>,
+    executed_instructions: u64,
+    exit_status: ExitStatus,
+    symbols: SymbolTable,
+    lines: LineTable,
+}
+
+impl<E: Debuggable> Debugger<E> {
+    /// Create a debugger for `cpu`, whose memory must already be set up with
+    /// `input_buffer` as the keyboard buffer. The last `HISTORY_DEPTH`
+    /// instructions executed can be undone
+    #[must_use]
+    pub fn new(
+        mut cpu: E,
+        input_buffer: Arc<Mutex<VecDeque<u8>>>,
+        max_instructions: Option<u64>,
+    ) -> Self {
+        cpu.set_history_depth(HISTORY_DEPTH);
+        Self {
+            cpu,
+            input_buffer,
+            breakpoints: BTreeSet::new(),
+            max_instructions,
+            executed_instructions: 0,
+            exit_status: ExitStatus::Halted,
+            symbols: SymbolTable::new(),
+            lines: LineTable::new(),
+        }
+    }
+
+    /// Show the labels in `symbols` in the disassembly of the instructions
+    #[must_use]
+    pub fn with_symbols(mut self, symbols: SymbolTable) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Show the source line of the instructions from `lines`
+    #[must_use]
+    pub fn with_lines(mut self, lines: LineTable) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Read the commands from `input` until the end of the input or a `quit`
+    /// command, writing the results to `output`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `input` can't be read or `output`
+    /// can't be written
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<ExitStatus> {
+        let mut last_command = String::new();
+        let mut lines = input.lines();
+
+        loop {
+            // Print the prompt and read a command
+            write!(output, "(debug) ")?;
+            output.flush()?;
+            let Some(line) = lines.next().transpose()? else {
+                break;
+            };
+
+            // Repeat the last command if the line is empty
+            let line = line.trim();
+            let command = if line.is_empty() {
+                last_command.clone()
+            } else {
+                line.to_owned()
+            };
+            if command.is_empty() {
+                continue;
+            }
+
+            // Execute the command
+            match self.execute(&command, &mut output) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(message) => writeln!(output, "Error: {message}")?,
+            }
+            last_command = command;
+        }
+
+        // If the program is still running then it has been interrupted
+        Ok(if self.cpu.is_running() {
+            ExitStatus::Interrupted
+        } else {
+            self.exit_status
+        })
+    }
+
+    /// Execute a command, returning `false` if the debugger should stop
+    fn execute<W: Write>(&mut self, command: &str, output: &mut W) -> Result<bool, String> {
+        let (name, arguments) = command.split_once(' ').unwrap_or((command, ""));
+        let mut arguments = arguments.split_whitespace();
+        let io_error = |error: io::Error| error.to_string();
+
+        match name {
+            "b" | "break" => {
+                let address = parse_address(arguments.next().ok_or("Missing address")?)?;
+                self.breakpoints.insert(address);
+                writeln!(output, "Breakpoint at x{address:04X}").map_err(io_error)?;
+            }
+
+            "d" | "delete" => {
+                let address = parse_address(arguments.next().ok_or("Missing address")?)?;
+                if !self.breakpoints.remove(&address) {
+                    return Err(format!("There is no breakpoint at x{address:04X}"));
+                }
+            }
+
+            "breakpoints" => {
+                for address in &self.breakpoints {
+                    writeln!(output, "x{address:04X}").map_err(io_error)?;
+                }
+            }
+
+            "s" | "step" | "n" | "next" | "c" | "continue" | "f" | "finish" | "bs" | "back"
+            | "bc" | "rcontinue" => {
+                let stop = self.run_command(name, arguments.next())?;
+                self.print_stop(&stop, output).map_err(io_error)?;
+            }
+            "history" => {
+                let depth = arguments.next().map_or(Ok(HISTORY_DEPTH), |depth| {
+                    depth
+                        .parse()
+                        .map_err(|_| format!("Invalid depth \"{depth}\""))
+                })?;
+                self.cpu.set_history_depth(depth);
+            }
+
+            "r" | "registers" => self.print_registers(output).map_err(io_error)?,
+
+            "set" => {
+                let name = arguments.next().ok_or("Missing register")?;
+                let register = E::registers()
+                    .into_iter()
+                    .find(|(register_name, _)| register_name.eq_ignore_ascii_case(name))
+                    .map(|(_, register)| register)
+                    .ok_or_else(|| format!("Unknown register \"{name}\""))?;
+                let value = parse_address(arguments.next().ok_or("Missing value")?)?;
+                self.cpu
+                    .poke_register(&register, value)
+                    .map_err(|error| error.to_string())?;
+            }
+
+            "x" | "memory" => {
+                let address = parse_address(arguments.next().ok_or("Missing address")?)?;
+                let count = arguments.next().map_or(Ok(1), parse_address)?;
+                for offset in 0..count {
+                    let address = address.wrapping_add(offset);
+                    let data = self.cpu.peek_memory(address);
+                    writeln!(output, "x{address:04X}: x{data:04X}").map_err(io_error)?;
+                }
+            }
+
+            "w" | "write" => {
+                let mut address = parse_address(arguments.next().ok_or("Missing address")?)?;
+                let values = arguments
+                    .map(parse_address)
+                    .collect::<Result<Vec<u16>, String>>()?;
+                if values.is_empty() {
+                    return Err("Missing value".to_owned());
+                }
+                for value in values {
+                    self.cpu.poke_memory(address, value);
+                    address = address.wrapping_add(1);
+                }
+            }
+
+            "cc" => {
+                let condition_code = condition_code_name(&self.cpu.get_condition_code());
+                writeln!(output, "{condition_code}").map_err(io_error)?;
+            }
+
+            "input" => {
+                let (_, text) = command.split_once(' ').ok_or("Missing text")?;
+                self.send_input(text)?;
+            }
+
+            "save" | "load" => {
+                let (_, file_name) = command.split_once(' ').ok_or("Missing file")?;
+                let result = if name == "save" {
+                    self.cpu.save_snapshot(file_name)
+                } else {
+                    self.cpu.load_snapshot(file_name)
+                };
+                result.map_err(|error| format!("Couldn't {name} \"{file_name}\": {error}"))?;
+            }
+
+            "dump" => {
+                let range = parse_range(arguments.next().ok_or("Missing range")?)?;
+                self.dump(range, arguments.next().ok_or("Missing file")?)?;
+            }
+
+            "h" | "help" => write!(output, "{HELP}").map_err(io_error)?,
+
+            "q" | "quit" => return Ok(false),
+
+            _ => {
+                return Err(format!(
+                    "Unknown command \"{name}\", type \"help\" for a list"
+                ))
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Write the memory cells from `start` to `end` to an image, in the format
+    /// of the extension of `file_name`
+    fn dump(&self, (start, end): (u16, u16), file_name: &str) -> Result<(), String> {
+        let format = ImageFormat::from_extension(file_name);
+        File::create(file_name)
+            .map(BufWriter::new)
+            .and_then(|mut file| {
+                write_memory(&self.cpu, start..=end, format, &mut file)?;
+                file.flush()
+            })
+            .map_err(|error| format!("Couldn't dump \"{file_name}\": {error}"))
+    }
+
+    /// Run or undo the program as requested by the command `name`, with the
+    /// optional number of steps `count`
+    fn run_command(&mut self, name: &str, count: Option<&str>) -> Result<Stop, String> {
+        Ok(match name {
+            "s" | "step" => self.run_until(Resume::Step(parse_count(count)?)),
+            "n" | "next" => self.run_until(Resume::Next),
+            "c" | "continue" => self.run_until(Resume::Continue),
+            "f" | "finish" => self.run_until(Resume::Finish),
+            "bs" | "back" => self.run_back(Some(parse_count(count)?)),
+            _ => self.run_back(None),
+        })
+    }
+
+    /// Print where the program has stopped and why
+    fn print_stop<W: Write>(&self, stop: &Stop, output: &mut W) -> io::Result<()> {
+        match stop {
+            Stop::Done => {}
+            Stop::Breakpoint => writeln!(output, "Breakpoint reached")?,
+            Stop::Halted => writeln!(output, "The program has halted")?,
+            Stop::InstructionLimit => writeln!(output, "Instruction limit reached")?,
+            Stop::InvalidTrap => writeln!(output, "Invalid trap")?,
+            Stop::Fault(error) => writeln!(output, "Fault: {error}")?,
+            Stop::HistoryStart => writeln!(output, "Reached the start of the history")?,
+        }
+
+        // Print the next instruction
+        if *stop != Stop::Halted {
+            let address = self.cpu.get_program_counter();
+            let instruction = self.cpu.peek_memory(address);
+            if let Some(label) = self.symbols.get(&address) {
+                writeln!(output, "{label}:")?;
+            }
+            write!(
+                output,
+                "x{address:04X}: x{instruction:04X}  {}",
+                E::disassemble(instruction, address, Some(&self.symbols))
+            )?;
+            if let Some(source) = self.lines.get(&address) {
+                write!(output, "  ({}:{})", source.file, source.line)?;
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a line of text to the keyboard
+    fn send_input(&self, text: &str) -> Result<(), String> {
+        let mut buffer = self
+            .input_buffer
+            .lock()
+            .map_err(|_| "Couldn't lock the keyboard buffer")?;
+        buffer.extend(text.bytes());
+        buffer.push_back(b'\n');
+        drop(buffer);
+
+        Ok(())
+    }
+
+    fn run_until(&mut self, resume: Resume) -> Stop {
+        // Depth of the subroutine calls compared to the starting point
+        let mut depth: i64 = 0;
+        let mut steps: u64 = 0;
+
+        loop {
+            // Stop if the CPU is halted or if the maximum number of
+            // instructions has been executed
+            if !self.cpu.is_running() {
+                return Stop::Halted;
+            }
+            if self
+                .max_instructions
+                .is_some_and(|max| self.executed_instructions >= max)
+            {
+                self.exit_status = ExitStatus::InstructionLimit;
+                return Stop::InstructionLimit;
+            }
+
+            // Step a CPU instruction, stopping if it can't be executed
+            let instruction = self.cpu.peek_memory(self.cpu.get_program_counter());
+            let interrupts = self.cpu.statistics().interrupts;
+            let outcome = match self.cpu.step_instruction() {
+                Ok(outcome) => outcome,
+                Err(error) => {
+                    self.exit_status = ExitStatus::Fault;
+                    return Stop::Fault(error);
+                }
+            };
+            self.executed_instructions += 1;
+            steps += 1;
+
+            // Keep track of the subroutine calls. Entering an exception or an
+            // interrupt service routine is a call too, whose RTI returns
+            // from it, and an instruction that raises an exception doesn't
+            // change the flow by itself
+            depth += match (outcome, E::control_flow(instruction)) {
+                (StepOutcome::Exception(_), _) | (_, ControlFlow::Call) => 1,
+                (_, ControlFlow::Return) => -1,
+                (_, ControlFlow::Other) => 0,
+            };
+            let accepted = self.cpu.statistics().interrupts.saturating_sub(interrupts);
+            depth += i64::try_from(accepted).unwrap_or(i64::MAX);
+
+            // Stop if the invalid trap routine has been called
+            let program_counter = self.cpu.get_program_counter();
+            if program_counter == E::INVALID_TRAP_ROUTINE {
+                self.exit_status = ExitStatus::InvalidTrap;
+                return Stop::InvalidTrap;
+            }
+
+            // Stop if the command is satisfied
+            let done = match resume {
+                Resume::Step(count) => steps >= count,
+                Resume::Next => depth <= 0,
+                Resume::Continue => false,
+                Resume::Finish => depth < 0,
+            };
+            if !self.cpu.is_running() {
+                return Stop::Halted;
+            }
+            if done {
+                return Stop::Done;
+            }
+
+            // Stop on the breakpoints
+            if self.breakpoints.contains(&program_counter) {
+                return Stop::Breakpoint;
+            }
+        }
+    }
+
+    /// Undo `count` instructions, or all of them if `None`, stopping early
+    /// on the breakpoints or if there are no more instructions to undo
+    fn run_back(&mut self, count: Option<u64>) -> Stop {
+        let mut steps: u64 = 0;
+
+        loop {
+            if !self.cpu.step_back() {
+                return Stop::HistoryStart;
+            }
+            self.executed_instructions = self.executed_instructions.saturating_sub(1);
+            steps += 1;
+
+            // Stop if the command is satisfied or on the breakpoints
+            if count.is_some_and(|count| steps >= count) {
+                return Stop::Done;
+            }
+            if self.breakpoints.contains(&self.cpu.get_program_counter()) {
+                return Stop::Breakpoint;
+            }
+        }
+    }
+
+    fn print_registers<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        // Print four registers per line
+        for registers in E::registers().chunks(4) {
+            let line = registers
+                .iter()
+                .map(|(name, register)| {
+                    format!("{name:<3} x{:04X}", self.cpu.get_register(register))
+                })
+                .collect::<Vec<String>>()
+                .join("  ");
+            writeln!(output, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the optional number of steps given to a command, defaulting to 1
+fn parse_count(count: Option<&str>) -> Result<u64, String> {
+    count.map_or(Ok(1), |count| {
+        count
+            .parse()
+            .map_err(|_| format!("Invalid number of steps \"{count}\""))
+    })
+}
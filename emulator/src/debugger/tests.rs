@@ -0,0 +1,345 @@
+use super::*;
+use crate::coverage::SourceLine;
+use architectures::{
+    lc2::{Lc2, MACHINE_CONTROL_REGISTER},
+    lc3::Lc3,
+    Architecture, WatcherType,
+};
+use std::{cell::Cell, rc::Rc};
+
+#[allow(clippy::unusual_byte_groupings)]
+const LC2_PROGRAM: [u16; 7] = [
+    0b0001_000_000_1_00001, // x3000: ADD R0, R0, #1
+    0b0100_1_00_000000101,  // x3001: JSR x3005
+    0b0001_000_000_1_00001, // x3002: ADD R0, R0, #1
+    0b1111_0000_00100101,   // x3003: TRAP x25 (HALT)
+    0,                      // x3004
+    0b0001_001_001_1_00010, // x3005: ADD R1, R1, #2
+    0b1101_000000000000,    // x3006: RET
+];
+
+fn debug<E: Debuggable>(mut cpu: E, program: &[u16], commands: &str) -> (ExitStatus, String) {
+    // Set the CPU up and load the program
+    let input_buffer = Arc::new(Mutex::new(VecDeque::new()));
+    cpu.setup_memory(input_buffer.clone());
+    for (address, instruction) in (0x3000..).zip(program) {
+        cpu.set_memory(address, *instruction);
+    }
+    cpu.set_entry_point(0x3000);
+
+    // Run the commands
+    let mut output = Vec::new();
+    let exit_status = Debugger::new(cpu, input_buffer, None)
+        .run(commands.as_bytes(), &mut output)
+        .unwrap();
+
+    (exit_status, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn breakpoint() {
+    let (exit_status, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        "break x3005\nbreakpoints\ncontinue\nregisters\nquit\n",
+    );
+
+    assert_eq!(exit_status, ExitStatus::Interrupted);
+    assert!(output.contains("Breakpoint at x3005"));
+    assert!(output.contains("Breakpoint reached\nx3005: x1262"));
+    assert!(output.contains("R0  x0001  R1  x0000"));
+    assert!(output.contains("PC  x3005"));
+}
+
+#[test]
+fn step() {
+    let (_, output) = debug(Lc2::new(0x3000), &LC2_PROGRAM, "step\n\nstep 2\n");
+
+    // The empty line repeats the last command
+    assert!(output.contains("x3001: x4805"));
+    assert!(output.contains("x3005: x1262"));
+    assert!(output.contains("x3002: x1021"));
+}
+
+#[test]
+fn next() {
+    let (_, output) = debug(Lc2::new(0x3000), &LC2_PROGRAM, "step\nnext\nregisters\n");
+
+    // The subroutine is executed as a single step
+    assert!(output.contains("x3002: x1021"));
+    assert!(output.contains("R0  x0001  R1  x0002"));
+}
+
+#[test]
+fn finish() {
+    let (_, output) = debug(Lc2::new(0x3000), &LC2_PROGRAM, "step 2\nfinish\n");
+
+    assert!(output.contains("x3005: x1262"));
+    assert!(output.contains("x3002: x1021"));
+}
+
+#[test]
+fn halt() {
+    let (exit_status, output) = debug(Lc2::new(0x3000), &LC2_PROGRAM, "continue\nstep\n");
+
+    assert_eq!(exit_status, ExitStatus::Halted);
+    assert_eq!(output.matches("The program has halted").count(), 2);
+}
+
+#[test]
+fn running_doesnt_read_machine_control_register() {
+    // Count the reads of the Machine Control Register
+    let mut cpu = Lc2::new(0x3000);
+    let reads = Rc::new(Cell::new(0));
+    let reads_watcher = reads.clone();
+    cpu.add_memory_watcher(MACHINE_CONTROL_REGISTER, WatcherType::OnRead, move |_| {
+        reads_watcher.set(reads_watcher.get() + 1);
+    });
+
+    // Checking if the processor is running doesn't read it
+    let (_, output) = debug(cpu, &LC2_PROGRAM, "step 2\n");
+    assert!(output.contains("x3005: x1262"));
+    assert_eq!(reads.get(), 0);
+}
+
+#[test]
+fn edit() {
+    let (_, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        "write x4000 x1234 5\nmemory x4000 2\nset r3 xff\nset pc x3002\nregisters\ncc\n",
+    );
+
+    assert!(output.contains("x4000: x1234\nx4001: x0005"));
+    assert!(output.contains("R3  x00FF"));
+    assert!(output.contains("PC  x3002"));
+
+    // Editing the registers doesn't change the condition code
+    assert!(output.contains("(debug) Z\n"));
+}
+
+#[test]
+fn memory_keeps_input() {
+    let input_buffer = Arc::new(Mutex::new(VecDeque::from(b"a".to_vec())));
+    let mut cpu = Lc3::new(0x3000);
+    cpu.setup_memory(input_buffer.clone());
+
+    // Examining the Keyboard Data Register doesn't consume the input
+    let mut output = Vec::new();
+    Debugger::new(cpu, input_buffer.clone(), None)
+        .run(&b"x xFE02\nx xFE00 3\n"[..], &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("xFE02: x0061\n"));
+    assert!(output.contains("xFE00: x8000\nxFE01: x0000\nxFE02: x0061\n"));
+    assert_eq!(input_buffer.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn poisoned_input() {
+    // Poison the keyboard buffer by panicking while it's locked
+    let input_buffer = Arc::new(Mutex::new(VecDeque::new()));
+    let input_buffer_thread = input_buffer.clone();
+    let _ = std::thread::spawn(move || {
+        let _lock = input_buffer_thread.lock();
+        panic!("The input thread has panicked");
+    })
+    .join();
+
+    let mut output = Vec::new();
+    Debugger::new(Lc3::new(0x3000), input_buffer, None)
+        .run(&b"input a\n"[..], &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Error: Couldn't lock the keyboard buffer"));
+}
+
+#[test]
+fn errors() {
+    let (_, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        "jump\nbreak\ndelete x3000\nset R8 0\nstep x\n",
+    );
+
+    assert!(output.contains("Error: Unknown command \"jump\""));
+    assert!(output.contains("Error: Missing address"));
+    assert!(output.contains("Error: There is no breakpoint at x3000"));
+    assert!(output.contains("Error: Unknown register \"R8\""));
+    assert!(output.contains("Error: Invalid number of steps \"x\""));
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn lc3_trap() {
+    let (_, output) = debug(
+        Lc3::new(0x3000),
+        &[
+            0b0101_000_000_1_00000, // x3000: AND R0, R0, #0
+            0b1111_0000_00100001,   // x3001: TRAP x21 (OUT)
+            0b1111_0000_00100101,   // x3002: TRAP x25 (HALT)
+        ],
+        "break x3001\ncontinue\nnext\nregisters\n",
+    );
+
+    // The trap routine is executed as a single step, returning to the
+    // "Supervisor" privilege mode of the program
+    assert!(output.contains("Breakpoint reached\nx3001: xF021"));
+    assert!(output.contains("x3002: xF025"));
+    assert!(output.contains("PSR x0002"));
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn lc3_exception() {
+    let (_, output) = debug(
+        Lc3::new(0x3000),
+        &[
+            0b0100_1_00000000010,  // x3000: JSR x3003
+            0b1111_0000_00100101,  // x3001: TRAP x25 (HALT)
+            0,                     // x3002
+            0b1101_000000000000,   // x3003: illegal opcode
+            0b1100_000_111_000000, // x3004: RET
+            0b1000_000000000000,   // x3005: RTI
+        ],
+        "break x3000\ncontinue\nwrite x0101 x3005\nstep\nfinish\n",
+    );
+
+    // The RTI of the exception service routine doesn't return from the
+    // subroutine
+    assert!(output.contains("x3003: xD000"));
+    assert!(output.contains("(debug) x3001: xF025"));
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn lc3_interrupt() {
+    let (_, output) = debug(
+        Lc3::new(0x3000),
+        &[
+            0b0001_000_000_1_00001, // x3000: ADD R0, R0, #1
+            0b0001_000_000_1_00001, // x3001: ADD R0, R0, #1
+            0b1111_0000_00100101,   // x3002: TRAP x25 (HALT)
+            0xfe02,                 // x3003: address of the KBDR
+            0b1010_010_111111110,   // x3004: LDI R2, x3003
+            0b1000_000000000000,    // x3005: RTI
+        ],
+        "break x3000\ncontinue\nwrite x0180 x3004\nwrite xFE00 x4000\ninput a\nnext\nregisters\n",
+    );
+
+    // The interrupt service routine is executed as part of the step
+    assert!(output.contains("(debug) x3001: x1021"));
+    assert!(output.contains("R0  x0001  R1  x0000  R2  x000A"));
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn fault() {
+    let (_, output) = debug(
+        Lc3::new(0x3000),
+        &[0b1000_000000000000], // x3000: RTI
+        "set psr x8000\nset pc x3000\nset psr x0002\nset r6 x4000\nwrite x4000 x3000 x0003\nstep\n",
+    );
+
+    // The debugger doesn't put the processor in an invalid state, but the
+    // program can
+    assert!(
+        output.contains("Error: The Processor Status Register x8000 has an invalid Condition Code")
+    );
+    assert!(output.contains(
+        "Fault: The Processor Status Register x0003 has an invalid Condition Code\nx3000: x8000"
+    ));
+}
+
+#[test]
+fn snapshot() {
+    let path = std::env::temp_dir().join(format!("debugger-snapshot-{}", std::process::id()));
+    let path = path.to_str().unwrap();
+    let (_, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        &format!("step 2\nsave {path}\nstep 2\nload {path}\nregisters\nload {path}.missing\n"),
+    );
+    std::fs::remove_file(path).unwrap();
+
+    // The machine is back to the state after the first two steps
+    assert!(output.contains("R0  x0001  R1  x0000"));
+    assert!(output.contains("PC  x3005"));
+    assert!(output.contains(&format!("Error: Couldn't load \"{path}.missing\"")));
+}
+
+#[test]
+fn step_back() {
+    let (exit_status, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        "continue\nbreak x3003\nrcontinue\nregisters\nback 2\nbc\nhistory 0\nstep\nback\nhistory\nstep\nback\n",
+    );
+
+    // Undoing the HALT resumes the program
+    assert_eq!(exit_status, ExitStatus::Interrupted);
+    assert!(output.contains("Breakpoint reached\nx3003: xF025"));
+    assert!(output.contains("R0  x0002  R1  x0002"));
+    assert!(output.contains("(debug) x3006: xD000"));
+    assert!(output.contains("Reached the start of the history\nx3000: x1021"));
+
+    // Nothing can be undone without a history
+    assert!(
+        output.contains("x3001: x4805  JSR x3005\n(debug) Reached the start of the history\nx3001")
+    );
+
+    // The history is enabled again with its default depth
+    assert!(
+        output.ends_with("x3005: x1262  ADD R1, R1, #2\n(debug) x3001: x4805  JSR x3005\n(debug) ")
+    );
+}
+
+#[test]
+fn labels() {
+    let mut cpu = Lc2::new(0x3000);
+    let input_buffer = Arc::new(Mutex::new(VecDeque::new()));
+    cpu.setup_memory(input_buffer.clone());
+    for (address, instruction) in (0x3000..).zip(LC2_PROGRAM) {
+        cpu.poke_memory(address, instruction);
+    }
+    cpu.set_entry_point(0x3000);
+
+    // The labels are shown before their address and in the operands, and the
+    // source lines after the instructions
+    let mut output = Vec::new();
+    Debugger::new(cpu, input_buffer, None)
+        .with_symbols([(0x3005, "DOUBLE".to_owned())].into())
+        .with_lines(
+            [(
+                0x3005,
+                SourceLine {
+                    file: "prog.asm".to_owned(),
+                    line: 9,
+                },
+            )]
+            .into(),
+        )
+        .run(&b"step\nstep\n"[..], &mut output)
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("x3001: x4805  JSR DOUBLE\n"));
+    assert!(output.contains("DOUBLE:\nx3005: x1262  ADD R1, R1, #2  (prog.asm:9)\n"));
+}
+
+#[test]
+fn dump() {
+    let path = std::env::temp_dir().join(format!("debugger-dump-{}.hex", std::process::id()));
+    let path = path.to_str().unwrap();
+    let (_, output) = debug(
+        Lc2::new(0x3000),
+        &LC2_PROGRAM,
+        &format!("dump x3005-x3006 {path}\ndump x3005 {path}\ndump x3005-x3006\n"),
+    );
+    let image = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(image, "3005\n1262\nD000\n");
+    assert!(output.contains("Error: Invalid range \"x3005\""));
+    assert!(output.contains("Error: Missing file"));
+}
@@ -0,0 +1,612 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::TcpStream,
+    rc::Rc,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use architectures::{WatcherId, WatcherType};
+
+use crate::{debugger::Debuggable, ExitStatus};
+
+/// Number of instructions executed between two checks for a Ctrl-C sent by
+/// the debugger, must be a power of two
+const INTERRUPT_CHECK_INTERVAL: u64 = 1024;
+
+/// Stream used to talk to the debugger
+pub trait Connection: Read + Write {
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the mode of the stream can't be
+    /// changed
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl Connection for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        Self::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        Self::set_nonblocking(self, nonblocking)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+enum Watchpoint {
+    Write,
+    Read,
+    Access,
+}
+
+impl Watchpoint {
+    const fn watches(self, watcher_type: &WatcherType) -> bool {
+        matches!(
+            (self, watcher_type),
+            (Self::Access, _)
+                | (Self::Write, WatcherType::OnWrite)
+                | (Self::Read, WatcherType::OnRead)
+        )
+    }
+}
+
+/// Reason why the execution has been paused
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+enum Stop {
+    Step,
+    Breakpoint,
+    Watchpoint(u16, WatcherType),
+    Interrupt,
+    Fault,
+    Exited(ExitStatus),
+}
+
+enum Packet {
+    Command(String),
+    Interrupt,
+}
+
+enum Reply {
+    Send(String),
+    SendAndClose(String),
+    Close,
+}
+
+/// GDB Remote Serial Protocol stub
+///
+/// The LC-2 and the LC-3 are word-addressable, so the addresses in the packets
+/// are word addresses, while the lengths are in bytes. Both the memory cells
+/// and the registers are sent in big-endian order
+pub struct GdbStub<E: Debuggable> {
+    cpu: E,
+    breakpoints: BTreeSet<u16>,
+    /// Watchpoints, with the ids of the memory watchers that implement them
+    watchpoints: BTreeMap<(u16, Watchpoint), Vec<WatcherId>>,
+    watchpoint_hit: Rc<Cell<Option<(u16, bool)>>>,
+    max_instructions: Option<u64>,
+    executed_instructions: u64,
+    exit_status: ExitStatus,
+    last_stop: Stop,
+    acknowledge: bool,
+    /// Bytes received while checking for a Ctrl-C that belong to a packet
+    received: VecDeque<u8>,
+}
+
+impl<E: Debuggable> GdbStub<E> {
+    /// Create a stub for `cpu`, whose memory must already be set up
+    #[must_use]
+    pub fn new(cpu: E, max_instructions: Option<u64>) -> Self {
+        Self {
+            cpu,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            watchpoint_hit: Rc::new(Cell::new(None)),
+            max_instructions,
+            executed_instructions: 0,
+            exit_status: ExitStatus::Halted,
+            last_stop: Stop::Step,
+            acknowledge: true,
+            received: VecDeque::new(),
+        }
+    }
+
+    /// Serve the packets sent through `connection` until the debugger detaches
+    /// or kills the program, or until the connection is closed
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if `connection` can't be read or
+    /// written
+    pub fn run<C: Connection>(&mut self, mut connection: C) -> io::Result<ExitStatus> {
+        while let Some(packet) = self.read_packet(&mut connection)? {
+            // A Ctrl-C received while the program is paused is ignored
+            let Packet::Command(packet) = packet else {
+                continue;
+            };
+
+            match self.handle(&packet, &mut connection)? {
+                Reply::Send(reply) => write_packet(&mut connection, &reply)?,
+                Reply::SendAndClose(reply) => {
+                    write_packet(&mut connection, &reply)?;
+                    break;
+                }
+                Reply::Close => break,
+            }
+        }
+
+        // If the program is still running then it has been interrupted
+        Ok(if self.cpu.is_running() {
+            ExitStatus::Interrupted
+        } else {
+            self.exit_status
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn handle<C: Connection>(&mut self, packet: &str, connection: &mut C) -> io::Result<Reply> {
+        let error = || Reply::Send("E01".to_owned());
+        let Some(command) = packet.chars().next() else {
+            return Ok(Reply::Send(String::new()));
+        };
+        let arguments = &packet[command.len_utf8()..];
+
+        Ok(match command {
+            '?' => Reply::Send(self.stop_reply()),
+
+            // Read all the registers
+            'g' => Reply::Send(E::registers().iter().fold(
+                String::new(),
+                |mut reply, (_, register)| {
+                    let _ = write!(reply, "{:04x}", self.cpu.get_register(register));
+                    reply
+                },
+            )),
+
+            // Write all the registers
+            'G' => {
+                let registers = E::registers();
+                let Some(values) = parse_words(arguments) else {
+                    return Ok(error());
+                };
+                if values.len() != registers.len() {
+                    return Ok(error());
+                }
+                let result = registers
+                    .iter()
+                    .zip(values)
+                    .try_for_each(|((_, register), value)| self.cpu.poke_register(register, value));
+                match result {
+                    Ok(()) => Reply::Send("OK".to_owned()),
+                    Err(_) => error(),
+                }
+            }
+
+            // Read a register
+            'p' => match parse_hex(arguments).and_then(|n| E::registers().into_iter().nth(n)) {
+                Some((_, register)) => {
+                    Reply::Send(format!("{:04x}", self.cpu.get_register(&register)))
+                }
+                None => error(),
+            },
+
+            // Write a register
+            'P' => {
+                let Some((number, value)) = arguments.split_once('=') else {
+                    return Ok(error());
+                };
+                let register = parse_hex(number).and_then(|n| E::registers().into_iter().nth(n));
+                match (register, parse_words(value).as_deref()) {
+                    (Some((_, register)), Some(&[value]))
+                        if self.cpu.poke_register(&register, value).is_ok() =>
+                    {
+                        Reply::Send("OK".to_owned())
+                    }
+                    _ => error(),
+                }
+            }
+
+            // Read the memory
+            'm' => {
+                let Some((address, length)) = parse_range(arguments) else {
+                    return Ok(error());
+                };
+                let mut reply = String::new();
+                for offset in 0..length.div_ceil(2) {
+                    let data = self.cpu.peek_memory(address.wrapping_add(offset));
+                    let _ = write!(reply, "{data:04x}");
+                }
+                reply.truncate(usize::from(length) * 2);
+                Reply::Send(reply)
+            }
+
+            // Write the memory
+            'M' => {
+                let Some((range, data)) = arguments.split_once(':') else {
+                    return Ok(error());
+                };
+                match (parse_range(range), parse_words(data)) {
+                    (Some((address, length)), Some(words))
+                        if words.len() * 2 == usize::from(length) =>
+                    {
+                        for (address, word) in (address..=u16::MAX).zip(words) {
+                            self.cpu.poke_memory(address, word);
+                        }
+                        Reply::Send("OK".to_owned())
+                    }
+                    _ => error(),
+                }
+            }
+
+            // Continue and single step, optionally from a new address
+            'c' | 's' => {
+                if !arguments.is_empty() {
+                    let Some(address) = parse_hex(arguments) else {
+                        return Ok(error());
+                    };
+                    self.cpu.set_program_counter(address);
+                }
+                self.last_stop = self.resume(command == 's', connection)?;
+                Reply::Send(self.stop_reply())
+            }
+
+            // Add and remove breakpoints and watchpoints
+            'Z' | 'z' => {
+                let mut fields = arguments.split(',');
+                let (Some(kind), Some(Some(address))) =
+                    (fields.next(), fields.next().map(parse_hex))
+                else {
+                    return Ok(error());
+                };
+                let watchpoint = match kind {
+                    "0" | "1" => None,
+                    "2" => Some(Watchpoint::Write),
+                    "3" => Some(Watchpoint::Read),
+                    "4" => Some(Watchpoint::Access),
+                    _ => return Ok(Reply::Send(String::new())),
+                };
+                match (watchpoint, command == 'Z') {
+                    (None, true) => {
+                        self.breakpoints.insert(address);
+                    }
+                    (None, false) => {
+                        self.breakpoints.remove(&address);
+                    }
+                    (Some(watchpoint), true) => self.add_watchpoint(address, watchpoint),
+                    (Some(watchpoint), false) => self.remove_watchpoint(address, watchpoint),
+                }
+                Reply::Send("OK".to_owned())
+            }
+
+            'q' => Reply::Send(Self::query(arguments)),
+
+            'Q' if arguments == "StartNoAckMode" => {
+                self.acknowledge = false;
+                Reply::Send("OK".to_owned())
+            }
+
+            // There is only one thread
+            'H' | 'T' => Reply::Send("OK".to_owned()),
+
+            'D' => Reply::SendAndClose("OK".to_owned()),
+            'k' => Reply::Close,
+            'v' if arguments == "Kill" => Reply::SendAndClose("OK".to_owned()),
+
+            _ => Reply::Send(String::new()),
+        })
+    }
+
+    fn query(query: &str) -> String {
+        if query.starts_with("Supported") {
+            return "PacketSize=1000;qXfer:features:read+;swbreak+;hwbreak+;QStartNoAckMode+"
+                .to_owned();
+        }
+
+        // Send the requested part of the target description
+        if let Some(range) = query.strip_prefix("Xfer:features:read:target.xml:") {
+            let Some((offset, length)): Option<(usize, usize)> = range
+                .split_once(',')
+                .and_then(|(offset, length)| Some((parse_hex(offset)?, parse_hex(length)?)))
+            else {
+                return "E01".to_owned();
+            };
+            let description = E::TARGET_DESCRIPTION;
+            let start = offset.min(description.len());
+            let end = start.saturating_add(length).min(description.len());
+            let prefix = if end == description.len() { 'l' } else { 'm' };
+            return format!("{prefix}{}", &description[start..end]);
+        }
+
+        match query {
+            "Attached" => "1".to_owned(),
+            "C" => "QC1".to_owned(),
+            "fThreadInfo" => "m1".to_owned(),
+            "sThreadInfo" => "l".to_owned(),
+            _ => String::new(),
+        }
+    }
+
+    fn stop_reply(&self) -> String {
+        match &self.last_stop {
+            Stop::Step => "S05".to_owned(),
+            Stop::Breakpoint => "T05swbreak:;".to_owned(),
+            Stop::Watchpoint(address, watcher_type) => {
+                let kind = if self
+                    .watchpoints
+                    .contains_key(&(*address, Watchpoint::Access))
+                {
+                    "awatch"
+                } else if *watcher_type == WatcherType::OnWrite {
+                    "watch"
+                } else {
+                    "rwatch"
+                };
+                format!("T05{kind}:{address:04x};")
+            }
+            Stop::Interrupt => "S02".to_owned(),
+            Stop::Fault => "S04".to_owned(),
+            Stop::Exited(exit_status) => format!("W{:02x}", exit_status.code()),
+        }
+    }
+
+    /// Run the program until it is stopped by a breakpoint, a watchpoint or
+    /// the debugger, or for a single instruction if `single_step` is set
+    fn resume<C: Connection>(&mut self, single_step: bool, connection: &mut C) -> io::Result<Stop> {
+        // Don't block while checking for a Ctrl-C
+        connection.set_nonblocking(true)?;
+        let stop = self.run_until_stop(single_step, connection);
+        connection.set_nonblocking(false)?;
+
+        stop
+    }
+
+    fn run_until_stop<C: Connection>(
+        &mut self,
+        single_step: bool,
+        connection: &mut C,
+    ) -> io::Result<Stop> {
+        let mut steps: u64 = 0;
+
+        loop {
+            // Stop if the CPU is halted or if the maximum number of
+            // instructions has been executed
+            if !self.cpu.is_running() {
+                return Ok(Stop::Exited(self.exit_status));
+            }
+            if self
+                .max_instructions
+                .is_some_and(|max| self.executed_instructions >= max)
+            {
+                self.exit_status = ExitStatus::InstructionLimit;
+                return Ok(Stop::Exited(self.exit_status));
+            }
+
+            // Step a CPU instruction
+            self.watchpoint_hit.set(None);
+            if self.cpu.step_instruction().is_err() {
+                return Ok(Stop::Fault);
+            }
+            self.executed_instructions += 1;
+            steps += 1;
+
+            // If the invalid trap routine has been called, remember it
+            let program_counter = self.cpu.get_program_counter();
+            if program_counter == E::INVALID_TRAP_ROUTINE {
+                self.exit_status = ExitStatus::InvalidTrap;
+            }
+
+            // Stop on the watchpoints, on the single steps and on the
+            // breakpoints
+            if let Some((address, write)) = self.watchpoint_hit.take() {
+                let watcher_type = if write {
+                    WatcherType::OnWrite
+                } else {
+                    WatcherType::OnRead
+                };
+                return Ok(Stop::Watchpoint(address, watcher_type));
+            }
+            if single_step {
+                return Ok(Stop::Step);
+            }
+            if self.breakpoints.contains(&program_counter) {
+                return Ok(Stop::Breakpoint);
+            }
+
+            // Every now and then check if the debugger wants to pause the
+            // program
+            if steps & (INTERRUPT_CHECK_INTERVAL - 1) == 0
+                && self.interrupt_requested(connection)?
+            {
+                return Ok(Stop::Interrupt);
+            }
+        }
+    }
+
+    fn add_watchpoint(&mut self, address: u16, watchpoint: Watchpoint) {
+        if self.watchpoints.contains_key(&(address, watchpoint)) {
+            return;
+        }
+
+        let mut watchers = Vec::new();
+        for watcher_type in [WatcherType::OnRead, WatcherType::OnWrite] {
+            if watchpoint.watches(&watcher_type) {
+                let write = watcher_type == WatcherType::OnWrite;
+                let watchpoint_hit = self.watchpoint_hit.clone();
+                watchers.push(
+                    self.cpu
+                        .add_memory_watcher(address, watcher_type, move |_| {
+                            if watchpoint_hit.get().is_none() {
+                                watchpoint_hit.set(Some((address, write)));
+                            }
+                        }),
+                );
+            }
+        }
+        self.watchpoints.insert((address, watchpoint), watchers);
+    }
+
+    fn remove_watchpoint(&mut self, address: u16, watchpoint: Watchpoint) {
+        for id in self
+            .watchpoints
+            .remove(&(address, watchpoint))
+            .into_iter()
+            .flatten()
+        {
+            self.cpu.remove_memory_watcher(id);
+        }
+    }
+
+    /// Check, without blocking, if the debugger has sent a Ctrl-C. The other
+    /// bytes of a packet are kept for `read_packet`, and a closed connection
+    /// counts as a request to stop
+    fn interrupt_requested<C: Connection>(&mut self, connection: &mut C) -> io::Result<bool> {
+        let mut byte = [0u8];
+        match connection.read(&mut byte) {
+            Ok(0) => Ok(true),
+            // Outside of a packet only a Ctrl-C and the start of a packet
+            // matter, `read_packet` would skip anything else
+            Ok(_) if self.received.is_empty() && byte[0] == 0x03 => Ok(true),
+            Ok(_) if self.received.is_empty() && byte[0] != b'$' => Ok(false),
+            Ok(_) => {
+                self.received.push_back(byte[0]);
+                Ok(false)
+            }
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Read a byte, starting from the ones received while the program was
+    /// running
+    fn read_byte<C: Connection>(&mut self, connection: &mut C) -> io::Result<Option<u8>> {
+        self.received
+            .pop_front()
+            .map_or_else(|| read_byte(connection), |byte| Ok(Some(byte)))
+    }
+
+    /// Read a packet, skipping the acknowledgements sent by the debugger.
+    /// Returns `None` if the connection has been closed
+    fn read_packet<C: Connection>(&mut self, connection: &mut C) -> io::Result<Option<Packet>> {
+        loop {
+            // Wait for the start of a packet or for a Ctrl-C
+            loop {
+                match self.read_byte(connection)? {
+                    None => return Ok(None),
+                    Some(b'$') => break,
+                    Some(0x03) => return Ok(Some(Packet::Interrupt)),
+                    Some(_) => {}
+                }
+            }
+
+            // Read the packet up to the checksum, removing the escapes
+            let mut data = Vec::new();
+            let mut checksum: u8 = 0;
+            let mut escaped = false;
+            loop {
+                let Some(byte) = self.read_byte(connection)? else {
+                    return Ok(None);
+                };
+                if byte == b'#' {
+                    break;
+                }
+                checksum = checksum.wrapping_add(byte);
+                match (escaped, byte) {
+                    (false, b'}') => escaped = true,
+                    (true, _) => {
+                        data.push(byte ^ 0x20);
+                        escaped = false;
+                    }
+                    (false, _) => data.push(byte),
+                }
+            }
+            let (Some(high), Some(low)) =
+                (self.read_byte(connection)?, self.read_byte(connection)?)
+            else {
+                return Ok(None);
+            };
+            let expected = char::from(high)
+                .to_digit(16)
+                .zip(char::from(low).to_digit(16))
+                .and_then(|(high, low)| u8::try_from(high << 4 | low).ok());
+
+            // Acknowledge the packet, or ask for it to be sent again
+            if self.acknowledge {
+                if expected != Some(checksum) {
+                    connection.write_all(b"-")?;
+                    continue;
+                }
+                connection.write_all(b"+")?;
+            }
+
+            return Ok(Some(Packet::Command(
+                String::from_utf8_lossy(&data).into_owned(),
+            )));
+        }
+    }
+}
+
+fn read_byte<C: Read>(connection: &mut C) -> io::Result<Option<u8>> {
+    let mut byte = [0u8];
+    match connection.read_exact(&mut byte) {
+        Ok(()) => Ok(Some(byte[0])),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn write_packet<C: Write>(connection: &mut C, data: &str) -> io::Result<()> {
+    // Escape the characters that have a special meaning
+    let mut packet = Vec::with_capacity(data.len() + 4);
+    packet.push(b'$');
+    for byte in data.bytes() {
+        if matches!(byte, b'$' | b'#' | b'}' | b'*') {
+            packet.extend([b'}', byte ^ 0x20]);
+        } else {
+            packet.push(byte);
+        }
+    }
+
+    // Append the checksum
+    let checksum = packet[1..]
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+    packet.extend(format!("#{checksum:02x}").bytes());
+
+    connection.write_all(&packet)?;
+    connection.flush()
+}
+
+fn parse_hex<T: TryFrom<u64>>(text: &str) -> Option<T> {
+    u64::from_str_radix(text, 16)
+        .ok()
+        .and_then(|value| T::try_from(value).ok())
+}
+
+/// Parse an `ADDRESS,LENGTH` pair
+fn parse_range(range: &str) -> Option<(u16, u16)> {
+    let (address, length) = range.split_once(',')?;
+    Some((parse_hex(address)?, parse_hex(length)?))
+}
+
+/// Parse a sequence of big-endian words, encoded as 4 hex digits each
+fn parse_words(data: &str) -> Option<Vec<u16>> {
+    data.as_bytes()
+        .chunks(4)
+        .map(|word| {
+            let word = std::str::from_utf8(word)
+                .ok()
+                .filter(|word| word.len() == 4)?;
+            u16::from_str_radix(word, 16).ok()
+        })
+        .collect()
+}
@@ -0,0 +1,247 @@
+use super::*;
+use architectures::{lc2::Lc2, lc3::Lc3};
+use std::{
+    collections::VecDeque,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[allow(clippy::unusual_byte_groupings)]
+const LC2_PROGRAM: [u16; 4] = [
+    0b0001_000_000_1_00001, // x3000: ADD R0, R0, #1
+    0b0011_000_000010000,   // x3001: ST R0, x3010
+    0b0010_001_000010000,   // x3002: LD R1, x3010
+    0b1111_0000_00100101,   // x3003: TRAP x25 (HALT)
+];
+
+/// Scripted GDB client
+struct Client {
+    stream: TcpStream,
+    acknowledge: bool,
+}
+
+impl Client {
+    /// Send a packet and return the reply
+    fn send(&mut self, packet: &str) -> String {
+        self.write(packet);
+        self.read()
+    }
+
+    fn write(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, u8::wrapping_add);
+        write!(self.stream, "${packet}#{checksum:02x}").unwrap();
+    }
+
+    fn read(&mut self) -> String {
+        // Read the acknowledgement and the reply
+        let mut next = || read_byte(&mut self.stream).unwrap().unwrap();
+        if self.acknowledge {
+            assert_eq!(next(), b'+');
+        }
+        assert_eq!(next(), b'$');
+        let reply = std::iter::repeat_with(&mut next)
+            .take_while(|byte| *byte != b'#')
+            .collect();
+        let _checksum = (next(), next());
+        if self.acknowledge {
+            self.stream.write_all(b"+").unwrap();
+        }
+
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+fn session<E, F>(mut cpu: E, program: &[u16], script: F) -> ExitStatus
+where
+    E: Debuggable,
+    F: FnOnce(&mut Client) + Send + 'static,
+{
+    // Set the CPU up and load the program
+    cpu.setup_memory(Arc::new(Mutex::new(VecDeque::new())));
+    for (address, instruction) in (0x3000..).zip(program) {
+        cpu.set_memory(address, *instruction);
+    }
+    cpu.set_entry_point(0x3000);
+
+    // Run the script on another thread
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut client = Client {
+            stream,
+            acknowledge: true,
+        };
+        script(&mut client);
+    });
+
+    // Serve the client
+    let (stream, _) = listener.accept().unwrap();
+    stream.set_nodelay(true).unwrap();
+    let exit_status = GdbStub::new(cpu, None).run(stream).unwrap();
+    client.join().unwrap();
+
+    exit_status
+}
+
+#[test]
+fn registers_and_memory() {
+    let exit_status = session(Lc2::new(0x3000), &LC2_PROGRAM, |client| {
+        assert!(client
+            .send("qSupported:swbreak+")
+            .contains("qXfer:features:read+"));
+        assert_eq!(
+            client.send("g"),
+            "00000000000000000000000000000000300000003003f025"
+        );
+
+        // Registers
+        assert_eq!(client.send("P0=1234"), "OK");
+        assert_eq!(client.send("p0"), "1234");
+        assert_eq!(client.send("p8"), "3000");
+        assert_eq!(client.send("pc"), "E01");
+
+        // Memory
+        assert_eq!(client.send("M4000,4:abcd0001"), "OK");
+        assert_eq!(client.send("m4000,4"), "abcd0001");
+        assert_eq!(client.send("m4000,3"), "abcd00");
+        assert_eq!(client.send("M4000,3:abcd00"), "E01");
+
+        client.write("k");
+    });
+
+    assert_eq!(exit_status, ExitStatus::Interrupted);
+}
+
+#[test]
+fn malformed_packets() {
+    session(Lc2::new(0x3000), &[], |client| {
+        // Packets starting with a character that is not ASCII, or made of
+        // bytes that are not valid UTF-8, are unknown commands
+        assert_eq!(client.send("\u{e8}g"), "");
+        assert_eq!(client.send("m\u{e8}"), "E01");
+        client.stream.write_all(b"$\xff\xfe#fd").unwrap();
+        assert_eq!(client.read(), "");
+
+        // A checksum that is not made of two hex digits is refused
+        client.stream.write_all(b"$g#+f").unwrap();
+        assert_eq!(read_byte(&mut client.stream).unwrap(), Some(b'-'));
+        assert_eq!(client.send("p0"), "0000");
+
+        client.send("D");
+    });
+}
+
+#[test]
+fn target_description() {
+    session(Lc3::new(0x3000), &[], |client| {
+        let description = client.send("qXfer:features:read:target.xml:0,fff");
+        assert!(description.starts_with("l<?xml"));
+        assert!(description.contains("<reg name=\"psr\""));
+
+        // The description can be read in chunks
+        assert_eq!(client.send("qXfer:features:read:target.xml:0,5"), "m<?xml");
+
+        // The register file contains 13 registers
+        assert_eq!(client.send("g").len(), 13 * 4);
+        client.send("D");
+    });
+}
+
+#[test]
+fn breakpoints() {
+    let exit_status = session(Lc2::new(0x3000), &LC2_PROGRAM, |client| {
+        assert_eq!(client.send("Z0,3002,2"), "OK");
+        assert_eq!(client.send("c"), "T05swbreak:;");
+        assert_eq!(client.send("p8"), "3002");
+        assert_eq!(client.send("z0,3002,2"), "OK");
+
+        // Single step
+        assert_eq!(client.send("s"), "S05");
+        assert_eq!(client.send("p8"), "3003");
+        assert_eq!(client.send("p1"), "0001");
+
+        // Run until the HALT
+        assert_eq!(client.send("c"), "W00");
+        assert_eq!(client.send("?"), "W00");
+        client.send("D");
+    });
+
+    assert_eq!(exit_status, ExitStatus::Halted);
+}
+
+#[test]
+fn watchpoints() {
+    session(Lc2::new(0x3000), &LC2_PROGRAM, |client| {
+        assert_eq!(client.send("Z2,3010,2"), "OK");
+        assert_eq!(client.send("Z3,3010,2"), "OK");
+
+        // Write watchpoint
+        assert_eq!(client.send("c"), "T05watch:3010;");
+        assert_eq!(client.send("p8"), "3002");
+
+        // Reading the memory doesn't trigger the watchpoints
+        assert_eq!(client.send("m3010,2"), "0001");
+
+        // Read watchpoint
+        assert_eq!(client.send("c"), "T05rwatch:3010;");
+        assert_eq!(client.send("p8"), "3003");
+
+        // Access watchpoint
+        assert_eq!(client.send("z2,3010,2"), "OK");
+        assert_eq!(client.send("z3,3010,2"), "OK");
+        assert_eq!(client.send("Z4,3010,2"), "OK");
+        assert_eq!(client.send("s3000"), "S05");
+        assert_eq!(client.send("c"), "T05awatch:3010;");
+        assert_eq!(client.send("z4,3010,2"), "OK");
+
+        assert_eq!(client.send("c"), "W00");
+        client.send("D");
+    });
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn interrupt() {
+    let exit_status = session(Lc2::new(0x3000), &[0b0000_111_000000000], |client| {
+        assert_eq!(client.send("QStartNoAckMode"), "OK");
+        client.acknowledge = false;
+
+        // Stop an infinite loop with a Ctrl-C
+        client.write("c");
+        client.stream.write_all(b"\x03").unwrap();
+        assert_eq!(client.read(), "S02");
+        assert_eq!(client.send("p8"), "3000");
+        assert_eq!(client.send("D"), "OK");
+    });
+
+    assert_eq!(exit_status, ExitStatus::Interrupted);
+}
+
+#[test]
+#[allow(clippy::unusual_byte_groupings)]
+fn packet_while_running() {
+    let program = [
+        0b0010_000_000000100,   // x3000: LD R0, x3004
+        0b0001_000_000_1_11111, // x3001: ADD R0, R0, #-1
+        0b0000_001_000000001,   // x3002: BRp x3001
+        0b1111_0000_00100101,   // x3003: TRAP x25 (HALT)
+        0x0800,                 // x3004: .FILL x0800
+    ];
+    let exit_status = session(Lc2::new(0x3000), &program, |client| {
+        assert_eq!(client.send("QStartNoAckMode"), "OK");
+        client.acknowledge = false;
+
+        // A packet sent while the program is running is answered once it
+        // stops
+        client.write("c");
+        client.write("p0");
+        assert_eq!(client.read(), "W00");
+        assert_eq!(client.read(), "0000");
+        assert_eq!(client.send("D"), "OK");
+    });
+
+    assert_eq!(exit_status, ExitStatus::Halted);
+}
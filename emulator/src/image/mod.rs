@@ -0,0 +1,295 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::Write as _,
+    io::{self, ErrorKind, Write},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use crate::debugger::Debuggable;
+
+/// Words placed from an origin
+pub type Block = (u16, Vec<u16>);
+
+/// Number of words in each data record of the Intel HEX files written
+const INTEL_HEX_RECORD_WORDS: usize = 8;
+
+/// Format of a memory image
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub enum ImageFormat {
+    /// Big-endian origin followed by the big-endian words
+    #[default]
+    Object,
+    /// Intel HEX records, whose addresses are word addresses and whose words
+    /// are big-endian
+    IntelHex,
+    /// Text with a word per line as 4 hexadecimal digits, the first line being
+    /// the origin
+    Hex,
+    /// Text with a word per line as 16 binary digits, the first line being
+    /// the origin
+    Binary,
+}
+
+impl ImageFormat {
+    /// Get the format of a file from its extension: `.ihex`, `.ihx` and
+    /// `.i16` are Intel HEX, `.hex` is the LC-3 hexadecimal text, `.bin` is
+    /// the binary text and anything else is an object file
+    #[must_use]
+    pub fn from_extension(file_name: &str) -> Self {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("ihex" | "ihx" | "i16") => Self::IntelHex,
+            Some("hex") => Self::Hex,
+            Some("bin") => Self::Binary,
+            _ => Self::Object,
+        }
+    }
+
+    /// Get the format of the file `file_name` from its extension, telling
+    /// the `.hex` files made of Intel HEX records from the LC-3 ones by their
+    /// content
+    #[must_use]
+    pub fn detect(file_name: &str, bytes: &[u8]) -> Self {
+        match Self::from_extension(file_name) {
+            Self::Hex if bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b':') => {
+                Self::IntelHex
+            }
+            format => format,
+        }
+    }
+
+    /// Parse an image, returning its blocks of words
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the image is not valid or if its
+    /// words don't fit in memory
+    pub fn parse(self, bytes: &[u8]) -> io::Result<Vec<Block>> {
+        let blocks = match self {
+            Self::Object => vec![parse_object(bytes)?],
+            Self::IntelHex => parse_intel_hex(&text(bytes)?)?,
+            Self::Hex => vec![parse_lines(&text(bytes)?, 16, 4)?],
+            Self::Binary => vec![parse_lines(&text(bytes)?, 2, 16)?],
+        };
+
+        if blocks
+            .iter()
+            .any(|(origin, words)| usize::from(*origin) + words.len() > 0x10000)
+        {
+            return Err(invalid("The file is too long"));
+        }
+        Ok(blocks)
+    }
+
+    /// Write an image of `words`, placed from `origin`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write<W: Write>(self, origin: u16, words: &[u16], mut output: W) -> io::Result<()> {
+        match self {
+            Self::Object => {
+                for word in std::iter::once(&origin).chain(words) {
+                    output.write_all(&word.to_be_bytes())?;
+                }
+            }
+            Self::IntelHex => {
+                for (index, chunk) in words.chunks(INTEL_HEX_RECORD_WORDS).enumerate() {
+                    let offset = u16::try_from(index * INTEL_HEX_RECORD_WORDS).unwrap_or(u16::MAX);
+                    let data = chunk
+                        .iter()
+                        .flat_map(|word| word.to_be_bytes())
+                        .collect::<Vec<_>>();
+                    writeln!(
+                        output,
+                        "{}",
+                        intel_hex_record(origin.wrapping_add(offset), 0x00, &data)
+                    )?;
+                }
+                writeln!(output, "{}", intel_hex_record(0, 0x01, &[]))?;
+            }
+            Self::Hex => {
+                for word in std::iter::once(&origin).chain(words) {
+                    writeln!(output, "{word:04X}")?;
+                }
+            }
+            Self::Binary => {
+                for word in std::iter::once(&origin).chain(words) {
+                    writeln!(output, "{word:016b}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Write the image of the memory cells at `addresses` of `cpu`, in `format`
+///
+/// # Errors
+///
+/// This function will return an `Err` if the output can't be written
+pub fn write_memory<E: Debuggable, W: Write>(
+    cpu: &E,
+    addresses: RangeInclusive<u16>,
+    format: ImageFormat,
+    output: W,
+) -> io::Result<()> {
+    let origin = *addresses.start();
+    let words = addresses
+        .map(|address| cpu.peek_memory(address))
+        .collect::<Vec<_>>();
+    format.write(origin, &words, output)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Get the text of an image
+fn text(bytes: &[u8]) -> io::Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| invalid("The file is not valid text"))
+}
+
+/// Parse an object file
+fn parse_object(bytes: &[u8]) -> io::Result<Block> {
+    if bytes.len() < 4 {
+        return Err(invalid(
+            "The file must contain an origin and at least a word",
+        ));
+    }
+    let chunks = bytes.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(invalid("The file must contain whole 16-bit words"));
+    }
+
+    let mut words = chunks.map(|word| u16::from_be_bytes([word[0], word[1]]));
+    let origin = words.next().unwrap_or_default();
+    Ok((origin, words.collect()))
+}
+
+/// Parse a text with a word per line, written with `digits` digits in
+/// `radix`. The empty lines are skipped
+fn parse_lines(source: &str, radix: u32, digits: usize) -> io::Result<Block> {
+    let mut words = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.len() != digits {
+            return Err(invalid(&format!(
+                "Invalid word \"{line}\" at line {}",
+                number + 1
+            )));
+        }
+        words
+            .push(u16::from_str_radix(line, radix).map_err(|_| {
+                invalid(&format!("Invalid word \"{line}\" at line {}", number + 1))
+            })?);
+    }
+
+    if words.len() < 2 {
+        return Err(invalid(
+            "The file must contain an origin and at least a word",
+        ));
+    }
+    let origin = words.remove(0);
+    Ok((origin, words))
+}
+
+/// Parse the records of an Intel HEX file, joining the data records that
+/// follow each other into the same block
+fn parse_intel_hex(source: &str) -> io::Result<Vec<Block>> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: &str| invalid(&format!("{message} at line {}", number + 1));
+
+        // Decode the bytes of the record, and check its length and checksum
+        let bytes = line
+            .strip_prefix(':')
+            .filter(|record| record.len() % 2 == 0)
+            .and_then(|record| {
+                (0..record.len())
+                    .step_by(2)
+                    .map(|index| u8::from_str_radix(record.get(index..index + 2)?, 16).ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| error("Invalid record"))?;
+        if bytes.len() < 5 || bytes.len() != usize::from(bytes[0]) + 5 {
+            return Err(error("Invalid record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err(error("Invalid checksum"));
+        }
+        let address = u16::from_be_bytes([bytes[1], bytes[2]]);
+        let data = &bytes[4..bytes.len() - 1];
+
+        match bytes[3] {
+            // Data
+            0x00 => {
+                let chunks = data.chunks_exact(2);
+                if !chunks.remainder().is_empty() {
+                    return Err(error("Odd number of data bytes"));
+                }
+                if data.is_empty() {
+                    continue;
+                }
+                let words = chunks.map(|word| u16::from_be_bytes([word[0], word[1]]));
+                match blocks.last_mut() {
+                    Some((origin, block))
+                        if usize::from(*origin) + block.len() == usize::from(address) =>
+                    {
+                        block.extend(words);
+                    }
+                    _ => blocks.push((address, words.collect())),
+                }
+            }
+
+            // End of file
+            0x01 => return Ok(blocks),
+
+            // Extended segment and linear addresses, that can only select the
+            // first 64K words
+            0x02 | 0x04 => {
+                if data.iter().any(|byte| *byte != 0) {
+                    return Err(error("Address beyond xFFFF"));
+                }
+            }
+
+            // Start addresses, that don't place any word
+            0x03 | 0x05 => {}
+
+            _ => return Err(error("Unknown record type")),
+        }
+    }
+
+    Err(invalid("Missing end of file record"))
+}
+
+/// Format an Intel HEX record, with its length and checksum
+fn intel_hex_record(address: u16, record_type: u8, data: &[u8]) -> String {
+    let mut bytes = vec![u8::try_from(data.len()).unwrap_or(u8::MAX)];
+    bytes.extend(address.to_be_bytes());
+    bytes.push(record_type);
+    bytes.extend(data);
+    let checksum = bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+    bytes.push(checksum);
+
+    bytes.iter().fold(String::from(":"), |mut record, byte| {
+        let _ = write!(record, "{byte:02X}");
+        record
+    })
+}
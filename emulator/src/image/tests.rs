@@ -0,0 +1,164 @@
+use super::*;
+use architectures::{lc2::Lc2, Architecture};
+
+/// Intel HEX image of 9 words from x3000, that take two data records
+const INTEL_HEX: &str = "\
+:10300000000100020003000400050006000700089C
+:02300800000BBB
+:00000001FF
+";
+
+fn words() -> Vec<u16> {
+    vec![1, 2, 3, 4, 5, 6, 7, 8, 11]
+}
+
+fn error(format: ImageFormat, bytes: &[u8]) -> String {
+    format.parse(bytes).unwrap_err().to_string()
+}
+
+#[test]
+fn formats() {
+    assert_eq!(ImageFormat::from_extension("prog.obj"), ImageFormat::Object);
+    assert_eq!(ImageFormat::from_extension("prog"), ImageFormat::Object);
+    assert_eq!(
+        ImageFormat::from_extension("prog.IHEX"),
+        ImageFormat::IntelHex
+    );
+    assert_eq!(ImageFormat::from_extension("prog.hex"), ImageFormat::Hex);
+    assert_eq!(ImageFormat::from_extension("prog.bin"), ImageFormat::Binary);
+
+    assert_eq!(
+        ImageFormat::detect("prog.hex", INTEL_HEX.as_bytes()),
+        ImageFormat::IntelHex
+    );
+    assert_eq!(
+        ImageFormat::detect("prog.hex", b"3000\n0001\n"),
+        ImageFormat::Hex
+    );
+}
+
+#[test]
+fn object() {
+    assert_eq!(
+        ImageFormat::Object
+            .parse(&[0x30, 0x00, 0xe0, 0x05, 0xf0, 0x25])
+            .unwrap(),
+        [(0x3000, vec![0xe005, 0xf025])]
+    );
+
+    // The last word can be at the end of the memory
+    assert_eq!(
+        ImageFormat::Object
+            .parse(&[0xff, 0xff, 0x12, 0x34])
+            .unwrap(),
+        [(0xffff, vec![0x1234])]
+    );
+
+    assert_eq!(
+        error(ImageFormat::Object, &[0x30, 0x00]),
+        "The file must contain an origin and at least a word"
+    );
+    assert_eq!(
+        error(ImageFormat::Object, &[0x30, 0x00, 0xe0, 0x05, 0xf0]),
+        "The file must contain whole 16-bit words"
+    );
+    assert_eq!(
+        error(ImageFormat::Object, &[0xff, 0xff, 0x12, 0x34, 0x56, 0x78]),
+        "The file is too long"
+    );
+}
+
+#[test]
+fn intel_hex() {
+    assert_eq!(
+        ImageFormat::IntelHex.parse(INTEL_HEX.as_bytes()).unwrap(),
+        [(0x3000, words())]
+    );
+
+    // The records that don't follow each other start a new block, and the
+    // extended addresses can only select the first 64K words
+    let source = ":020000040000FA\n:02400000004876\n:02500000006945\n:00000001FF\n";
+    assert_eq!(
+        ImageFormat::IntelHex.parse(source.as_bytes()).unwrap(),
+        [(0x4000, vec![0x0048]), (0x5000, vec![0x0069])]
+    );
+
+    let error = |source: &str| error(ImageFormat::IntelHex, source.as_bytes());
+    assert_eq!(error("3000\n"), "Invalid record at line 1");
+    assert_eq!(error(":02400000004877\n"), "Invalid checksum at line 1");
+    assert_eq!(
+        error(":03400000004800690C\n"),
+        "Invalid record length at line 1"
+    );
+    assert_eq!(
+        error(":014000000BB4\n"),
+        "Odd number of data bytes at line 1"
+    );
+    assert_eq!(error(":020000040001F9\n"), "Address beyond xFFFF at line 1");
+    assert_eq!(error(":00000006FA\n"), "Unknown record type at line 1");
+    assert_eq!(error(":02400000004876\n"), "Missing end of file record");
+    assert_eq!(
+        error(":04FFFF00004800694D\n:00000001FF\n"),
+        "The file is too long"
+    );
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        ImageFormat::Hex.parse(b"3000\nE005\n\nf025\n").unwrap(),
+        [(0x3000, vec![0xe005, 0xf025])]
+    );
+    assert_eq!(
+        ImageFormat::Binary
+            .parse(b"0011000000000000\r\n1110000000000101\r\n")
+            .unwrap(),
+        [(0x3000, vec![0xe005])]
+    );
+
+    assert_eq!(
+        error(ImageFormat::Hex, b"3000\nE05\n"),
+        "Invalid word \"E05\" at line 2"
+    );
+    assert_eq!(
+        error(ImageFormat::Binary, b"0011000000000000\n111000000000010X\n"),
+        "Invalid word \"111000000000010X\" at line 2"
+    );
+    assert_eq!(
+        error(ImageFormat::Hex, b"3000\n"),
+        "The file must contain an origin and at least a word"
+    );
+}
+
+#[test]
+fn round_trip() {
+    for format in [
+        ImageFormat::Object,
+        ImageFormat::IntelHex,
+        ImageFormat::Hex,
+        ImageFormat::Binary,
+    ] {
+        let mut output = Vec::new();
+        format.write(0x3000, &words(), &mut output).unwrap();
+        assert_eq!(format.parse(&output).unwrap(), [(0x3000, words())]);
+    }
+
+    let mut output = Vec::new();
+    ImageFormat::IntelHex
+        .write(0x3000, &words(), &mut output)
+        .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), INTEL_HEX);
+}
+
+#[test]
+fn memory() {
+    let mut cpu = Lc2::new(0x3000);
+    cpu.load_bytes(0x3000, &[0xe0, 0x05, 0xf0, 0x25]).unwrap();
+
+    let mut output = Vec::new();
+    write_memory(&cpu, 0x3000..=0x3002, ImageFormat::Hex, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "3000\nE005\nF025\n0000\n"
+    );
+}
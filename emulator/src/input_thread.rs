@@ -1,6 +1,7 @@
 use console::Key;
 use std::{
     collections::VecDeque,
+    io::{BufReader, Read},
     sync::{
         mpsc::{self, Sender, TryRecvError},
         Arc, Mutex,
@@ -12,13 +13,15 @@ pub struct InputThread {
     buffer: Arc<Mutex<VecDeque<u8>>>,
     stop_signal: Sender<()>,
     join_handle: Option<thread::JoinHandle<()>>,
+    interactive: bool,
 }
 
 impl InputThread {
+    /// Spawn a thread that reads the keys pressed on the terminal and puts
+    /// them into `buffer`
     #[must_use]
-    pub fn spawn() -> Self {
-        // Create a new buffer and a channel to stop the thread
-        let buffer = Arc::new(Mutex::new(VecDeque::<u8>::new()));
+    pub fn spawn(buffer: Arc<Mutex<VecDeque<u8>>>) -> Self {
+        // Create a channel to stop the thread
         let (stop_tx, stop_rx) = mpsc::channel::<()>();
 
         // Create the thread
@@ -28,10 +31,8 @@ impl InputThread {
             let term = console::Term::stdout();
             let _ = term.hide_cursor();
 
-            loop {
-                // Read stdin without echo or buffering (raw console)
-                let Ok(key) = term.read_key_raw() else { break };
-
+            // Read stdin without echo or buffering (raw console)
+            while let Ok(key) = term.read_key_raw() {
                 // Stop if the channel is broken or if is not empty
                 match stop_rx.try_recv() {
                     Ok(()) | Err(TryRecvError::Disconnected) => break,
@@ -67,6 +68,42 @@ impl InputThread {
             buffer,
             stop_signal: stop_tx,
             join_handle: Some(handle),
+            interactive: true,
+        }
+    }
+
+    /// Spawn a thread that puts all the bytes of `reader` into `buffer`,
+    /// instead of reading them from the terminal
+    #[must_use]
+    pub fn from_reader<R: Read + Send + 'static>(
+        reader: R,
+        buffer: Arc<Mutex<VecDeque<u8>>>,
+    ) -> Self {
+        // Create a channel to stop the thread
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        // Create the thread
+        let buffer_thread = buffer.clone();
+        let handle = thread::spawn(move || {
+            // Put every byte of the reader into the buffer
+            for byte in BufReader::new(reader).bytes() {
+                let (Ok(byte), Ok(mut buffer)) = (byte, buffer_thread.lock()) else {
+                    break;
+                };
+                buffer.push_back(byte);
+            }
+
+            // Wait for the stop signal, so that the thread stays healthy while
+            // the emulator is running
+            let _ = stop_rx.recv();
+        });
+
+        // Return the struct
+        Self {
+            buffer,
+            stop_signal: stop_tx,
+            join_handle: Some(handle),
+            interactive: false,
         }
     }
 
@@ -87,13 +124,15 @@ impl InputThread {
 
 impl Drop for InputThread {
     fn drop(&mut self) {
-        // Print a message
+        // Print a message, if the thread is reading from the terminal
         println!();
-        if self.is_healthy() {
-            // TODO: Send a char to stdin to exit without pressing any keys
-            println!("\nPress any key to continue...");
-        } else {
-            eprintln!("\nCtrl-C detected, closing...");
+        if self.interactive {
+            if self.is_healthy() {
+                // TODO: Send a char to stdin to exit without pressing any keys
+                println!("\nPress any key to continue...");
+            } else {
+                eprintln!("\nCtrl-C detected, closing...");
+            }
         }
 
         // Send the stop signal to the thread
@@ -1,15 +1,18 @@
 use std::{
     collections::VecDeque,
     fs::File,
-    io::{self, BufRead, BufReader, Read, Write},
+    io,
     sync::{Arc, Mutex},
 };
 
 use architectures::{
-    lc2::{Lc2, Register},
-    Architecture, WatcherType,
+    common::SymbolTable,
+    lc2::{Gpr, Instruction, Lc2, Register, Snapshot, MACHINE_CONTROL_REGISTER},
+    Architecture, Error,
 };
 
+use crate::debugger::ControlFlow;
+
 macro_rules! embed_assembly {
     ($trap_routine: expr) => {{
         include_bytes!(concat!(env!("OUT_DIR"), "/lc2/", $trap_routine, ".o"))
@@ -17,106 +20,40 @@ macro_rules! embed_assembly {
 }
 
 impl crate::Emulator for Lc2 {
-    fn emulate(&mut self, input_thread: crate::InputThread) {
-        // Get the input buffer
-        let input_buffer = input_thread.get_buffer();
-
-        // While the CPU is active...
-        while self.get_memory(0xffff) & 0x8000 != 0 {
-            // Step a CPU instruction
-            self.step_instruction();
-
-            // Is the input thread is not healthy, exit
-            if !input_thread.is_healthy() {
-                break;
-            }
-
-            // Get the Keyboard Status Register
-            let keyboard_status_register = self.get_memory(0xf400);
-
-            // If the input buffer is not empty...
-            if let Some(input_byte) = input_buffer.lock().unwrap().front() {
-                let input_byte = u16::from(*input_byte);
-
-                // Set the Keyboard Status Register
-                if keyboard_status_register & 0x8000 == 0 {
-                    self.set_memory(0xf400, keyboard_status_register | 0x8000);
-                }
-
-                // Set the Keyboard Data Register
-                self.set_memory(0xf401, input_byte);
-            }
-            // Else unset the Keyboard Status Register
-            else if keyboard_status_register & 0x8000 != 0 {
-                self.set_memory(0xf400, keyboard_status_register & 0x7fff);
-            }
-        }
+    const INVALID_TRAP_ROUTINE: u16 = 0xfd00;
+
+    fn is_running(&self) -> bool {
+        self.peek_memory(MACHINE_CONTROL_REGISTER) & 0x8000 != 0
+    }
+
+    fn save_snapshot(&self, file_name: &str) -> io::Result<()> {
+        self.snapshot().write_to(File::create(file_name)?)
     }
 
-    fn load_binary(&mut self, file_name: &str) -> io::Result<()> {
-        // Open the file in a BufReader
-        let mut reader = BufReader::new(File::open(file_name)?);
-
-        // Get the start address from the first two bytes of the binary
-        let mut start_address = [0u8; 2];
-        start_address.clone_from_slice(reader.fill_buf()?.get(..2).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "File must be at least 2 bytes long",
-            )
-        })?);
-        reader.consume(2);
-        let start_address = u16::from_be_bytes(start_address);
-
-        // Set the Program Counter to `start_address`
-        self.set_register(&Register::ProgramCounter, start_address);
-
-        // Put the rest of the binary in memory starting from `start_address`
-        self.load_bytes(
-            start_address,
-            &reader.bytes().collect::<Result<Vec<u8>, _>>()?,
-        )
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "The file is too long"))?;
+    fn load_snapshot(&mut self, file_name: &str) -> io::Result<()> {
+        let snapshot = Snapshot::read_from(File::open(file_name)?)?;
+        self.restore(&snapshot)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
 
-        Ok(())
+    fn get_program_counter(&self) -> u16 {
+        self.get_register(&Register::ProgramCounter)
+    }
+
+    fn set_program_counter(&mut self, address: u16) {
+        self.set_register(&Register::ProgramCounter, address)
+            .expect("The Program Counter can hold any address");
+    }
+
+    fn set_entry_point(&mut self, address: u16) {
+        self.set_program_counter(address);
     }
 
     #[allow(clippy::too_many_lines)]
     fn setup_memory(&mut self, input_buffer: Arc<Mutex<VecDeque<u8>>>) {
-        // Set the Video Status Register and the Machine Control Register
-        self.set_memory(0xf3fc, 0x8000);
-        self.set_memory(0xffff, 0x8000);
-
-        // Add the memory watcher for the Video Data Register
-        self.add_memory_watcher(0xf3ff, WatcherType::OnWrite, |data: u16| {
-            // Print the character to stdout
-            print!(
-                "{}",
-                char::from_u32(u32::from(data) & 0xff)
-                    .expect("Character is not convertible to UTF-8")
-            );
-
-            // If the data contains another character (packed string), print it to
-            // stdout
-            if data & 0xff00 != 0 {
-                print!(
-                    "{}",
-                    char::from_u32(u32::from(data) >> 8)
-                        .expect("Character is not convertible to UTF-8")
-                );
-            }
-
-            // Flush the stdout buffer
-            io::stdout()
-                .flush()
-                .expect("Couldn't flush the stdout buffer");
-        });
-
-        // If the Keyboard Data Register is read, remove the first byte in the
-        // input buffer
-        self.add_memory_watcher(0xf401, WatcherType::OnRead, move |_| {
-            input_buffer.lock().unwrap().pop_front();
-        });
+        // Map the keyboard, the display, the timer and the Machine Control
+        // Register
+        self.add_default_devices(input_buffer, Box::new(io::stdout()));
 
         // ================================= Trap ==================================
 
@@ -165,3 +102,64 @@ impl crate::Emulator for Lc2 {
             .expect("Couldn't put trap subroutine at address 0xfd00");
     }
 }
+
+impl crate::debugger::Debuggable for Lc2 {
+    const TARGET_DESCRIPTION: &'static str = include_str!("target.xml");
+    const OPCODES: [&'static str; 16] = architectures::lc2::OPCODES;
+
+    fn disassemble(word: u16, address: u16, symbols: Option<&SymbolTable>) -> String {
+        architectures::lc2::disassemble(word, address, symbols).text
+    }
+
+    fn registers() -> Vec<(&'static str, Register)> {
+        vec![
+            ("R0", Register::Gpr(Gpr::R0)),
+            ("R1", Register::Gpr(Gpr::R1)),
+            ("R2", Register::Gpr(Gpr::R2)),
+            ("R3", Register::Gpr(Gpr::R3)),
+            ("R4", Register::Gpr(Gpr::R4)),
+            ("R5", Register::Gpr(Gpr::R5)),
+            ("R6", Register::Gpr(Gpr::R6)),
+            ("R7", Register::Gpr(Gpr::R7)),
+            ("PC", Register::ProgramCounter),
+            ("IR", Register::InstructionRegister),
+            ("MAR", Register::MemoryAddressRegister),
+            ("MDR", Register::MemoryDataRegister),
+        ]
+    }
+
+    fn control_flow(instruction: u16) -> ControlFlow {
+        match Instruction::decode(instruction) {
+            Instruction::Jsr { link: true, .. }
+            | Instruction::Jsrr { link: true, .. }
+            | Instruction::Trap { .. } => ControlFlow::Call,
+            Instruction::Ret | Instruction::Rti => ControlFlow::Return,
+            _ => ControlFlow::Other,
+        }
+    }
+
+    fn poke_memory(&mut self, address: u16, data: u16) {
+        // Save the Memory Address Register and the Memory Data Register
+        let memory_address_register = self.get_register(&Register::MemoryAddressRegister);
+        let memory_data_register = self.get_register(&Register::MemoryDataRegister);
+
+        self.set_memory(address, data);
+
+        // Restore the Memory Address Register and the Memory Data Register
+        self.set_register(&Register::MemoryAddressRegister, memory_address_register)
+            .and_then(|()| self.set_register(&Register::MemoryDataRegister, memory_data_register))
+            .expect("The Memory Address Register and the Memory Data Register can hold any value");
+    }
+
+    fn poke_register(&mut self, register: &Register, data: u16) -> Result<(), Error> {
+        // Writing a general purpose register updates the condition code, so
+        // save it and restore it afterwards
+        let condition_code = self.get_condition_code();
+        self.set_register(register, data)?;
+        if let Register::Gpr(_) = register {
+            self.set_condition_code(&condition_code);
+        }
+
+        Ok(())
+    }
+}
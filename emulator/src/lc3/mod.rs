@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use architectures::{
+    common::SymbolTable,
+    lc3::{Gpr, Instruction, Lc3, Register, Snapshot, MACHINE_CONTROL_REGISTER},
+    Architecture, Error,
+};
+
+use crate::debugger::ControlFlow;
+
+macro_rules! embed_assembly {
+    ($trap_routine: expr) => {{
+        include_bytes!(concat!(env!("OUT_DIR"), "/lc3/", $trap_routine, ".o"))
+    }};
+}
+
+impl crate::Emulator for Lc3 {
+    const INVALID_TRAP_ROUTINE: u16 = 0x0580;
+
+    fn is_running(&self) -> bool {
+        self.peek_memory(MACHINE_CONTROL_REGISTER) & 0x8000 != 0
+    }
+
+    fn save_snapshot(&self, file_name: &str) -> io::Result<()> {
+        self.snapshot().write_to(File::create(file_name)?)
+    }
+
+    fn load_snapshot(&mut self, file_name: &str) -> io::Result<()> {
+        let snapshot = Snapshot::read_from(File::open(file_name)?)?;
+        self.restore(&snapshot)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn get_program_counter(&self) -> u16 {
+        self.get_register(&Register::ProgramCounter)
+    }
+
+    fn set_program_counter(&mut self, address: u16) {
+        self.set_register(&Register::ProgramCounter, address)
+            .expect("The Program Counter can hold any address");
+    }
+
+    fn set_entry_point(&mut self, address: u16) {
+        // Save the address of the user program where the boot routine expects
+        // it, and start the boot routine in "Supervisor" privilege mode
+        self.set_memory(0x0200, address);
+        self.set_register(&Register::ProcessorStatusRegister, 0x0002)
+            .expect("The Processor Status Register should have a valid Condition Code");
+        self.set_program_counter(0x0201);
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn setup_memory(&mut self, input_buffer: Arc<Mutex<VecDeque<u8>>>) {
+        // Map the keyboard, the display, the timer and the Machine Control
+        // Register
+        self.add_default_devices(input_buffer, Box::new(io::stdout()));
+
+        // ================================= Boot ==================================
+
+        // Boot routine
+        self.load_bytes(0x0200, embed_assembly!("boot.asm"))
+            .expect("Couldn't put boot routine at address 0x0200");
+
+        // ================================= Trap ==================================
+
+        // Setup the trap vector table
+        for address in 0x0000..=0x00ff {
+            self.set_memory(
+                address,
+                match address {
+                    0x20 => 0x0400, // GETC
+                    0x21 => 0x0430, // OUT
+                    0x22 => 0x0450, // PUTS
+                    0x23 => 0x04a0, // IN
+                    0x24 => 0x0500, // PUTSP
+                    0x25 => 0x0540, // HALT
+                    _ => 0x0580,    // Invalid trap
+                },
+            );
+        }
+
+        // GETC syscall
+        self.load_bytes(0x0400, embed_assembly!("getc.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0400");
+
+        // OUT syscall
+        self.load_bytes(0x0430, embed_assembly!("out.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0430");
+
+        // PUTs Syscall
+        self.load_bytes(0x0450, embed_assembly!("puts.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0450");
+
+        // IN syscall
+        self.load_bytes(0x04a0, embed_assembly!("in.asm"))
+            .expect("Couldn't put trap subroutine at address 0x04a0");
+
+        // PUTSP syscall
+        self.load_bytes(0x0500, embed_assembly!("putsp.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0500");
+
+        // HALT syscall
+        self.load_bytes(0x0540, embed_assembly!("halt.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0540");
+
+        // Invalid trap syscall
+        self.load_bytes(0x0580, embed_assembly!("invalid.asm"))
+            .expect("Couldn't put trap subroutine at address 0x0580");
+
+        // =============================== Interrupt ===============================
+
+        // Setup the interrupt vector table
+        for address in 0x0100..=0x01ff {
+            self.set_memory(
+                address,
+                match address {
+                    0x0100 => 0x0600, // Privilege Mode Violation
+                    0x0101 => 0x0680, // Illegal Opcode
+                    0x0102 => 0x0700, // Access Control Violation
+                    _ => 0x0780,      // Unexpected interrupt
+                },
+            );
+        }
+
+        // Privilege Mode Violation exception
+        self.load_bytes(0x0600, embed_assembly!("privilege_mode_violation.asm"))
+            .expect("Couldn't put exception routine at address 0x0600");
+
+        // Illegal Opcode exception
+        self.load_bytes(0x0680, embed_assembly!("illegal_opcode.asm"))
+            .expect("Couldn't put exception routine at address 0x0680");
+
+        // Access Control Violation exception
+        self.load_bytes(0x0700, embed_assembly!("access_control_violation.asm"))
+            .expect("Couldn't put exception routine at address 0x0700");
+
+        // Unexpected interrupt
+        self.load_bytes(0x0780, embed_assembly!("unexpected_interrupt.asm"))
+            .expect("Couldn't put interrupt routine at address 0x0780");
+    }
+}
+
+impl crate::debugger::Debuggable for Lc3 {
+    const TARGET_DESCRIPTION: &'static str = include_str!("target.xml");
+    const OPCODES: [&'static str; 16] = architectures::lc3::OPCODES;
+
+    fn disassemble(word: u16, address: u16, symbols: Option<&SymbolTable>) -> String {
+        architectures::lc3::disassemble(word, address, symbols).text
+    }
+
+    fn registers() -> Vec<(&'static str, Register)> {
+        vec![
+            ("R0", Register::Gpr(Gpr::R0)),
+            ("R1", Register::Gpr(Gpr::R1)),
+            ("R2", Register::Gpr(Gpr::R2)),
+            ("R3", Register::Gpr(Gpr::R3)),
+            ("R4", Register::Gpr(Gpr::R4)),
+            ("R5", Register::Gpr(Gpr::R5)),
+            ("R6", Register::Gpr(Gpr::R6)),
+            ("R7", Register::Gpr(Gpr::R7)),
+            ("PC", Register::ProgramCounter),
+            ("IR", Register::InstructionRegister),
+            ("PSR", Register::ProcessorStatusRegister),
+            ("MAR", Register::MemoryAddressRegister),
+            ("MDR", Register::MemoryDataRegister),
+        ]
+    }
+
+    fn control_flow(instruction: u16) -> ControlFlow {
+        match Instruction::decode(instruction) {
+            Instruction::Jsr { .. } | Instruction::Jsrr { .. } | Instruction::Trap { .. } => {
+                ControlFlow::Call
+            }
+            // RET is JMP R7
+            Instruction::Jmp { base: Gpr::R7 } | Instruction::Rti => ControlFlow::Return,
+            _ => ControlFlow::Other,
+        }
+    }
+
+    fn poke_memory(&mut self, address: u16, data: u16) {
+        // Save the Memory Address Register and the Memory Data Register
+        let memory_address_register = self.get_register(&Register::MemoryAddressRegister);
+        let memory_data_register = self.get_register(&Register::MemoryDataRegister);
+
+        self.set_memory(address, data);
+
+        // Restore the Memory Address Register and the Memory Data Register
+        self.set_register(&Register::MemoryAddressRegister, memory_address_register)
+            .and_then(|()| self.set_register(&Register::MemoryDataRegister, memory_data_register))
+            .expect("The Memory Address Register and the Memory Data Register can hold any value");
+    }
+
+    fn poke_register(&mut self, register: &Register, data: u16) -> Result<(), Error> {
+        // Writing a general purpose register updates the condition code, so
+        // save it and restore it afterwards
+        let condition_code = self.get_condition_code();
+        self.set_register(register, data)?;
+        if let Register::Gpr(_) = register {
+            self.set_condition_code(&condition_code);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+use crate::Emulator;
+use architectures::{
+    devices::Display,
+    lc3::{DISPLAY_DATA_REGISTER, DISPLAY_STATUS_REGISTER},
+};
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+/// Output of the display shared with the test
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn textbook_echo() {
+    // Keyboard echo from the chapter 9 of Patt and Patel, "Introduction to
+    // Computing Systems" (3rd edition), which polls the Keyboard Status
+    // Register and the Display Status Register directly, with a HALT as its
+    // next task
+    let program = [
+        0xa206, // x3000: START     LDI R1, A
+        0x07fe, // x3001:           BRzp START
+        0xa005, // x3002:           LDI R0, B
+        0xa205, // x3003: ECHO      LDI R1, C
+        0x07fe, // x3004:           BRzp ECHO
+        0xb004, // x3005:           STI R0, D
+        0x0e04, // x3006:           BRnzp NEXT_TASK
+        0xfe00, // x3007: A         .FILL xFE00
+        0xfe02, // x3008: B         .FILL xFE02
+        0xfe04, // x3009: C         .FILL xFE04
+        0xfe06, // x300A: D         .FILL xFE06
+        0xf025, // x300B: NEXT_TASK HALT
+    ];
+
+    // Set the CPU up, capturing the output of the display
+    let mut cpu = Lc3::new(0x3000);
+    cpu.setup_memory(Arc::new(Mutex::new(VecDeque::from(b"a".to_vec()))));
+    let output = Rc::new(RefCell::new(Vec::new()));
+    cpu.add_device(
+        DISPLAY_STATUS_REGISTER..=DISPLAY_DATA_REGISTER,
+        Box::new(Display::new(
+            DISPLAY_STATUS_REGISTER,
+            DISPLAY_DATA_REGISTER,
+            false,
+            Box::new(Output(output.clone())),
+        )),
+    );
+    for (address, word) in (0x3000..).zip(program) {
+        cpu.set_memory(address, word);
+    }
+    cpu.set_entry_point(0x3000);
+
+    // Assert that the character is echoed before halting
+    for _ in 0..1000 {
+        if !cpu.is_running() {
+            break;
+        }
+        cpu.step_instruction().unwrap();
+    }
+    assert!(!cpu.is_running());
+    assert_eq!(
+        output.borrow().as_slice(),
+        b"a\nHalting the processor...".as_slice()
+    );
+}
@@ -0,0 +1,28 @@
+; BOOT: Start the user program in "Supervisor"
+; privilege mode, so that it can poll the devices
+; like the programs of the textbook do
+.orig 0x0200
+
+; Address of the first instruction of the user
+; program, set by the emulator before booting
+user_program_counter: .fill 0x3000
+
+; Load the Supervisor Stack Pointer
+LD R6, supervisor_stack_pointer
+
+; Push the Processor Status Register ("Supervisor"
+; mode, priority 0) and the address of the user
+; program on the supervisor stack
+LD R0, user_processor_status_register
+ADD R6, R6, -1
+STR R0, R6, 0
+LD R0, user_program_counter
+ADD R6, R6, -1
+STR R0, R6, 0
+
+; Clear R0 and jump to the user program
+AND R0, R0, 0
+RTI
+
+supervisor_stack_pointer: .fill 0x3000
+user_processor_status_register: .fill 0x0002
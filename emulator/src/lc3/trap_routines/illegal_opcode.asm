@@ -0,0 +1,19 @@
+.orig 0x0680
+
+; Save the state of R0
+ST R0, save_r0
+
+; Print the error banner
+LEA R0, banner
+PUTS
+
+; Halt the processor
+HALT
+
+; Restore the state of R0 and return
+LD R0, save_r0
+RTI
+
+save_r0: .fill 0x0000
+
+banner: .stringz "\nAn Illegal Opcode exception was raised!"
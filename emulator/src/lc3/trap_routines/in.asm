@@ -0,0 +1,30 @@
+; IN: Get a character with a prompt
+.orig 0x04a0
+
+; Save the status of the R1 register
+ST R1, save_r1
+
+; Print the prompt
+LEA R0, prompt
+PUTS
+
+; Get a new character, save it into R1
+; and print it back to the console
+GETC
+ADD R1, R0, 0
+OUT
+
+; Print a newline
+LD R0, line_feed
+OUT
+
+; Restore the status of R0 and R1
+; and return
+ADD R0, R1, 0
+LD R1, save_r1
+RTI
+
+save_r1: .fill 0x0000
+
+line_feed: .fill 0x000a
+prompt: .stringz "Input a character: "
@@ -0,0 +1,35 @@
+; PUTS: Print the string pointed by R0 to screen
+.orig 0x0450
+
+; R0 -> Char to print
+; R1 -> Pointer to char
+
+; Save the state of the R0 and R1
+; registers
+ST R0, save_r0
+ST R1, save_r1
+
+; Save the pointer to the string into R1
+ADD R1, R0, 0
+
+print_loop:
+  ; Load a char from the string, terminating
+  ; the loop if it's a null
+  LDR R0, R1, 0
+  BRz stop_printing
+
+  ; Print the char to screen, increment the
+  ; pointer and loop again
+  OUT
+  ADD R1, R1, 1
+  BR print_loop
+
+; Restore the state of the R0 and R1
+; registers and return
+stop_printing:
+  LD R0, save_r0
+  LD R1, save_r1
+  RTI
+
+save_r0: .fill 0x0000
+save_r1: .fill 0x0000
@@ -0,0 +1,72 @@
+; PUTSP: Print the packed string pointed by R0 to screen
+.orig 0x0500
+
+; R0 -> Char to print
+; R1 -> Pointer to char
+; R2 -> Mask
+; R3 -> Rotation counter
+
+; Save the state of the R0, R1, R2
+; and R3 registers
+ST R0, save_r0
+ST R1, save_r1
+ST R2, save_r2
+ST R3, save_r3
+
+; Save the pointer to the string into R1
+; and the mask into R2
+ADD R1, R0, 0
+LD R2, mask
+
+print_loop:
+  ; Load the low 8 bits of the word and
+  ; break out of the loop if they're null
+  LDR R0, R1, 0
+  AND R0, R0, R2
+  BRz end_print_loop
+
+  ; Print the first character
+  OUT
+
+  ; Rotate the word left by 8 bits to move
+  ; the high byte into the low 8 bits
+  LDR R0, R1, 0
+  AND R3, R3, 0
+  ADD R3, R3, 8
+  rotate_loop:
+    ADD R0, R0, 0
+    BRzp shift_left
+    ADD R0, R0, R0
+    ADD R0, R0, 1
+    BR next_rotation
+    shift_left:
+      ADD R0, R0, R0
+    next_rotation:
+      ADD R3, R3, -1
+      BRp rotate_loop
+
+  ; Break out of the loop if the second
+  ; character is null, else print it
+  AND R0, R0, R2
+  BRz end_print_loop
+  OUT
+
+  ; Increment the pointer and loop again
+  ADD R1, R1, 1
+  BR print_loop
+
+; Restore the state of the R0, R1, R2 and
+; R3 registers and return
+end_print_loop:
+  LD R0, save_r0
+  LD R1, save_r1
+  LD R2, save_r2
+  LD R3, save_r3
+  RTI
+
+save_r0: .fill 0x0000
+save_r1: .fill 0x0000
+save_r2: .fill 0x0000
+save_r3: .fill 0x0000
+
+mask: .fill 0x00ff
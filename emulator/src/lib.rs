@@ -1,20 +1,122 @@
 use std::{
     collections::VecDeque,
+    io,
     sync::{Arc, Mutex},
 };
 
+pub mod assembly;
+pub mod cli;
+pub mod coverage;
+pub mod debugger;
+pub mod gdb;
+pub mod image;
+pub mod loader;
+pub mod profile;
+pub mod statistics;
+pub mod trace;
+
 mod lc2;
+mod lc3;
 
 mod input_thread;
 pub use input_thread::InputThread;
 
+/// Reason why the emulation has stopped
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum ExitStatus {
+    Halted,
+    InstructionLimit,
+    LoadError,
+    InvalidTrap,
+    Interrupted,
+    /// The processor has reached a state in which it can't execute
+    Fault,
+}
+
+impl ExitStatus {
+    /// Get the exit code of the emulator process
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Halted => 0,
+            Self::InstructionLimit => 2,
+            Self::LoadError => 3,
+            Self::InvalidTrap => 4,
+            Self::Fault => 5,
+            Self::Interrupted => 130,
+        }
+    }
+}
+
 pub trait Emulator: architectures::Architecture {
-    fn emulate(&mut self, input_thread: InputThread);
+    /// Address of the routine called by the traps with an invalid vector
+    const INVALID_TRAP_ROUTINE: u16;
+
+    /// Run the program until the processor is halted, the input thread is
+    /// stopped or `max_instructions` instructions are executed, calling
+    /// `after_step` after each instruction
+    fn emulate<F: FnMut(&Self)>(
+        &mut self,
+        input_thread: InputThread,
+        max_instructions: Option<u64>,
+        mut after_step: F,
+    ) -> ExitStatus {
+        let mut exit_status = ExitStatus::Halted;
+        let mut executed_instructions: u64 = 0;
+
+        // While the CPU is active...
+        while self.is_running() {
+            // Stop if the maximum number of instructions has been executed
+            if max_instructions.is_some_and(|max| executed_instructions >= max) {
+                return ExitStatus::InstructionLimit;
+            }
+
+            // Step a CPU instruction, stopping if it can't be executed
+            if let Err(error) = self.step_instruction() {
+                eprintln!("Error: {error}");
+                return ExitStatus::Fault;
+            }
+            executed_instructions += 1;
+            after_step(self);
+
+            // If the invalid trap routine has been called, remember it
+            if self.get_program_counter() == Self::INVALID_TRAP_ROUTINE {
+                exit_status = ExitStatus::InvalidTrap;
+            }
+
+            // If the input thread is not healthy, exit
+            if !input_thread.is_healthy() {
+                return ExitStatus::Interrupted;
+            }
+        }
 
+        exit_status
+    }
+
+    /// Check the Machine Control Register to see if the processor is running,
+    /// peeking it so that its watchers and its device aren't triggered
+    fn is_running(&self) -> bool;
+
+    /// Save the state of the machine to a snapshot file
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the file can't be written
+    fn save_snapshot(&self, file_name: &str) -> io::Result<()>;
+
+    /// Restore the state of the machine from a snapshot file
+    ///
     /// # Errors
     ///
-    /// This method will return an `Err` if there is an error with the file, or
-    /// if the binary is too short or too long
-    fn load_binary(&mut self, file_name: &str) -> std::io::Result<()>;
+    /// This method will return an `Err` if the file can't be read, or if it
+    /// doesn't contain a valid snapshot of this architecture
+    fn load_snapshot(&mut self, file_name: &str) -> io::Result<()>;
+
+    fn get_program_counter(&self) -> u16;
+    fn set_program_counter(&mut self, address: u16);
+
+    /// Prepare the processor to run the program starting from `address`
+    fn set_entry_point(&mut self, address: u16);
+
     fn setup_memory(&mut self, input_buffer: Arc<Mutex<VecDeque<u8>>>);
 }
@@ -0,0 +1,220 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use architectures::{common::SymbolTable, Error};
+
+use crate::{
+    assembly::assemble, cli::Isa, coverage::LineTable, debugger::Debuggable, image::ImageFormat,
+};
+
+/// Block of words of an object file, placed from its origin
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Segment {
+    /// Name of the object file
+    pub file: String,
+    pub origin: u16,
+    pub words: Vec<u16>,
+}
+
+impl Segment {
+    /// Get the addresses occupied by the segment
+    #[must_use]
+    pub fn addresses(&self) -> RangeInclusive<u16> {
+        let end = usize::from(self.origin) + self.words.len().max(1) - 1;
+        self.origin..=u16::try_from(end).unwrap_or(u16::MAX)
+    }
+}
+
+/// Parse a symbol file written by the LC-3 assembler, whose table has a line
+/// per label with its name and its hexadecimal address:
+///
+/// ```text
+/// // Symbol table
+/// // Scope level 0:
+/// //  Symbol Name       Page Address
+/// //  ----------------  ------------
+/// //  LOOP              3002
+/// ```
+///
+/// # Errors
+///
+/// This function will return an `Err` if a label of the table doesn't have a
+/// valid address
+pub fn parse_symbols(source: &str) -> io::Result<SymbolTable> {
+    let mut symbols = SymbolTable::new();
+    let mut in_table = false;
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim_start().strip_prefix("//").unwrap_or(line);
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+        // The labels follow the separator under the titles of the table
+        let [name, address] = tokens[..] else {
+            continue;
+        };
+        if name.chars().all(|character| character == '-') {
+            in_table = true;
+            continue;
+        }
+        if !in_table {
+            continue;
+        }
+
+        let address = address.strip_prefix(['x', 'X']).unwrap_or(address);
+        let address = u16::from_str_radix(address, 16).map_err(|_| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid address of \"{name}\" at line {}", number + 1),
+            )
+        })?;
+        symbols.insert(address, name.to_owned());
+    }
+
+    Ok(symbols)
+}
+
+/// Object files and assembly sources to load into memory, with their labels
+/// and the source lines of the assembled words
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct Program {
+    pub segments: Vec<Segment>,
+    pub symbols: SymbolTable,
+    pub lines: LineTable,
+    /// Architecture the assembly sources are assembled for
+    architecture: Isa,
+}
+
+impl Program {
+    /// Create an empty program, whose assembly sources are assembled for
+    /// `architecture`
+    #[must_use]
+    pub fn new(architecture: Isa) -> Self {
+        Self {
+            architecture,
+            ..Default::default()
+        }
+    }
+
+    /// Add `segment` to the program
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the segment overlaps one of the
+    /// segments already added
+    pub fn add_segment(&mut self, segment: Segment) -> io::Result<()> {
+        let addresses = segment.addresses();
+        if let Some(other) = self.segments.iter().find(|other| {
+            let other = other.addresses();
+            addresses.start() <= other.end() && other.start() <= addresses.end()
+        }) {
+            let other_addresses = other.addresses();
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "x{:04X}-x{:04X} overlaps x{:04X}-x{:04X} of \"{}\"",
+                    addresses.start(),
+                    addresses.end(),
+                    other_addresses.start(),
+                    other_addresses.end(),
+                    other.file
+                ),
+            ));
+        }
+
+        self.segments.push(segment);
+        Ok(())
+    }
+
+    /// Add the blocks of the object file `file_name` to the program, along
+    /// with the labels of the symbol file with the same name and the `.sym`
+    /// extension, if there is one. The format of the object file is detected
+    /// from its extension and its content, and the files ending in `.asm` are
+    /// assembled
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if one of the files can't be read or
+    /// is invalid, or if the object file overlaps the ones already added. The
+    /// errors in an assembly source are `SourceError`s
+    pub fn add_object(&mut self, file_name: &str) -> io::Result<()> {
+        if Path::new(file_name)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("asm"))
+        {
+            return self.add_source(file_name);
+        }
+
+        let bytes = fs::read(file_name)?;
+        for (origin, words) in ImageFormat::detect(file_name, &bytes).parse(&bytes)? {
+            self.add_segment(Segment {
+                file: file_name.to_owned(),
+                origin,
+                words,
+            })?;
+        }
+
+        let symbol_file = Path::new(file_name).with_extension("sym");
+        match fs::read_to_string(&symbol_file) {
+            Ok(source) => {
+                let symbols = parse_symbols(&source).map_err(|error| {
+                    io::Error::new(error.kind(), format!("{}: {error}", symbol_file.display()))
+                })?;
+                self.symbols.extend(symbols);
+                Ok(())
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Assemble the source `file_name` and add it to the program, with its
+    /// labels and source lines
+    fn add_source(&mut self, file_name: &str) -> io::Result<()> {
+        let source = fs::read_to_string(file_name)?;
+        let assembly = assemble(file_name, &source, self.architecture)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+        for (origin, words) in assembly.blocks {
+            self.add_segment(Segment {
+                file: file_name.to_owned(),
+                origin,
+                words,
+            })?;
+        }
+        self.symbols.extend(assembly.symbols);
+        self.lines.extend(assembly.lines);
+        Ok(())
+    }
+
+    /// Get the object files of the program, with the addresses they occupy
+    #[must_use]
+    pub fn files(&self) -> Vec<(String, RangeInclusive<u16>)> {
+        self.segments
+            .iter()
+            .map(|segment| (segment.file.clone(), segment.addresses()))
+            .collect()
+    }
+
+    /// Write the segments into the memory of `cpu`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if a segment doesn't fit in memory
+    pub fn load_into<E: Debuggable>(&self, cpu: &mut E) -> Result<(), Error> {
+        for segment in &self.segments {
+            let bytes = segment
+                .words
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<_>>();
+            cpu.load_bytes(segment.origin, &bytes)?;
+        }
+
+        Ok(())
+    }
+}
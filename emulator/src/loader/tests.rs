@@ -0,0 +1,166 @@
+use super::*;
+use crate::assembly::SourceError;
+use architectures::{lc3::Lc3, Architecture};
+
+/// Symbol file written by the LC-3 assembler
+const SYMBOLS: &str = "\
+// Symbol table
+// Scope level 0:
+//\tSymbol Name       Page Address
+//\t----------------  ------------
+//\tMAIN              3000
+//\tLOOP              3002
+
+";
+
+fn segment(file: &str, origin: u16, length: u16) -> Segment {
+    Segment {
+        file: file.to_owned(),
+        origin,
+        words: vec![0; usize::from(length)],
+    }
+}
+
+#[test]
+fn addresses() {
+    assert_eq!(segment("prog.obj", 0x3000, 2).addresses(), 0x3000..=0x3001);
+
+    // The last word can be at the end of the memory
+    assert_eq!(segment("end.obj", 0xffff, 1).addresses(), 0xffff..=0xffff);
+}
+
+#[test]
+fn symbols() {
+    assert_eq!(
+        parse_symbols(SYMBOLS).unwrap(),
+        SymbolTable::from([(0x3000, "MAIN".to_owned()), (0x3002, "LOOP".to_owned())])
+    );
+    assert_eq!(
+        parse_symbols("//\t----  ----\n//\tMAIN  x3000\n//\tLOOP  30O2\n")
+            .unwrap_err()
+            .to_string(),
+        "Invalid address of \"LOOP\" at line 3"
+    );
+}
+
+#[test]
+fn overlapping_segments() {
+    let mut program = Program::default();
+    program.add_segment(segment("a.obj", 0x3000, 0x10)).unwrap();
+
+    // The segments can be next to each other, but they can't overlap
+    program.add_segment(segment("b.obj", 0x3010, 0x10)).unwrap();
+    program.add_segment(segment("c.obj", 0x2ff0, 0x10)).unwrap();
+    assert_eq!(
+        program
+            .add_segment(segment("d.obj", 0x301f, 2))
+            .unwrap_err()
+            .to_string(),
+        "x301F-x3020 overlaps x3010-x301F of \"b.obj\""
+    );
+    assert_eq!(
+        program.files(),
+        [
+            ("a.obj".to_owned(), 0x3000..=0x300f),
+            ("b.obj".to_owned(), 0x3010..=0x301f),
+            ("c.obj".to_owned(), 0x2ff0..=0x2fff),
+        ]
+    );
+}
+
+#[test]
+fn object_files() {
+    let directory = std::env::temp_dir().join(format!("loader-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file = |name: &str| directory.join(name).to_str().unwrap().to_owned();
+    fs::write(file("main.obj"), [0x30, 0x00, 0xe0, 0x05, 0xf0, 0x25]).unwrap();
+    fs::write(file("main.sym"), SYMBOLS).unwrap();
+    fs::write(file("data.obj"), [0x40, 0x00, 0x00, 0x48]).unwrap();
+    fs::write(file("bad.obj"), [0x40, 0x00, 0x00, 0x48]).unwrap();
+    fs::write(file("bad.sym"), "//\t----  ----\n//\tDATA  4OOO\n").unwrap();
+
+    // The symbol file is optional
+    let mut program = Program::default();
+    program.add_object(&file("main.obj")).unwrap();
+    program.add_object(&file("data.obj")).unwrap();
+    let bad = program.add_object(&file("bad.obj"));
+    let missing = Program::default().add_object(&file("missing.obj"));
+    let bad_symbols = Program::default().add_object(&file("bad.obj"));
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(
+        program.symbols.get(&0x3002).map(String::as_str),
+        Some("LOOP")
+    );
+    assert!(bad.unwrap_err().to_string().contains("overlaps"));
+    assert_eq!(missing.unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(
+        bad_symbols.unwrap_err().to_string(),
+        format!("{}: Invalid address of \"DATA\" at line 2", file("bad.sym"))
+    );
+
+    // The words are written into memory
+    let mut cpu = Lc3::new(0x3000);
+    program.load_into(&mut cpu).unwrap();
+    assert_eq!(cpu.get_memory(0x3001), 0xf025);
+    assert_eq!(cpu.get_memory(0x4000), 0x0048);
+}
+
+#[test]
+fn image_files() {
+    let directory = std::env::temp_dir().join(format!("loader-images-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file = |name: &str| directory.join(name).to_str().unwrap().to_owned();
+    fs::write(file("main.bin"), "0011000000000000\n1111000000100101\n").unwrap();
+    fs::write(file("data.hex"), ":04400000004800690B\n:00000001FF\n").unwrap();
+    fs::write(file("table.hex"), "5000\n0001\n0002\n").unwrap();
+
+    // The `.hex` files can be either Intel HEX or LC-3 hexadecimal text
+    let mut program = Program::default();
+    for name in ["main.bin", "data.hex", "table.hex"] {
+        program.add_object(&file(name)).unwrap();
+    }
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(
+        program
+            .segments
+            .iter()
+            .map(|segment| (segment.origin, segment.words.clone()))
+            .collect::<Vec<_>>(),
+        [
+            (0x3000, vec![0xf025]),
+            (0x4000, vec![0x0048, 0x0069]),
+            (0x5000, vec![0x0001, 0x0002]),
+        ]
+    );
+}
+
+#[test]
+fn assembly_sources() {
+    let directory = std::env::temp_dir().join(format!("loader-sources-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file = |name: &str| directory.join(name).to_str().unwrap().to_owned();
+    fs::write(file("main.asm"), ".orig x3000\nmain: HALT\n.end\n").unwrap();
+    fs::write(file("bad.asm"), ".orig x4000\n  ADDD R1, R1, R2\n").unwrap();
+
+    let mut program = Program::new(Isa::Lc3);
+    program.add_object(&file("main.asm")).unwrap();
+    let bad = program.add_object(&file("bad.asm")).unwrap_err();
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(program.segments[0].words, [0xf025]);
+    assert_eq!(
+        program.symbols.get(&0x3000).map(String::as_str),
+        Some("main")
+    );
+    assert_eq!(program.lines[&0x3000].line, 2);
+
+    // The errors in the sources keep the name of their file
+    let bad = bad
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<SourceError>()
+        .unwrap();
+    assert_eq!(bad.file, file("bad.asm"));
+}
@@ -1,15 +1,341 @@
-use architectures::lc2::Lc2;
-use emulator::{Emulator, InputThread};
+use architectures::{
+    common::{Profile, SymbolTable},
+    lc2::Lc2,
+    lc3::Lc3,
+};
+use emulator::{
+    assembly::SourceError,
+    cli::{Command, DisassembleOptions, GdbAddress, InputSource, Isa, Options, USAGE},
+    coverage::CoverageReport,
+    debugger::{Debuggable, Debugger},
+    gdb::GdbStub,
+    loader::{Program, Segment},
+    profile::ProfileReport,
+    statistics::write_summary,
+    trace::TraceWriter,
+    ExitStatus, InputThread,
+};
+use std::{
+    collections::VecDeque,
+    env,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    net::TcpListener,
+    process::ExitCode,
+    sync::{Arc, Mutex},
+};
 
-fn main() {
-    // Spawn an input thread
-    let input_thread = InputThread::spawn();
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
 
-    // Create a new LC2, set it up and load the binary
-    let mut cpu = Lc2::new(0x3000);
-    cpu.setup_memory(input_thread.get_buffer());
-    cpu.load_binary("test.obj").unwrap();
+fn main() -> ExitCode {
+    // Parse the command line arguments
+    let options = match Command::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Disassemble(options)) => {
+            let exit_status = match options.architecture {
+                Isa::Lc2 => disassemble(Lc2::new(0x3000), &options),
+                Isa::Lc3 => disassemble(Lc3::new(0x3000), &options),
+            };
+            return ExitCode::from(exit_status.code());
+        }
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("Error: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    // Run the binary
-    cpu.emulate(input_thread);
+    // Create a new CPU of the chosen architecture and run the program
+    let exit_status = match options.architecture {
+        Isa::Lc2 => run(Lc2::new(0x3000), &options),
+        Isa::Lc3 => run(Lc3::new(0x3000), &options),
+    };
+
+    ExitCode::from(exit_status.code())
+}
+
+fn run<E: Debuggable>(mut cpu: E, options: &Options) -> ExitStatus {
+    // Set the CPU up
+    let input_buffer = Arc::new(Mutex::new(VecDeque::<u8>::new()));
+    cpu.setup_memory(input_buffer.clone());
+
+    // Restore the snapshot, if any
+    if let Some(file_name) = &options.snapshot {
+        if let Err(error) = cpu.load_snapshot(file_name) {
+            eprintln!("Couldn't restore \"{file_name}\": {error}");
+            return ExitStatus::LoadError;
+        }
+    }
+
+    // Load the object files, using the origin of the first one as the entry
+    // point if it's not given and the machine isn't restored from a snapshot
+    let Some(program) = load_program(&mut cpu, options.architecture, &options.files) else {
+        return ExitStatus::LoadError;
+    };
+    let mut entry_point = options.entry_point;
+    if options.snapshot.is_none() {
+        entry_point =
+            entry_point.or_else(|| program.segments.first().map(|segment| segment.origin));
+    }
+    if let Some(entry_point) = entry_point {
+        cpu.set_entry_point(entry_point);
+    }
+
+    // In debug mode stdin is used for the commands, so the keyboard input
+    // either comes from a file or from the `input` command
+    if options.debug {
+        let _input_thread = match &options.input {
+            InputSource::Stdin => None,
+            InputSource::File(path) => match File::open(path) {
+                Ok(file) => Some(InputThread::from_reader(file, input_buffer.clone())),
+                Err(error) => {
+                    eprintln!("Couldn't open \"{}\": {error}", path.display());
+                    return ExitStatus::LoadError;
+                }
+            },
+        };
+
+        // Run the debugger
+        return Debugger::new(cpu, input_buffer, options.max_instructions)
+            .with_symbols(program.symbols)
+            .with_lines(program.lines)
+            .run(io::stdin().lock(), io::stdout())
+            .unwrap_or_else(|error| {
+                eprintln!("Debugger error: {error}");
+                ExitStatus::Interrupted
+            });
+    }
+
+    // Spawn an input thread reading from the chosen source
+    let input_thread = match &options.input {
+        InputSource::Stdin => InputThread::spawn(input_buffer),
+        InputSource::File(path) => match File::open(path) {
+            Ok(file) => InputThread::from_reader(file, input_buffer),
+            Err(error) => {
+                eprintln!("Couldn't open \"{}\": {error}", path.display());
+                return ExitStatus::LoadError;
+            }
+        },
+    };
+
+    // Let GDB control the program, keeping the input thread alive
+    if let Some(address) = &options.gdb {
+        let stub = GdbStub::new(cpu, options.max_instructions);
+        let exit_status = serve_gdb(stub, address).unwrap_or_else(|error| {
+            eprintln!("GDB stub error: {error}");
+            ExitStatus::Interrupted
+        });
+        drop(input_thread);
+        return exit_status;
+    }
+
+    emulate(cpu, options, input_thread, &program)
+}
+
+/// Run the program without a debugger, writing the trace, the coverage
+/// report, the profile and the statistics asked for in `options`
+fn emulate<E: Debuggable>(
+    mut cpu: E,
+    options: &Options,
+    input_thread: InputThread,
+    program: &Program,
+) -> ExitStatus {
+    // Create the trace, the coverage report and the profile, if any
+    let Ok(trace_file) = create_output(options.trace.as_deref()) else {
+        return ExitStatus::LoadError;
+    };
+    let Ok(coverage) = create_output(options.coverage.as_deref()) else {
+        return ExitStatus::LoadError;
+    };
+    let Ok(profile) = create_output(options.profile.as_deref()) else {
+        return ExitStatus::LoadError;
+    };
+    let mut trace = trace_file.map(|file| {
+        TraceWriter::new(file, options.trace_format).with_symbols(program.symbols.clone())
+    });
+    cpu.set_tracing(trace.is_some());
+    cpu.set_coverage(coverage.is_some());
+    cpu.set_profiling(profile.is_some());
+
+    // Run the binary, stopping the trace if it can't be written
+    let exit_status = cpu.emulate(input_thread, options.max_instructions, |cpu| {
+        if let Some(writer) = &mut trace {
+            if let Err(error) = writer.write(cpu) {
+                eprintln!("Couldn't write the trace: {error}");
+                trace = None;
+            }
+        }
+    });
+    if let Some(Err(error)) = trace.as_mut().map(TraceWriter::flush) {
+        eprintln!("Couldn't write the trace: {error}");
+    }
+
+    // Write the coverage report and print its summary after the output of
+    // the program
+    if let Some(output) = coverage {
+        if let Err(error) = write_coverage(&mut cpu, program, output) {
+            eprintln!("Couldn't write the coverage: {error}");
+        }
+    }
+
+    // Write the collapsed stacks and print the report of the routines after
+    // the output of the program
+    if let Some(output) = profile {
+        if let Err(error) = write_profile(&cpu, &program.symbols, output) {
+            eprintln!("Couldn't write the profile: {error}");
+        }
+    }
+
+    // Print the statistics after the output of the program
+    if options.statistics {
+        let _ = io::stdout().flush();
+        let _ = write_summary(&cpu, io::stderr().lock());
+    }
+
+    exit_status
+}
+
+/// Create the output file `file_name`, if any, printing an error if it can't
+/// be created
+fn create_output(file_name: Option<&str>) -> io::Result<Option<BufWriter<File>>> {
+    file_name
+        .map(|file_name| {
+            File::create(file_name)
+                .map(BufWriter::new)
+                .inspect_err(|error| {
+                    eprintln!("Couldn't create \"{file_name}\": {error}");
+                })
+        })
+        .transpose()
+}
+
+/// Write the lcov report of the coverage of the object files of `program` to
+/// `output`, and print its summary
+fn write_coverage<E: Debuggable>(
+    cpu: &mut E,
+    program: &Program,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let coverage = cpu.coverage().cloned().unwrap_or_default();
+    let files = program.files();
+    let mut report = CoverageReport::new(&coverage, &files).with_symbols(&program.symbols);
+    if !program.lines.is_empty() {
+        report = report.with_lines(&program.lines);
+    }
+    report.write_lcov(&mut output)?;
+    output.flush()?;
+
+    let _ = io::stdout().flush();
+    report.write_summary(cpu, io::stderr().lock())
+}
+
+/// Write the collapsed stacks of the profile to `output`, and print the
+/// report of the routines, named with their labels in `symbols`
+fn write_profile<E: Debuggable>(
+    cpu: &E,
+    symbols: &SymbolTable,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let stacks = cpu.profile().map(Profile::stacks).unwrap_or_default();
+    let report = ProfileReport::new(&stacks).with_symbols(symbols);
+    report.write_collapsed(&mut output)?;
+    output.flush()?;
+
+    let _ = io::stdout().flush();
+    report.write_report(io::stderr().lock())
+}
+
+/// Print the disassembly of the object files, or of the range of addresses
+/// in the options
+fn disassemble<E: Debuggable>(mut cpu: E, options: &DisassembleOptions) -> ExitStatus {
+    // Set the CPU up, so that the operating system can be disassembled too
+    cpu.setup_memory(Arc::new(Mutex::new(VecDeque::new())));
+
+    // Load the object files, and disassemble the addresses they occupy unless
+    // a range is given
+    let Some(program) = load_program(&mut cpu, options.architecture, &options.files) else {
+        return ExitStatus::LoadError;
+    };
+    let ranges = options.range.map_or_else(
+        || program.segments.iter().map(Segment::addresses).collect(),
+        |(start, end)| vec![start..=end],
+    );
+
+    // Print an instruction per line, stopping if stdout is closed
+    let mut stdout = io::stdout().lock();
+    for address in ranges.into_iter().flatten() {
+        let word = cpu.peek_memory(address);
+        let text = E::disassemble(word, address, Some(&program.symbols));
+        let label = program
+            .symbols
+            .get(&address)
+            .map_or_else(String::new, |label| format!("{label}:\n"));
+        if writeln!(stdout, "{label}x{address:04X}  x{word:04X}  {text}").is_err() {
+            break;
+        }
+    }
+
+    ExitStatus::Halted
+}
+
+/// Read the object files `files` and their symbol files, assembling the
+/// sources for `architecture`, and load them into the memory of `cpu`,
+/// printing an error if one of them can't be loaded
+fn load_program<E: Debuggable>(
+    cpu: &mut E,
+    architecture: Isa,
+    files: &[String],
+) -> Option<Program> {
+    let mut program = Program::new(architecture);
+    for file_name in files {
+        if let Err(error) = program.add_object(file_name) {
+            // The errors in the sources already name their file
+            match error
+                .get_ref()
+                .and_then(|error| error.downcast_ref::<SourceError>())
+            {
+                Some(error) => eprintln!("Error: {error}"),
+                None => eprintln!("Couldn't load \"{file_name}\": {error}"),
+            }
+            return None;
+        }
+    }
+    if let Err(error) = program.load_into(cpu) {
+        eprintln!("Couldn't load the program: {error}");
+        return None;
+    }
+
+    Some(program)
+}
+
+/// Wait for a GDB connection on `address` and serve it
+fn serve_gdb<E: Debuggable>(mut stub: GdbStub<E>, address: &GdbAddress) -> io::Result<ExitStatus> {
+    match address {
+        GdbAddress::Tcp(address) => {
+            let listener = TcpListener::bind(address)?;
+            eprintln!("Waiting for GDB on {}...", listener.local_addr()?);
+            let (stream, _) = listener.accept()?;
+            stream.set_nodelay(true)?;
+            stub.run(stream)
+        }
+
+        #[cfg(unix)]
+        GdbAddress::Unix(path) => {
+            let listener = UnixListener::bind(path)?;
+            eprintln!("Waiting for GDB on {}...", path.display());
+            let result = listener.accept().and_then(|(stream, _)| stub.run(stream));
+            let _ = fs::remove_file(path);
+            result
+        }
+
+        #[cfg(not(unix))]
+        GdbAddress::Unix(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Unix sockets are not supported on this platform",
+        )),
+    }
 }
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use architectures::common::{StackProfile, SymbolTable};
+
+use crate::statistics::hundredths;
+
+/// Name of the stack of the instructions executed outside any routine
+const TOP: &str = "[top]";
+
+/// Instructions executed by a routine and by the routines it has called
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+struct RoutineProfile {
+    /// Instructions executed with the routine anywhere in the call stack
+    inclusive: u64,
+    /// Instructions executed by the routine itself
+    exclusive: u64,
+    calls: u64,
+}
+
+/// Report of the instructions executed by each call stack
+pub struct ProfileReport<'a> {
+    stacks: &'a BTreeMap<Vec<u16>, StackProfile>,
+    symbols: Option<&'a SymbolTable>,
+}
+
+impl<'a> ProfileReport<'a> {
+    /// Report the call stacks in `stacks`, from the outermost routine to the
+    /// innermost one
+    #[must_use]
+    pub const fn new(stacks: &'a BTreeMap<Vec<u16>, StackProfile>) -> Self {
+        Self {
+            stacks,
+            symbols: None,
+        }
+    }
+
+    /// Name the routines with the labels in `symbols`
+    #[must_use]
+    pub const fn with_symbols(mut self, symbols: &'a SymbolTable) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    /// Write the stacks in the collapsed format read by the flame graph
+    /// tools, with a line for each stack that has executed an instruction
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write_collapsed<W: Write>(&self, mut output: W) -> io::Result<()> {
+        for (stack, profile) in self.stacks {
+            if profile.instructions == 0 {
+                continue;
+            }
+
+            let mut names = vec![TOP.to_owned()];
+            names.extend(stack.iter().map(|routine| self.name(*routine)));
+            writeln!(output, "{} {}", names.join(";"), profile.instructions)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a table of the routines, sorted by the instructions they have
+    /// executed including the ones of the routines they have called
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write_report<W: Write>(&self, mut output: W) -> io::Result<()> {
+        let total = self
+            .stacks
+            .values()
+            .map(|profile| profile.instructions)
+            .sum::<u64>();
+
+        // A recursive routine counts the instructions of a stack only once
+        let mut routines: BTreeMap<u16, RoutineProfile> = BTreeMap::new();
+        for (stack, profile) in self.stacks {
+            for (index, routine) in stack.iter().enumerate() {
+                if !stack[..index].contains(routine) {
+                    routines.entry(*routine).or_default().inclusive += profile.instructions;
+                }
+            }
+            if let Some(routine) = stack.last() {
+                let routine = routines.entry(*routine).or_default();
+                routine.exclusive += profile.instructions;
+                routine.calls += profile.calls;
+            }
+        }
+        let mut routines = routines.into_iter().collect::<Vec<_>>();
+        routines.sort_by_key(|(_, routine)| Reverse(routine.inclusive));
+
+        writeln!(output, "Profile of {total} instructions:")?;
+        writeln!(
+            output,
+            "  {:>19}  {:>19}  {:>8}  Routine",
+            "Inclusive", "Exclusive", "Calls"
+        )?;
+        let percentage = |count: u64| {
+            if total == 0 {
+                String::new()
+            } else {
+                format!("{}%", hundredths(count * 100, total))
+            }
+        };
+        for (routine, profile) in routines {
+            writeln!(
+                output,
+                "  {:>10}  {:>7}  {:>10}  {:>7}  {:>8}  {}",
+                profile.inclusive,
+                percentage(profile.inclusive),
+                profile.exclusive,
+                percentage(profile.exclusive),
+                profile.calls,
+                self.name(routine)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the label of the routine at `address`, or its address
+    fn name(&self, address: u16) -> String {
+        self.symbols
+            .and_then(|symbols| symbols.get(&address))
+            .map_or_else(|| format!("x{address:04X}"), Clone::clone)
+    }
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+/// Stacks of a program whose MAIN routine calls SORT twice, which calls
+/// itself once and the unnamed routine at x3100 once
+fn stacks() -> BTreeMap<Vec<u16>, StackProfile> {
+    let profile = |instructions, calls| StackProfile {
+        instructions,
+        calls,
+    };
+    [
+        (vec![], profile(2, 0)),
+        (vec![0x3000], profile(10, 1)),
+        (vec![0x3000, 0x3050], profile(20, 2)),
+        (vec![0x3000, 0x3050, 0x3050], profile(8, 1)),
+        (vec![0x3000, 0x3050, 0x3100], profile(0, 1)),
+    ]
+    .into()
+}
+
+fn symbols() -> SymbolTable {
+    [(0x3000, "MAIN".to_owned()), (0x3050, "SORT".to_owned())].into()
+}
+
+#[test]
+fn collapsed() {
+    // The stacks that haven't executed an instruction are not written
+    let (stacks, symbols) = (stacks(), symbols());
+    let mut output = Vec::new();
+    ProfileReport::new(&stacks)
+        .with_symbols(&symbols)
+        .write_collapsed(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[top] 2\n[top];MAIN 10\n[top];MAIN;SORT 20\n[top];MAIN;SORT;SORT 8\n"
+    );
+}
+
+#[test]
+fn collapsed_without_symbols() {
+    let stacks = stacks();
+    let mut output = Vec::new();
+    ProfileReport::new(&stacks)
+        .write_collapsed(&mut output)
+        .unwrap();
+
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("[top];x3000;x3050;x3050 8\n"));
+}
+
+#[test]
+fn report() {
+    let (stacks, symbols) = (stacks(), symbols());
+    let mut output = Vec::new();
+    ProfileReport::new(&stacks)
+        .with_symbols(&symbols)
+        .write_report(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Profile of 40 instructions:\n\
+         \x20           Inclusive            Exclusive     Calls  Routine\n\
+         \x20         38   95.00%          10   25.00%         1  MAIN\n\
+         \x20         28   70.00%          28   70.00%         3  SORT\n\
+         \x20          0    0.00%           0    0.00%         1  x3100\n"
+    );
+}
+
+#[test]
+fn empty_report() {
+    let stacks = BTreeMap::new();
+    let mut output = Vec::new();
+    ProfileReport::new(&stacks)
+        .write_report(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Profile of 0 instructions:\n\
+         \x20           Inclusive            Exclusive     Calls  Routine\n"
+    );
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests;
+
+use std::io::{self, Write};
+
+use crate::debugger::Debuggable;
+
+/// Write a summary of the statistics of `cpu`, with the opcodes sorted from
+/// the most executed one
+///
+/// # Errors
+///
+/// This function will return an `Err` if the output can't be written
+pub fn write_summary<E: Debuggable, W: Write>(cpu: &E, mut output: W) -> io::Result<()> {
+    let statistics = cpu.statistics();
+    let instructions = statistics.instructions;
+
+    writeln!(output, "Instructions:   {instructions}")?;
+    write!(output, "Cycles:         {}", statistics.cycles)?;
+    if instructions > 0 {
+        write!(
+            output,
+            " ({} per instruction)",
+            hundredths(statistics.cycles, instructions)
+        )?;
+    }
+    writeln!(output)?;
+    writeln!(output, "Memory reads:   {}", statistics.memory_reads)?;
+    writeln!(output, "Memory writes:  {}", statistics.memory_writes)?;
+    writeln!(
+        output,
+        "Branches:       {} taken, {} not taken",
+        statistics.branches_taken, statistics.branches_not_taken
+    )?;
+    writeln!(output, "Interrupts:     {}", statistics.interrupts)?;
+
+    // Name the traps as the disassembler does
+    if !statistics.traps.is_empty() {
+        writeln!(output, "Traps:")?;
+        for (vector, count) in &statistics.traps {
+            let text = E::disassemble(0xf000 | u16::from(*vector), 0, None);
+            writeln!(output, "  x{vector:02X}  {text:<10} {count:>10}")?;
+        }
+    }
+
+    // Sort the opcodes by the number of executions, keeping the order of the
+    // encoding for the ones executed the same number of times
+    let mut opcodes = E::OPCODES
+        .iter()
+        .zip(statistics.opcodes)
+        .filter(|(_, count)| *count > 0)
+        .collect::<Vec<_>>();
+    opcodes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    if !opcodes.is_empty() {
+        writeln!(output, "Opcodes:")?;
+        for (name, count) in opcodes {
+            writeln!(
+                output,
+                "  {name:<10} {count:>10}  {:>6}%",
+                hundredths(count * 100, instructions)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Divide `dividend` by `divisor`, which must not be zero, and write the
+/// result rounded to two decimal places
+pub(crate) fn hundredths(dividend: u64, divisor: u64) -> String {
+    let result = (u128::from(dividend) * 100 + u128::from(divisor) / 2) / u128::from(divisor);
+    format!("{}.{:02}", result / 100, result % 100)
+}
@@ -0,0 +1,78 @@
+use super::*;
+use architectures::{lc2::Lc2, lc3::Lc3};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// Execute `program` from x3000, returning the summary of the statistics
+fn summary<E: Debuggable>(mut cpu: E, program: &[u16]) -> String {
+    cpu.setup_memory(Arc::new(Mutex::new(VecDeque::new())));
+    for (address, instruction) in (0x3000..).zip(program) {
+        cpu.set_memory(address, *instruction);
+    }
+    cpu.set_program_counter(0x3000);
+    for _ in program {
+        cpu.step_instruction().unwrap();
+    }
+
+    let mut output = Vec::new();
+    write_summary(&cpu, &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn lc2() {
+    // ADD R1, R1, #1
+    // ADD R1, R1, #1
+    // BRp x3003
+    // NOT R1, R1
+    let output = summary(Lc2::new(0x3000), &[0x1261, 0x1261, 0x0203, 0x927f]);
+
+    assert_eq!(
+        output,
+        "Instructions:   4\n\
+         Cycles:         36 (9.00 per instruction)\n\
+         Memory reads:   4\n\
+         Memory writes:  0\n\
+         Branches:       1 taken, 0 not taken\n\
+         Interrupts:     0\n\
+         Opcodes:\n  \
+           ADD                 2   50.00%\n  \
+           BR                  1   25.00%\n  \
+           NOT                 1   25.00%\n"
+    );
+}
+
+#[test]
+fn traps() {
+    // TRAP x25, whose routine is the one of the operating system, that
+    // pushes the Processor Status Register and the Program Counter
+    let output = summary(Lc3::new(0x3000), &[0xf025]);
+
+    assert!(output.contains("Memory reads:   2\nMemory writes:  2\n"));
+    assert!(output.contains("Traps:\n  x25  HALT                1\n"));
+    assert!(output.contains("  TRAP                1  100.00%\n"));
+}
+
+#[test]
+fn empty() {
+    let output = summary(Lc3::new(0x3000), &[]);
+
+    assert_eq!(
+        output,
+        "Instructions:   0\n\
+         Cycles:         0\n\
+         Memory reads:   0\n\
+         Memory writes:  0\n\
+         Branches:       0 taken, 0 not taken\n\
+         Interrupts:     0\n"
+    );
+}
+
+#[test]
+fn rounding() {
+    assert_eq!(hundredths(1, 3), "0.33");
+    assert_eq!(hundredths(2, 3), "0.67");
+    assert_eq!(hundredths(200, 1), "200.00");
+}
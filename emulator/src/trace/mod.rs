@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use architectures::{
+    common::{ConditionCode, SymbolTable},
+    WatcherType,
+};
+
+use crate::{cli::TraceFormat, debugger::Debuggable};
+
+/// Writer of the trace of the executed instructions, with a line per
+/// instruction
+pub struct TraceWriter<W: Write> {
+    output: W,
+    format: TraceFormat,
+    symbols: SymbolTable,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub const fn new(output: W, format: TraceFormat) -> Self {
+        Self {
+            output,
+            format,
+            symbols: SymbolTable::new(),
+        }
+    }
+
+    /// Show the labels in `symbols` in the disassembly of the instructions
+    #[must_use]
+    pub fn with_symbols(mut self, symbols: SymbolTable) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Write the record of the last instruction executed by `cpu`, whose
+    /// tracing must be enabled. Nothing is written if there is no record
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn write<E: Debuggable>(&mut self, cpu: &E) -> io::Result<()> {
+        let Some(record) = cpu.last_trace() else {
+            return Ok(());
+        };
+
+        // Name the registers as the debugger does
+        let names = E::registers();
+        let name = |register: &E::Register| {
+            names
+                .iter()
+                .find(|(_, named)| named == register)
+                .map_or_else(|| format!("{register:?}"), |(name, _)| (*name).to_owned())
+        };
+        let text = E::disassemble(record.instruction, record.address, Some(&self.symbols));
+        let condition_code = condition_code_name(&record.condition_code);
+
+        match self.format {
+            // x3001: x6680  LDR R3, R2, #0  R3=x0005 [x4000]->x0005 CC=P
+            TraceFormat::Text => {
+                write!(
+                    self.output,
+                    "x{:04X}: x{:04X}  {text}",
+                    record.address, record.instruction
+                )?;
+                for (register, data) in &record.registers {
+                    write!(self.output, "  {}=x{data:04X}", name(register))?;
+                }
+                for access in &record.memory {
+                    let arrow = match access.access_type {
+                        WatcherType::OnRead => "->",
+                        WatcherType::OnWrite => "<-",
+                    };
+                    write!(
+                        self.output,
+                        "  [x{:04X}]{arrow}x{:04X}",
+                        access.address, access.data
+                    )?;
+                }
+                writeln!(self.output, "  CC={condition_code}")
+            }
+
+            // {"pc":12289,"ir":26240,"asm":"LDR R3, R2, #0","registers":[["R3",5]],
+            //  "memory":[["read",16384,5]],"cc":"P"}
+            TraceFormat::JsonLines => {
+                let registers = record
+                    .registers
+                    .iter()
+                    .map(|(register, data)| format!("[{},{data}]", json_string(&name(register))))
+                    .collect::<Vec<String>>()
+                    .join(",");
+                let memory = record
+                    .memory
+                    .iter()
+                    .map(|access| {
+                        let access_type = match access.access_type {
+                            WatcherType::OnRead => "read",
+                            WatcherType::OnWrite => "write",
+                        };
+                        format!("[\"{access_type}\",{},{}]", access.address, access.data)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(",");
+                writeln!(
+                    self.output,
+                    "{{\"pc\":{},\"ir\":{},\"asm\":{},\"registers\":[{registers}],\"memory\":[{memory}],\"cc\":\"{condition_code}\"}}",
+                    record.address,
+                    record.instruction,
+                    json_string(&text),
+                )
+            }
+        }
+    }
+
+    /// Flush the output
+    ///
+    /// # Errors
+    ///
+    /// This method will return an `Err` if the output can't be written
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Get the letter of a Condition Code
+#[must_use]
+pub const fn condition_code_name(condition_code: &ConditionCode) -> &'static str {
+    match condition_code {
+        ConditionCode::Negative => "N",
+        ConditionCode::Zero => "Z",
+        ConditionCode::Positive => "P",
+    }
+}
+
+/// Quote and escape `text` as a JSON string
+fn json_string(text: &str) -> String {
+    let mut string = String::with_capacity(text.len() + 2);
+    string.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => string.push_str("\\\""),
+            '\\' => string.push_str("\\\\"),
+            '\n' => string.push_str("\\n"),
+            character if character.is_control() => {
+                let _ = write!(string, "\\u{:04x}", u32::from(character));
+            }
+            character => string.push(character),
+        }
+    }
+    string.push('"');
+    string
+}
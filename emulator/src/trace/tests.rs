@@ -0,0 +1,61 @@
+use super::*;
+use architectures::{lc2::Lc2, lc3::Lc3};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// Execute `program` from x3000 with R1 = 5 and R2 = x4000, returning the
+/// trace in `format`
+fn trace<E: Debuggable>(mut cpu: E, program: &[u16], format: TraceFormat) -> String {
+    cpu.setup_memory(Arc::new(Mutex::new(VecDeque::new())));
+    for (address, instruction) in (0x3000..).zip(program) {
+        cpu.set_memory(address, *instruction);
+    }
+    cpu.set_program_counter(0x3000);
+    cpu.set_register(&E::registers()[1].1, 5).unwrap();
+    cpu.set_register(&E::registers()[2].1, 0x4000).unwrap();
+    cpu.set_tracing(true);
+
+    let mut writer = TraceWriter::new(Vec::new(), format);
+    for _ in program {
+        cpu.step_instruction().unwrap();
+        writer.write(&cpu).unwrap();
+    }
+
+    String::from_utf8(writer.output).unwrap()
+}
+
+#[test]
+fn text() {
+    // STR R1, R2, #0
+    // LDR R3, R2, #0
+    let output = trace(Lc2::new(0x3000), &[0x7280, 0x6680], TraceFormat::Text);
+
+    assert_eq!(
+        output,
+        "x3000: x7280  STR R1, R2, #0  [x4000]<-x0005  CC=P\n\
+         x3001: x6680  LDR R3, R2, #0  R3=x0005  [x4000]->x0005  CC=P\n"
+    );
+}
+
+#[test]
+fn json_lines() {
+    // STR R1, R2, #0
+    // LDR R3, R2, #0
+    let output = trace(Lc3::new(0x3000), &[0x7280, 0x6680], TraceFormat::JsonLines);
+
+    assert_eq!(
+        output,
+        "{\"pc\":12288,\"ir\":29312,\"asm\":\"STR R1, R2, #0\",\"registers\":[],\"memory\":[[\"write\",16384,5]],\"cc\":\"P\"}\n\
+         {\"pc\":12289,\"ir\":26240,\"asm\":\"LDR R3, R2, #0\",\"registers\":[[\"R3\",5]],\"memory\":[[\"read\",16384,5]],\"cc\":\"P\"}\n"
+    );
+}
+
+#[test]
+fn escape() {
+    assert_eq!(
+        json_string("\"a\\b\"\n\u{1}"),
+        "\"\\\"a\\\\b\\\"\\n\\u0001\""
+    );
+}